@@ -67,6 +67,12 @@ impl SpeedMode {
         ]
     }
 
+    /// Whether PROG START in this mode runs until WAIT or stop
+    /// (the other execution modes advance one step per press)
+    pub fn runs_continuously(&self) -> bool {
+        matches!(self, SpeedMode::Run | SpeedMode::IntRun)
+    }
+
     /// Create from index (0-6)
     pub fn from_index(index: usize) -> Option<SpeedMode> {
         match index {
@@ -82,6 +88,21 @@ impl SpeedMode {
     }
 }
 
+/// Instructions executed per PROG START batch in the continuous run modes
+pub const RUN_BATCH: u64 = 10_000;
+
+/// How many instructions one batch executes in the given speed mode
+///
+/// SS, SMC and SI stop at the next instruction boundary, so they run one
+/// instruction; RUN and INT RUN run a large batch; DISP and LOAD execute nothing.
+pub fn speed_to_batch(mode: SpeedMode) -> u64 {
+    match mode {
+        SpeedMode::SS | SpeedMode::SMC | SpeedMode::SI => 1,
+        SpeedMode::Run | SpeedMode::IntRun => RUN_BATCH,
+        SpeedMode::Disp | SpeedMode::Load => 0,
+    }
+}
+
 #[derive(Properties, PartialEq)]
 pub struct CircularKnobProps {
     /// Current position of the knob
@@ -289,4 +310,26 @@ mod tests {
     fn test_default_mode() {
         assert_eq!(SpeedMode::default(), SpeedMode::Run);
     }
+
+    #[test]
+    fn test_speed_to_batch() {
+        assert_eq!(speed_to_batch(SpeedMode::SS), 1);
+        assert_eq!(speed_to_batch(SpeedMode::SMC), 1);
+        assert_eq!(speed_to_batch(SpeedMode::SI), 1);
+        assert_eq!(speed_to_batch(SpeedMode::Run), RUN_BATCH);
+        assert_eq!(speed_to_batch(SpeedMode::IntRun), RUN_BATCH);
+        assert_eq!(speed_to_batch(SpeedMode::Disp), 0);
+        assert_eq!(speed_to_batch(SpeedMode::Load), 0);
+    }
+
+    #[test]
+    fn test_speed_to_batch_matches_continuous_modes() {
+        for mode in SpeedMode::all() {
+            if mode.runs_continuously() {
+                assert!(speed_to_batch(mode) > 1);
+            } else {
+                assert!(speed_to_batch(mode) <= 1);
+            }
+        }
+    }
 }
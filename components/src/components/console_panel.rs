@@ -10,6 +10,7 @@ use crate::components::circular_knob::{CircularKnob, SpeedMode};
 use crate::components::emergency_stop::EmergencyStop;
 use crate::components::indicator_lights::{RegisterDisplay, ControlDisplay};
 use crate::components::power_switch::PowerSwitch;
+use crate::components::register_panel::acc_flags;
 use crate::components::sixteen_bit_panel::SixteenBitPanel;
 
 /// IBM 1130 Register State
@@ -79,13 +80,23 @@ pub enum ConsoleAction {
     TogglePower,
     SetLampTest(bool),
     Load,
+    /// LOAD ACC: copy the switches into the accumulator
+    LoadAcc,
     Deposit,
     DepositNext,
-    Examine,
-    ExamineNext,
+    /// EXAMINE with the word read from memory[IAR]
+    Examine(u16),
+    /// EXAMINE NEXT with the word read from memory[IAR + 1]
+    ExamineNext(u16),
     Reset,
     ToggleRunning,
+    /// PROG START: enter the run state if the speed knob runs continuously
+    Start,
+    /// PROG STOP / IMM STOP
+    Stop,
     UpdateRegisters(Registers),
+    /// Control/status indicators from the CPU; WAIT ends the run state
+    UpdateControl(ControlState),
     ToggleHelp,
     SetHelpActive(bool),
 }
@@ -117,6 +128,11 @@ impl Reducible for ConsoleState {
                     new_state.registers.iar = new_state.switches;
                 }
             }
+            ConsoleAction::LoadAcc => {
+                if new_state.power_on {
+                    new_state.registers.acc = new_state.switches;
+                }
+            }
             ConsoleAction::Deposit => {
                 if new_state.power_on {
                     new_state.registers.sbr = new_state.switches;
@@ -128,15 +144,17 @@ impl Reducible for ConsoleState {
                     new_state.registers.sbr = new_state.switches;
                 }
             }
-            ConsoleAction::Examine => {
+            ConsoleAction::Examine(word) => {
                 if new_state.power_on {
                     new_state.registers.sar = new_state.registers.iar;
+                    new_state.registers.sbr = word;
                 }
             }
-            ConsoleAction::ExamineNext => {
+            ConsoleAction::ExamineNext(word) => {
                 if new_state.power_on {
                     new_state.registers.iar = new_state.registers.iar.wrapping_add(1);
                     new_state.registers.sar = new_state.registers.iar;
+                    new_state.registers.sbr = word;
                 }
             }
             ConsoleAction::Reset => {
@@ -148,9 +166,23 @@ impl Reducible for ConsoleState {
                     new_state.running = !new_state.running;
                 }
             }
+            ConsoleAction::Start => {
+                if new_state.power_on {
+                    new_state.running = new_state.speed_mode.runs_continuously();
+                }
+            }
+            ConsoleAction::Stop => {
+                new_state.running = false;
+            }
             ConsoleAction::UpdateRegisters(regs) => {
                 new_state.registers = regs;
             }
+            ConsoleAction::UpdateControl(control) => {
+                new_state.control = control;
+                if control.wait {
+                    new_state.running = false;
+                }
+            }
             ConsoleAction::ToggleHelp => {
                 new_state.help_active = !new_state.help_active;
             }
@@ -163,20 +195,49 @@ impl Reducible for ConsoleState {
     }
 }
 
+/// DEPOSIT (or DEPOSIT NEXT): write the switches to memory[IAR] (or IAR + 1)
+/// through `on_deposit`, returning the action that updates the console lights
+fn deposit(state: &ConsoleState, next: bool, on_deposit: &Callback<(u16, u16)>) -> ConsoleAction {
+    let addr = if next { state.registers.iar.wrapping_add(1) } else { state.registers.iar };
+    if state.power_on {
+        on_deposit.emit((addr, state.switches));
+    }
+    if next { ConsoleAction::DepositNext } else { ConsoleAction::Deposit }
+}
+
+/// EXAMINE (or EXAMINE NEXT): read memory[IAR] (or IAR + 1) through
+/// `on_examine`, returning the action that shows the word in SBR
+fn examine(state: &ConsoleState, next: bool, on_examine: &Callback<u16, u16>) -> ConsoleAction {
+    let addr = if next { state.registers.iar.wrapping_add(1) } else { state.registers.iar };
+    let word = if state.power_on { on_examine.emit(addr) } else { state.registers.sbr };
+    if next { ConsoleAction::ExamineNext(word) } else { ConsoleAction::Examine(word) }
+}
+
 #[derive(Properties, PartialEq)]
 pub struct ConsolePanelProps {
     #[prop_or_default]
     pub on_state_change: Callback<ConsoleState>,
     #[prop_or_default]
     pub external_registers: Option<Registers>,
+    /// Control/status indicators driven by the CPU (WAIT, carry, overflow)
+    #[prop_or_default]
+    pub external_control: Option<ControlState>,
     #[prop_or_default]
     pub on_load: Callback<u16>,
+    /// LOAD ACC pressed with the switch value to put in the accumulator
     #[prop_or_default]
-    pub on_deposit: Callback<(u16, u16)>,
+    pub on_load_acc: Callback<u16>,
+    /// Write (address, value) to memory
     #[prop_or_default]
-    pub on_examine: Callback<u16>,
+    pub on_deposit: Callback<(u16, u16)>,
+    /// Read the word at an address from memory
+    #[prop_or(Callback::from(|_| 0))]
+    pub on_examine: Callback<u16, u16>,
     #[prop_or_default]
     pub on_start_stop: Callback<bool>,
+    /// PROG START pressed, with the speed knob position selecting how far to run
+    #[prop_or_default]
+    pub on_program_start: Callback<SpeedMode>,
     #[prop_or_default]
     pub on_reset: Callback<()>,
     /// External help state (controlled from header)
@@ -202,6 +263,17 @@ pub fn console_panel(props: &ConsolePanelProps) -> Html {
         });
     }
 
+    {
+        let state = state.clone();
+        let external_control = props.external_control;
+        use_effect_with(external_control, move |control| {
+            if let Some(control) = control {
+                state.dispatch(ConsoleAction::UpdateControl(*control));
+            }
+            || ()
+        });
+    }
+
     let on_switch_change = {
         let state = state.clone();
         Callback::from(move |value: u16| {
@@ -248,21 +320,30 @@ pub fn console_panel(props: &ConsolePanelProps) -> Html {
         })
     };
 
-    let _on_deposit = {
+    let on_load_acc = {
+        let state = state.clone();
+        let callback = props.on_load_acc.clone();
+        Callback::from(move |_: MouseEvent| {
+            if state.power_on {
+                state.dispatch(ConsoleAction::LoadAcc);
+                callback.emit(state.switches);
+            }
+        })
+    };
+
+    let on_deposit = {
         let state = state.clone();
         let callback = props.on_deposit.clone();
         Callback::from(move |_: MouseEvent| {
-            state.dispatch(ConsoleAction::Deposit);
-            callback.emit((state.registers.iar, state.switches));
+            state.dispatch(deposit(&state, false, &callback));
         })
     };
 
-    let _on_deposit_next = {
+    let on_deposit_next = {
         let state = state.clone();
         let callback = props.on_deposit.clone();
         Callback::from(move |_: MouseEvent| {
-            state.dispatch(ConsoleAction::DepositNext);
-            callback.emit((state.registers.iar.wrapping_add(1), state.switches));
+            state.dispatch(deposit(&state, true, &callback));
         })
     };
 
@@ -270,17 +351,15 @@ pub fn console_panel(props: &ConsolePanelProps) -> Html {
         let state = state.clone();
         let callback = props.on_examine.clone();
         Callback::from(move |_: MouseEvent| {
-            state.dispatch(ConsoleAction::Examine);
-            callback.emit(state.registers.iar);
+            state.dispatch(examine(&state, false, &callback));
         })
     };
 
-    let _on_examine_next = {
+    let on_examine_next = {
         let state = state.clone();
         let callback = props.on_examine.clone();
         Callback::from(move |_: MouseEvent| {
-            state.dispatch(ConsoleAction::ExamineNext);
-            callback.emit(state.registers.iar.wrapping_add(1));
+            state.dispatch(examine(&state, true, &callback));
         })
     };
 
@@ -296,10 +375,12 @@ pub fn console_panel(props: &ConsolePanelProps) -> Html {
     let on_start = {
         let state = state.clone();
         let callback = props.on_start_stop.clone();
+        let on_program_start = props.on_program_start.clone();
         Callback::from(move |_: MouseEvent| {
             if !state.running {
-                state.dispatch(ConsoleAction::ToggleRunning);
+                state.dispatch(ConsoleAction::Start);
                 callback.emit(true);
+                on_program_start.emit(state.speed_mode);
             }
         })
     };
@@ -309,13 +390,14 @@ pub fn console_panel(props: &ConsolePanelProps) -> Html {
         let callback = props.on_start_stop.clone();
         Callback::from(move |_: MouseEvent| {
             if state.running {
-                state.dispatch(ConsoleAction::ToggleRunning);
+                state.dispatch(ConsoleAction::Stop);
                 callback.emit(false);
             }
         })
     };
 
     let button_disabled = !state.power_on;
+    let flags = acc_flags(state.registers.acc);
 
     html! {
         <div class="console-panel">
@@ -349,6 +431,9 @@ pub fn console_panel(props: &ConsolePanelProps) -> Html {
                         indirect={state.control.indirect}
                         carry={state.control.carry}
                         overflow={state.control.overflow}
+                        positive={flags.positive}
+                        zero={flags.zero}
+                        negative={flags.negative}
                         lamp_test={state.lamp_test}
                         power_on={state.power_on}
                     />
@@ -464,6 +549,14 @@ pub fn console_panel(props: &ConsolePanelProps) -> Html {
                     // Row 4: LOAD IAR, PROGRAM LOAD
                     <button class="console-btn blue" onclick={on_load.clone()} disabled={button_disabled}>{"LOAD IAR"}</button>
                     <button class="console-btn blue" onclick={on_examine.clone()} disabled={button_disabled}>{"PROG LOAD"}</button>
+                    // Row 5: DEPOSIT, DEPOSIT NEXT (key a program in from the switches)
+                    <button class="console-btn yellow" onclick={on_deposit} disabled={button_disabled}>{"DEPOSIT"}</button>
+                    <button class="console-btn yellow" onclick={on_deposit_next} disabled={button_disabled}>{"DEPOSIT NEXT"}</button>
+                    // Row 6: EXAMINE, EXAMINE NEXT
+                    <button class="console-btn cyan" onclick={on_examine} disabled={button_disabled}>{"EXAMINE"}</button>
+                    <button class="console-btn cyan" onclick={on_examine_next} disabled={button_disabled}>{"EXAMINE NEXT"}</button>
+                    // Row 7: LOAD ACC (switches into the accumulator)
+                    <button class="console-btn blue" onclick={on_load_acc} disabled={button_disabled}>{"LOAD ACC"}</button>
                 </div>
             </div>
 
@@ -579,4 +672,192 @@ mod tests {
         let new_state = state.reduce(ConsoleAction::Load);
         assert_eq!(new_state.registers.iar, 0x1234);
     }
+
+    #[test]
+    fn test_load_acc_action() {
+        let state = std::rc::Rc::new(ConsoleState {
+            power_on: true,
+            switches: 0x8001,
+            registers: Registers { iar: 0x10, ..Registers::default() },
+            ..ConsoleState::default()
+        });
+        let new_state = state.reduce(ConsoleAction::LoadAcc);
+        assert_eq!(new_state.registers.acc, 0x8001);
+        assert_eq!(new_state.registers.iar, 0x10);
+
+        // Ignored with power off
+        let state = std::rc::Rc::new(ConsoleState { switches: 0x8001, ..ConsoleState::default() });
+        assert_eq!(state.reduce(ConsoleAction::LoadAcc).registers.acc, 0);
+    }
+
+    type TestMemory = std::rc::Rc<std::cell::RefCell<Vec<u16>>>;
+
+    fn memory_callbacks() -> (TestMemory, Callback<(u16, u16)>, Callback<u16, u16>) {
+        let memory = std::rc::Rc::new(std::cell::RefCell::new(vec![0u16; 64]));
+        let on_deposit = {
+            let memory = memory.clone();
+            Callback::from(move |(addr, value): (u16, u16)| {
+                memory.borrow_mut()[addr as usize] = value;
+            })
+        };
+        let on_examine = {
+            let memory = memory.clone();
+            Callback::from(move |addr: u16| memory.borrow()[addr as usize])
+        };
+        (memory, on_deposit, on_examine)
+    }
+
+    #[test]
+    fn test_deposit_then_examine() {
+        let (memory, on_deposit, on_examine) = memory_callbacks();
+        let state = std::rc::Rc::new(ConsoleState {
+            power_on: true,
+            switches: 0xBEEF,
+            registers: Registers { iar: 0x10, ..Registers::default() },
+            ..ConsoleState::default()
+        });
+
+        let action = deposit(&state, false, &on_deposit);
+        let state = state.clone().reduce(action);
+        assert_eq!(memory.borrow()[0x10], 0xBEEF);
+
+        let action = examine(&state, false, &on_examine);
+        assert!(action == ConsoleAction::Examine(0xBEEF));
+        let state = state.reduce(action);
+        assert_eq!(state.registers.sar, 0x10);
+        assert_eq!(state.registers.sbr, 0xBEEF);
+    }
+
+    #[test]
+    fn test_deposit_next_and_examine_next_advance_iar() {
+        let (memory, on_deposit, on_examine) = memory_callbacks();
+        memory.borrow_mut()[0x12] = 0x1234;
+        let state = std::rc::Rc::new(ConsoleState {
+            power_on: true,
+            switches: 7,
+            registers: Registers { iar: 0x10, ..Registers::default() },
+            ..ConsoleState::default()
+        });
+
+        let state = state.clone().reduce(deposit(&state, true, &on_deposit));
+        assert_eq!(state.registers.iar, 0x11);
+        assert_eq!(memory.borrow()[0x11], 7);
+
+        let state = state.clone().reduce(examine(&state, true, &on_examine));
+        assert_eq!(state.registers.iar, 0x12);
+        assert_eq!(state.registers.sbr, 0x1234);
+    }
+
+    #[test]
+    fn test_examine_shows_word_at_cpu_iar() {
+        let (memory, _, on_examine) = memory_callbacks();
+        memory.borrow_mut()[0x20] = 0xC0DE;
+        let state = std::rc::Rc::new(ConsoleState {
+            power_on: true,
+            ..ConsoleState::default()
+        });
+
+        // The CPU pushes its registers to the console, then the operator presses EXAMINE
+        let state = state.reduce(ConsoleAction::UpdateRegisters(Registers {
+            iar: 0x20,
+            ..Registers::default()
+        }));
+        let state = state.clone().reduce(examine(&state, false, &on_examine));
+        assert_eq!(state.registers.sar, 0x20);
+        assert_eq!(state.registers.sbr, 0xC0DE);
+        assert_eq!(state.registers.iar, 0x20);
+    }
+
+    #[test]
+    fn test_deposit_ignored_when_power_off() {
+        let (memory, on_deposit, _) = memory_callbacks();
+        let state = ConsoleState {
+            switches: 0xFFFF,
+            ..ConsoleState::default()
+        };
+
+        deposit(&state, false, &on_deposit);
+        assert_eq!(memory.borrow()[0], 0);
+    }
+
+    fn powered(speed_mode: SpeedMode) -> std::rc::Rc<ConsoleState> {
+        std::rc::Rc::new(ConsoleState {
+            power_on: true,
+            speed_mode,
+            ..ConsoleState::default()
+        })
+    }
+
+    #[test]
+    fn test_start_ignored_when_power_off() {
+        let state = std::rc::Rc::new(ConsoleState::default());
+        let state = state.reduce(ConsoleAction::Start);
+        assert!(!state.running);
+    }
+
+    #[test]
+    fn test_start_and_stop_in_run_mode() {
+        let state = powered(SpeedMode::Run).reduce(ConsoleAction::Start);
+        assert!(state.running);
+
+        let state = state.reduce(ConsoleAction::Stop);
+        assert!(!state.running);
+    }
+
+    #[test]
+    fn test_start_in_single_step_mode_does_not_latch_run() {
+        let state = powered(SpeedMode::SS).reduce(ConsoleAction::Start);
+        assert!(!state.running);
+
+        let state = powered(SpeedMode::SI).reduce(ConsoleAction::Start);
+        assert!(!state.running);
+    }
+
+    #[test]
+    fn test_wait_from_cpu_ends_run() {
+        let state = powered(SpeedMode::Run).reduce(ConsoleAction::Start);
+        let control = ControlState {
+            wait: true,
+            carry: true,
+            ..ControlState::default()
+        };
+
+        let state = state.reduce(ConsoleAction::UpdateControl(control));
+        assert!(!state.running);
+        assert!(state.control.wait);
+        assert!(state.control.carry);
+    }
+
+    #[test]
+    fn test_update_registers_sets_ext() {
+        let state = std::rc::Rc::new(ConsoleState::default());
+        let regs = Registers {
+            acc: 0x0001,
+            ext: 0x24F8,
+            iar: 0x12,
+            ..Registers::default()
+        };
+
+        let state = state.reduce(ConsoleAction::UpdateRegisters(regs));
+        assert_eq!(state.registers.ext, 0x24F8);
+        assert_eq!(state.registers.acc, 0x0001);
+    }
+
+    #[test]
+    fn test_three_deposit_nexts_fill_consecutive_addresses() {
+        let (memory, on_deposit, _) = memory_callbacks();
+        let mut state = std::rc::Rc::new(ConsoleState {
+            power_on: true,
+            registers: Registers { iar: 0x10, ..Registers::default() },
+            ..ConsoleState::default()
+        });
+
+        for word in [0x1030, 0x5031, 0xF000] {
+            state = state.reduce(ConsoleAction::SetSwitches(word));
+            state = state.clone().reduce(deposit(&state, true, &on_deposit));
+        }
+        assert_eq!(state.registers.iar, 0x13);
+        assert_eq!(memory.borrow()[0x11..0x14], [0x1030, 0x5031, 0xF000]);
+        assert_eq!(memory.borrow()[0x10], 0);
+    }
 }
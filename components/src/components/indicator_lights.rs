@@ -166,6 +166,13 @@ pub struct ControlDisplayProps {
     /// Overflow flag
     #[prop_or(false)]
     pub overflow: bool,
+    /// ACC sign flags
+    #[prop_or(false)]
+    pub positive: bool,
+    #[prop_or(false)]
+    pub zero: bool,
+    #[prop_or(false)]
+    pub negative: bool,
     /// Lamp test mode
     #[prop_or(false)]
     pub lamp_test: bool,
@@ -218,6 +225,14 @@ pub fn control_display(props: &ControlDisplayProps) -> Html {
                 <div class={classes!("control-indicator", (show && (props.lamp_test || props.carry)).then_some("lit"))}>{"C"}</div>
                 <div class={classes!("control-indicator", (show && (props.lamp_test || props.overflow)).then_some("lit"))}>{"V"}</div>
             </div>
+
+            // ACC sign column
+            <div class="control-column">
+                <div class="column-label">{"ACC"}</div>
+                <div class={classes!("control-indicator", (show && (props.lamp_test || props.positive)).then_some("lit"))}>{"P"}</div>
+                <div class={classes!("control-indicator", (show && (props.lamp_test || props.zero)).then_some("lit"))}>{"Z"}</div>
+                <div class={classes!("control-indicator", (show && (props.lamp_test || props.negative)).then_some("lit"))}>{"N"}</div>
+            </div>
         </div>
     }
 }
@@ -241,7 +256,7 @@ mod tests {
         assert_eq!((value >> 15) & 1, 1);
 
         // Check LSB (bit 15)
-        assert_eq!((value >> 0) & 1, 1);
+        assert_eq!(value & 1, 1);
 
         // Check middle bit (bit 8)
         assert_eq!((value >> 7) & 1, 0);
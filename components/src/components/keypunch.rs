@@ -7,16 +7,92 @@
 // - Save/Load deck as file
 
 use yew::prelude::*;
-use punch_card_core::punch_card::{CardType, PunchCard};
+use punch_card_core::ebcdic::{char_to_ebcdic, ebcdic_to_char, ebcdic_to_hollerith, hollerith_to_ebcdic};
+use punch_card_core::hollerith::HollerithCode;
+use punch_card_core::punch_card::{CardType, Column, PunchCard};
 use gloo::file::{Blob, ObjectUrl};
 use web_sys::HtmlInputElement;
 use wasm_bindgen::JsCast;
 
+/// Character code used to punch and read text columns
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CardEncoding {
+    /// Columns are read with the keypunch character set; unknown punches show as '?'
+    #[default]
+    Ascii,
+    /// Columns are read as EBCDIC, as the IBM 1130 card reader did; unknown punches read as blanks
+    Ebcdic,
+}
+
+/// Data words that fit in columns 1-72 of a binary card (72 columns × 12 bits)
+const WORDS_PER_BINARY_CARD: usize = 54;
+
+/// Data words per object card; the first two words hold the load address and count
+pub const OBJECT_WORDS_PER_CARD: usize = WORDS_PER_BINARY_CARD - 2;
+
+/// Read a column's punches as a 12-bit value (row 12 most significant)
+fn column_value(column: &Column) -> u16 {
+    column
+        .punches
+        .as_array()
+        .iter()
+        .fold(0, |acc, &punched| (acc << 1) | punched as u16)
+}
+
+/// Build the punches for a 12-bit column value (row 12 most significant)
+fn column_punches(value: u16) -> HollerithCode {
+    let mut rows = [false; 12];
+    for (i, row) in rows.iter_mut().enumerate() {
+        *row = value & (0x800 >> i) != 0;
+    }
+    HollerithCode::from_array(rows)
+}
+
+/// Pack up to 54 words into the columns of a binary card
+fn binary_card(words: &[u16]) -> PunchCard {
+    let mut card = PunchCard::new(CardType::Binary);
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    let mut column = 0;
+    for &word in words.iter().take(WORDS_PER_BINARY_CARD) {
+        bits = (bits << 16) | word as u32;
+        bit_count += 16;
+        while bit_count >= 12 {
+            bit_count -= 12;
+            let _ = card.set_column_hollerith(column, column_punches((bits >> bit_count) as u16 & 0xFFF));
+            bits &= (1 << bit_count) - 1;
+            column += 1;
+        }
+    }
+    if bit_count > 0 {
+        let _ = card.set_column_hollerith(column, column_punches((bits << (12 - bit_count)) as u16));
+    }
+    card
+}
+
+/// Unpack the 54 words held in columns 1-72 of a binary card
+fn card_words(card: &PunchCard) -> Vec<u16> {
+    let mut words = Vec::with_capacity(WORDS_PER_BINARY_CARD);
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    for column in card.columns().iter().take(72) {
+        bits = (bits << 12) | column_value(column) as u32;
+        bit_count += 12;
+        if bit_count >= 16 {
+            bit_count -= 16;
+            words.push((bits >> bit_count) as u16);
+            bits &= (1 << bit_count) - 1;
+        }
+    }
+    words
+}
+
 /// Represents a deck of punch cards
 #[derive(Clone, PartialEq)]
 pub struct Deck {
     pub cards: Vec<PunchCard>,
     pub current_card: usize,
+    pub encoding: CardEncoding,
 }
 
 impl Default for Deck {
@@ -24,6 +100,7 @@ impl Default for Deck {
         Self {
             cards: vec![PunchCard::new(CardType::Text)],
             current_card: 0,
+            encoding: CardEncoding::default(),
         }
     }
 }
@@ -39,6 +116,145 @@ impl Deck {
         &mut self.cards[self.current_card]
     }
 
+    /// Punch a character into a column of the current card using the deck encoding
+    pub fn punch_char(&mut self, index: usize, c: char) -> Result<(), &'static str> {
+        match self.encoding {
+            CardEncoding::Ascii => self.current_mut().set_column_char(index, c),
+            CardEncoding::Ebcdic => {
+                let column = self
+                    .current_mut()
+                    .get_column_mut(index)
+                    .ok_or("Column index out of range")?;
+                // Characters with no EBCDIC code are left blank
+                let byte = char_to_ebcdic(c).unwrap_or(0x40);
+                *column = Column {
+                    punches: ebcdic_to_hollerith(byte),
+                    printed_char: ebcdic_to_char(byte),
+                };
+                Ok(())
+            }
+        }
+    }
+
+    /// Read one column as a character using the deck encoding
+    pub fn column_char(&self, column: &Column) -> char {
+        match self.encoding {
+            CardEncoding::Ascii => column.to_char().unwrap_or('?'),
+            CardEncoding::Ebcdic => {
+                ebcdic_to_char(hollerith_to_ebcdic(&column.punches)).unwrap_or(' ')
+            }
+        }
+    }
+
+    /// Read a whole card as text using the deck encoding
+    pub fn card_text(&self, card: &PunchCard) -> String {
+        card.columns().iter().map(|col| self.column_char(col)).collect()
+    }
+
+    /// Read the deck as source text, one line per card with trailing blanks removed
+    pub fn to_source(&self) -> String {
+        self.cards
+            .iter()
+            .map(|card| self.card_text(card).trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Pack the binary cards of the deck into 16-bit words (object deck loading)
+    ///
+    /// Columns 1-72 of each binary card hold 12 bits apiece, row 12 being the
+    /// most significant, so every four columns form three words. Text cards
+    /// are skipped.
+    pub fn to_words(&self) -> Vec<u16> {
+        self.cards
+            .iter()
+            .filter(|c| c.card_type() == CardType::Binary)
+            .flat_map(card_words)
+            .collect()
+    }
+
+    /// Punch (address, word) placements as an object deck of binary cards
+    ///
+    /// Runs of consecutive addresses are split into cards of at most
+    /// `OBJECT_WORDS_PER_CARD` words; each card starts with its load address
+    /// and word count.
+    pub fn from_placements(placements: &[(u16, u16)]) -> Self {
+        let mut runs: Vec<(u16, Vec<u16>)> = Vec::new();
+        for &(addr, word) in placements {
+            match runs.last_mut() {
+                Some((start, words))
+                    if words.len() < OBJECT_WORDS_PER_CARD
+                        && start.wrapping_add(words.len() as u16) == addr =>
+                {
+                    words.push(word)
+                }
+                _ => runs.push((addr, vec![word])),
+            }
+        }
+
+        let cards: Vec<PunchCard> = runs
+            .into_iter()
+            .map(|(addr, words)| {
+                let mut card_data = vec![addr, words.len() as u16];
+                card_data.extend(words);
+                binary_card(&card_data)
+            })
+            .collect();
+
+        if cards.is_empty() {
+            return Self::default();
+        }
+        Self {
+            cards,
+            ..Self::default()
+        }
+    }
+
+    /// Read an object deck back as (address, word) placements
+    ///
+    /// Text cards are skipped. Fails if a binary card has no valid header.
+    pub fn to_placements(&self) -> Result<Vec<(u16, u16)>, String> {
+        let mut placements = Vec::new();
+        for (i, card) in self.cards.iter().enumerate() {
+            if card.card_type() != CardType::Binary {
+                continue;
+            }
+            let words = card_words(card);
+            let (addr, count) = (words[0], words[1] as usize);
+            if count == 0 || count > OBJECT_WORDS_PER_CARD {
+                return Err(format!("Card {}: invalid word count {}", i + 1, count));
+            }
+            for (offset, &word) in words[2..2 + count].iter().enumerate() {
+                placements.push((addr.wrapping_add(offset as u16), word));
+            }
+        }
+        Ok(placements)
+    }
+
+    /// The column `col` of the card before `card_idx`, as copied by the DUP key
+    ///
+    /// Returns None for the first card or an out-of-range card or column.
+    pub fn dup_column_from_prev(&self, card_idx: usize, col: usize) -> Option<Column> {
+        let prev = card_idx.checked_sub(1)?;
+        self.cards.get(prev)?.get_column(col).cloned()
+    }
+
+    /// DUP: copy column `col` of the previous card into the current card
+    ///
+    /// Returns false (leaving the card unchanged) when there is nothing to copy.
+    pub fn dup_column(&mut self, col: usize) -> bool {
+        match self.dup_column_from_prev(self.current_card, col) {
+            Some(column) => match self.current_mut().get_column_mut(col) {
+                Some(target) => {
+                    *target = column;
+                    true
+                }
+                None => false,
+            },
+            None => false,
+        }
+    }
+
     /// Add a new blank card to the deck
     pub fn add_card(&mut self) {
         self.cards.push(PunchCard::new(CardType::Text));
@@ -96,6 +312,7 @@ impl Deck {
         Self {
             cards,
             current_card: 0,
+            encoding: CardEncoding::default(),
         }
     }
 }
@@ -105,22 +322,68 @@ pub struct KeypunchProps {
     /// Callback when deck changes
     #[prop_or_default]
     pub on_deck_change: Callback<Deck>,
+    /// Initial character encoding (can be toggled from the keypunch)
+    #[prop_or_default]
+    pub encoding: CardEncoding,
+    /// Deck to load into the keypunch, replacing the current one when it changes
+    #[prop_or_default]
+    pub deck: Option<Deck>,
 }
 
 #[function_component(Keypunch)]
 pub fn keypunch(props: &KeypunchProps) -> Html {
-    let deck = use_state(Deck::default);
+    let encoding = props.encoding;
+    let deck = use_state(|| Deck {
+        encoding,
+        ..Deck::default()
+    });
     let current_column = use_state(|| 0usize);
     let download_url = use_state(|| None::<ObjectUrl>);
 
+    {
+        let deck = deck.clone();
+        let current_column = current_column.clone();
+        use_effect_with(props.deck.clone(), move |loaded| {
+            if let Some(loaded) = loaded {
+                deck.set(loaded.clone());
+                current_column.set(0);
+            }
+            || ()
+        });
+    }
+
+    // DUP key: copy the column above from the previous card and advance
+    let dup = {
+        let deck = deck.clone();
+        let current_column = current_column.clone();
+        let on_deck_change = props.on_deck_change.clone();
+        Callback::from(move |_: ()| {
+            let col = *current_column;
+            let mut new_deck = (*deck).clone();
+            if new_deck.dup_column(col) {
+                deck.set(new_deck.clone());
+                current_column.set(col + 1);
+                on_deck_change.emit(new_deck);
+            }
+        })
+    };
+
     // Handle keyboard input
     let on_key_press = {
         let deck = deck.clone();
         let current_column = current_column.clone();
         let on_deck_change = props.on_deck_change.clone();
+        let dup = dup.clone();
         Callback::from(move |e: KeyboardEvent| {
             let key = e.key();
 
+            // Ctrl+D is the DUP key
+            if e.ctrl_key() && key.eq_ignore_ascii_case("d") {
+                e.prevent_default();
+                dup.emit(());
+                return;
+            }
+
             // Handle special keys
             match key.as_str() {
                 "Enter" => {
@@ -160,7 +423,7 @@ pub fn keypunch(props: &KeypunchProps) -> Html {
             if key.len() == 1 && *current_column < 80
                 && let Some(c) = key.chars().next() {
                     let mut new_deck = (*deck).clone();
-                    let _ = new_deck.current_mut().set_column_char(*current_column, c);
+                    let _ = new_deck.punch_char(*current_column, c);
                     deck.set(new_deck.clone());
                     current_column.set(*current_column + 1);
                     on_deck_change.emit(new_deck);
@@ -178,12 +441,11 @@ pub fn keypunch(props: &KeypunchProps) -> Html {
                 && let Ok(input) = input.dyn_into::<HtmlInputElement>() {
                     let text = input.value();
                     let mut new_deck = (*deck).clone();
-                    let card = new_deck.current_mut();
 
                     // Clear current card and repunch
-                    card.clear();
+                    new_deck.current_mut().clear();
                     for (i, c) in text.chars().take(80).enumerate() {
-                        let _ = card.set_column_char(i, c);
+                        let _ = new_deck.punch_char(i, c);
                     }
 
                     let col = text.len().min(80);
@@ -243,6 +505,20 @@ pub fn keypunch(props: &KeypunchProps) -> Html {
         })
     };
 
+    let on_toggle_encoding = {
+        let deck = deck.clone();
+        let on_deck_change = props.on_deck_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut new_deck = (*deck).clone();
+            new_deck.encoding = match new_deck.encoding {
+                CardEncoding::Ascii => CardEncoding::Ebcdic,
+                CardEncoding::Ebcdic => CardEncoding::Ascii,
+            };
+            deck.set(new_deck.clone());
+            on_deck_change.emit(new_deck);
+        })
+    };
+
     // Save deck handler
     let on_save = {
         let deck = deck.clone();
@@ -272,7 +548,10 @@ pub fn keypunch(props: &KeypunchProps) -> Html {
                             let file = gloo::file::File::from(file);
                             let reader = gloo::file::callbacks::read_as_bytes(&file, move |result| {
                                 if let Ok(data) = result {
-                                    let new_deck = Deck::from_binary(&data);
+                                    let new_deck = Deck {
+                                        encoding: deck.encoding,
+                                        ..Deck::from_binary(&data)
+                                    };
                                     deck.set(new_deck.clone());
                                     current_column.set(0);
                                     on_deck_change.emit(new_deck);
@@ -285,7 +564,7 @@ pub fn keypunch(props: &KeypunchProps) -> Html {
     };
 
     // Get current card text for input display
-    let current_text = deck.current().to_text().trim_end().to_string();
+    let current_text = deck.card_text(deck.current()).trim_end().to_string();
 
     html! {
         <div class="keypunch" tabindex="0" onkeydown={on_key_press}>
@@ -333,6 +612,19 @@ pub fn keypunch(props: &KeypunchProps) -> Html {
                     </button>
                     <button onclick={on_new_card}>{"New Card"}</button>
                     <button onclick={on_clear_card}>{"Clear Card"}</button>
+                    <button
+                        onclick={dup.reform(|_: MouseEvent| ())}
+                        disabled={deck.current_card == 0}
+                        title="Copy this column from the previous card (Ctrl+D)"
+                    >
+                        {"DUP"}
+                    </button>
+                    <button onclick={on_toggle_encoding}>
+                        {match deck.encoding {
+                            CardEncoding::Ascii => "Encoding: ASCII",
+                            CardEncoding::Ebcdic => "Encoding: EBCDIC",
+                        }}
+                    </button>
                 </div>
                 <div class="file-buttons">
                     <button onclick={on_save}>{"Save Deck"}</button>
@@ -352,7 +644,7 @@ pub fn keypunch(props: &KeypunchProps) -> Html {
             <div class="deck-preview">
                 { for deck.cards.iter().enumerate().map(|(idx, card)| {
                     let is_current = idx == deck.current_card;
-                    let preview_text: String = card.to_text().chars().take(20).collect();
+                    let preview_text: String = deck.card_text(card).chars().take(20).collect();
                     let class = if is_current { "card-preview current" } else { "card-preview" };
 
                     html! {
@@ -594,4 +886,130 @@ mod tests {
 
         assert_eq!(loaded.cards.len(), 2);
     }
+
+    #[test]
+    fn test_ebcdic_byte_maps_to_character() {
+        let mut deck = Deck {
+            encoding: CardEncoding::Ebcdic,
+            ..Deck::default()
+        };
+
+        // EBCDIC 0x4B is '.', punched 12-3-8
+        deck.punch_char(0, '.').unwrap();
+        let column = deck.current().get_column(0).unwrap();
+        assert_eq!(column.punches, ebcdic_to_hollerith(0x4B));
+        assert_eq!(hollerith_to_ebcdic(&column.punches), 0x4B);
+        assert_eq!(deck.column_char(column), '.');
+    }
+
+    #[test]
+    fn test_text_roundtrip_under_each_encoding() {
+        let source = "LOOP: LD 0 10  ; X=(A+B)*2, OK?";
+        for encoding in [CardEncoding::Ascii, CardEncoding::Ebcdic] {
+            let mut deck = Deck {
+                encoding,
+                ..Deck::default()
+            };
+            for (i, c) in source.chars().enumerate() {
+                deck.punch_char(i, c).unwrap();
+            }
+            assert_eq!(deck.to_source(), source, "{:?}", encoding);
+        }
+    }
+
+    #[test]
+    fn test_unknown_punches_by_encoding() {
+        let mut deck = Deck::default();
+        let _ = deck
+            .current_mut()
+            .set_column_hollerith(0, punch_card_core::hollerith::HollerithCode::new(vec![1, 2]));
+
+        let column = deck.current().get_column(0).unwrap().clone();
+        assert_eq!(deck.column_char(&column), '?');
+        deck.encoding = CardEncoding::Ebcdic;
+        assert_eq!(deck.column_char(&column), ' ');
+    }
+
+    #[test]
+    fn test_deck_to_words_packs_columns() {
+        let mut card = PunchCard::new(CardType::Binary);
+        for (i, value) in [0xFFF, 0x000, 0x800, 0x001].into_iter().enumerate() {
+            card.set_column_hollerith(i, column_punches(value)).unwrap();
+        }
+        let deck = Deck {
+            cards: vec![PunchCard::from_text("TEXT CARD"), card],
+            ..Deck::default()
+        };
+
+        let words = deck.to_words();
+        assert_eq!(words.len(), 54); // 72 columns x 12 bits / 16
+        assert_eq!(&words[..3], &[0xFFF0, 0x0080, 0x0001]);
+        assert!(words[3..].iter().all(|&w| w == 0));
+    }
+
+    #[test]
+    fn test_deck_to_words_without_binary_cards() {
+        assert!(Deck::default().to_words().is_empty());
+
+        let deck = Deck {
+            cards: Vec::new(),
+            ..Deck::default()
+        };
+        assert!(deck.to_words().is_empty());
+    }
+
+    #[test]
+    fn test_binary_card_roundtrip() {
+        let words: Vec<u16> = (0..54).map(|i| (i as u16).wrapping_mul(0x1357)).collect();
+        assert_eq!(card_words(&binary_card(&words)), words);
+    }
+
+    #[test]
+    fn test_object_deck_placements_roundtrip() {
+        // Two runs, the second long enough to need a second card
+        let mut placements: Vec<(u16, u16)> = vec![(0x10, 0x1030), (0x11, 0xF000)];
+        placements.extend((0..60).map(|i| (0x100 + i, i)));
+
+        let deck = Deck::from_placements(&placements);
+        assert_eq!(deck.cards.len(), 3);
+        assert!(deck.cards.iter().all(|c| c.card_type() == CardType::Binary));
+        assert_eq!(deck.to_placements().unwrap(), placements);
+
+        // Header of the second card: load address and word count
+        assert_eq!(&card_words(&deck.cards[1])[..2], &[0x100, 52]);
+    }
+
+    #[test]
+    fn test_object_deck_rejects_bad_header() {
+        let deck = Deck {
+            cards: vec![PunchCard::new(CardType::Binary)],
+            ..Deck::default()
+        };
+        assert!(deck.to_placements().is_err());
+        assert_eq!(Deck::from_placements(&[]).to_placements(), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_dup_column_from_prev() {
+        let mut deck = Deck::default();
+        deck.punch_char(72, '1').unwrap();
+        deck.add_card();
+
+        // First card has no previous card to copy from
+        assert_eq!(deck.dup_column_from_prev(0, 72), None);
+        assert_eq!(deck.dup_column_from_prev(1, 72), Some(Column::from_char('1')));
+        assert_eq!(deck.dup_column_from_prev(1, 80), None);
+        assert_eq!(deck.dup_column_from_prev(5, 72), None);
+
+        assert!(deck.dup_column(72));
+        assert_eq!(deck.current().get_column(72), Some(&Column::from_char('1')));
+        // Blank columns duplicate as blank
+        deck.punch_char(0, 'X').unwrap();
+        assert!(deck.dup_column(0));
+        assert!(deck.current().get_column(0).unwrap().is_blank());
+
+        deck.prev_card();
+        assert!(!deck.dup_column(72));
+        assert_eq!(deck.current().get_column(72), Some(&Column::from_char('1')));
+    }
 }
@@ -1,5 +1,79 @@
 use yew::prelude::*;
 
+/// Number base used to show each memory word
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DisplayRadix {
+    /// Four hex digits (`FFFF`)
+    #[default]
+    Hex,
+    /// Two's complement signed decimal (`-1`)
+    SignedDecimal,
+    /// Unsigned decimal (`65535`)
+    UnsignedDecimal,
+    /// Sixteen bits in groups of four (`1111 1111 1111 1111`)
+    Binary,
+}
+
+impl DisplayRadix {
+    /// Format a word in this radix, padded to a fixed width so columns line up
+    pub fn format_word(self, word: u16) -> String {
+        match self {
+            DisplayRadix::Hex => format!("{:04X}", word),
+            DisplayRadix::SignedDecimal => format!("{:>6}", word as i16),
+            DisplayRadix::UnsignedDecimal => format!("{:>5}", word),
+            DisplayRadix::Binary => {
+                let bits = format!("{:016b}", word);
+                let groups: Vec<&str> = (0..16).step_by(4).map(|i| &bits[i..i + 4]).collect();
+                groups.join(" ")
+            }
+        }
+    }
+
+    /// Width in characters of a formatted word
+    pub fn width(self) -> usize {
+        match self {
+            DisplayRadix::Hex => 4,
+            DisplayRadix::SignedDecimal => 6,
+            DisplayRadix::UnsignedDecimal => 5,
+            DisplayRadix::Binary => 19,
+        }
+    }
+
+    /// Short label for the toggle control
+    pub fn label(self) -> &'static str {
+        match self {
+            DisplayRadix::Hex => "HEX",
+            DisplayRadix::SignedDecimal => "DEC",
+            DisplayRadix::UnsignedDecimal => "UNS",
+            DisplayRadix::Binary => "BIN",
+        }
+    }
+
+    /// The radix the toggle switches to next
+    pub fn next(self) -> Self {
+        match self {
+            DisplayRadix::Hex => DisplayRadix::SignedDecimal,
+            DisplayRadix::SignedDecimal => DisplayRadix::UnsignedDecimal,
+            DisplayRadix::UnsignedDecimal => DisplayRadix::Binary,
+            DisplayRadix::Binary => DisplayRadix::Hex,
+        }
+    }
+}
+
+/// Split a word into its (high, low) bytes
+pub fn split_word(w: u16) -> (u8, u8) {
+    ((w >> 8) as u8, w as u8)
+}
+
+/// Format a word as its two bytes in hex (`12 34`)
+pub fn format_word_bytes(word: u16) -> String {
+    let (high, low) = split_word(word);
+    format!("{:02X} {:02X}", high, low)
+}
+
+/// Width in characters of `format_word_bytes` output
+const BYTE_SPLIT_WIDTH: usize = 5;
+
 #[derive(Properties, PartialEq)]
 pub struct MemoryViewerProps {
     pub memory: Vec<u8>,
@@ -29,6 +103,12 @@ pub struct WordMemoryViewerProps {
     pub title: Option<String>,
     #[prop_or_default]
     pub changed_addresses: Vec<usize>,
+    /// Initial display radix (can be toggled from the viewer)
+    #[prop_or_default]
+    pub radix: DisplayRadix,
+    /// Initially show each word as its two bytes (can be toggled from the viewer)
+    #[prop_or(false)]
+    pub byte_split: bool,
 }
 
 #[function_component(MemoryViewer)]
@@ -95,6 +175,34 @@ pub fn word_memory_viewer(props: &WordMemoryViewerProps) -> Html {
         .clone()
         .unwrap_or_else(|| format!("Memory (First {} Words)", props.words_to_show));
 
+    let radix = use_state(|| props.radix);
+    let on_toggle_radix = {
+        let radix = radix.clone();
+        Callback::from(move |_: MouseEvent| radix.set(radix.next()))
+    };
+    let radix = *radix;
+
+    let byte_split = use_state(|| props.byte_split);
+    let on_toggle_byte_split = {
+        let byte_split = byte_split.clone();
+        Callback::from(move |_: MouseEvent| byte_split.set(!*byte_split))
+    };
+    let byte_split = *byte_split;
+
+    // Each word stays one element in either view, so highlights cover the whole word
+    let format_cell = move |word: u16| {
+        if byte_split {
+            format_word_bytes(word)
+        } else {
+            radix.format_word(word)
+        }
+    };
+    let blank = " ".repeat(if byte_split {
+        BYTE_SPLIT_WIDTH
+    } else {
+        radix.width()
+    });
+
     let rows = (0..props.words_to_show)
         .step_by(props.words_per_row)
         .map(|word_addr| {
@@ -146,13 +254,13 @@ pub fn word_memory_viewer(props: &WordMemoryViewerProps) -> Html {
 
                             html! {
                                 <span {class} key={addr} title={tooltip}>
-                                    {format!("{:04X}", word)}
+                                    {format_cell(word)}
                                 </span>
                             }
                         } else {
                             html! {
                                 <span class="memory-word" key={addr}>
-                                    {"    "}
+                                    {blank.clone()}
                                 </span>
                             }
                         }
@@ -163,10 +271,86 @@ pub fn word_memory_viewer(props: &WordMemoryViewerProps) -> Html {
 
     html! {
         <div class="memory-panel">
-            <div class="panel-title">{title}</div>
+            <div class="panel-title">
+                {title}
+                <button class="radix-toggle" onclick={on_toggle_byte_split} title="Show words as two bytes">
+                    {if byte_split { "BYTES" } else { "WORDS" }}
+                </button>
+                <button
+                    class="radix-toggle"
+                    onclick={on_toggle_radix}
+                    disabled={byte_split}
+                    title="Change number base"
+                >
+                    {radix.label()}
+                </button>
+            </div>
             <div class="memory-viewer">
                 { for rows }
             </div>
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_word_all_ones() {
+        assert_eq!(DisplayRadix::Hex.format_word(0xFFFF), "FFFF");
+        assert_eq!(DisplayRadix::SignedDecimal.format_word(0xFFFF).trim(), "-1");
+        assert_eq!(DisplayRadix::UnsignedDecimal.format_word(0xFFFF), "65535");
+        assert_eq!(
+            DisplayRadix::Binary.format_word(0xFFFF),
+            "1111 1111 1111 1111"
+        );
+    }
+
+    #[test]
+    fn test_format_word_widths() {
+        for radix in [
+            DisplayRadix::Hex,
+            DisplayRadix::SignedDecimal,
+            DisplayRadix::UnsignedDecimal,
+            DisplayRadix::Binary,
+        ] {
+            for word in [0, 1, 0x7FFF, 0x8000, 0xFFFF] {
+                assert_eq!(
+                    radix.format_word(word).len(),
+                    radix.width(),
+                    "{radix:?} {word}"
+                );
+            }
+        }
+        assert_eq!(DisplayRadix::SignedDecimal.format_word(0x8000), "-32768");
+        assert_eq!(
+            DisplayRadix::Binary.format_word(0x1234),
+            "0001 0010 0011 0100"
+        );
+    }
+
+    #[test]
+    fn test_radix_toggle_cycles() {
+        let mut radix = DisplayRadix::default();
+        for _ in 0..4 {
+            radix = radix.next();
+        }
+        assert_eq!(radix, DisplayRadix::Hex);
+    }
+
+    #[test]
+    fn test_split_word() {
+        assert_eq!(split_word(0x1234), (0x12, 0x34));
+        assert_eq!(split_word(0xFF00), (0xFF, 0x00));
+        assert_eq!(split_word(0x00FF), (0x00, 0xFF));
+    }
+
+    #[test]
+    fn test_format_word_bytes() {
+        assert_eq!(format_word_bytes(0xC0FF), "C0 FF");
+        for word in [0, 0x00FF, 0xFFFF] {
+            assert_eq!(format_word_bytes(word).len(), BYTE_SPLIT_WIDTH);
+        }
+    }
+}
@@ -26,14 +26,17 @@ pub use register_panel::*;
 pub use sidebar::*;
 
 // Re-export console panel components
-pub use circular_knob::{CircularKnob, SpeedMode};
-pub use console_panel::{ConsolePanel, ConsoleState, ConsoleAction, Registers};
+pub use circular_knob::{CircularKnob, RUN_BATCH, SpeedMode, speed_to_batch};
+pub use console_panel::{ConsolePanel, ConsoleState, ConsoleAction, ControlState, Registers};
 pub use emergency_stop::EmergencyStop;
 pub use indicator_lights::{IndicatorLights, RegisterDisplay};
-pub use keypunch::{Keypunch, Deck, PunchCardSvg};
+pub use keypunch::{Keypunch, Deck, CardEncoding, PunchCardSvg};
 pub use lamp_test_button::LampTestButton;
 pub use power_switch::PowerSwitch;
 pub use sixteen_bit_panel::{SixteenBitPanel, PanelMode};
 pub use tab_container::{Tab, TabContainer, TabNav, TabPlaceholder};
 pub use toggle_switch::ToggleSwitch;
-pub use printer::{Printer, PrinterState, sample_assembler_listing};
+pub use printer::{
+    DEFAULT_PRINTER_COLUMNS, LineOverflow, Printer, PrinterState, Spacing, carriage_control, fit_line,
+    printer_text, sample_assembler_listing,
+};
@@ -7,6 +7,7 @@
 // - Auto-scroll
 
 use yew::prelude::*;
+use gloo::file::{Blob, ObjectUrl};
 use gloo::timers::callback::Timeout;
 use wasm_bindgen::prelude::*;
 
@@ -24,9 +25,104 @@ pub struct PrinterState {
     pub printing: bool,
     /// Queue of lines to print
     pub queue: Vec<String>,
+    /// Indices into `completed_lines` where a new page begins
+    pub page_breaks: Vec<usize>,
+}
+
+/// Print positions on a 1403 line printer carriage
+pub const DEFAULT_PRINTER_COLUMNS: usize = 132;
+
+/// What to do with text past the last print position
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineOverflow {
+    /// Continue the text on the following line(s)
+    #[default]
+    Wrap,
+    /// Cut the text off, marking the last position with `»`
+    Clip,
+}
+
+/// Fit text to the printer width, producing one or more printed lines
+pub fn fit_line(text: &str, columns: usize, overflow: LineOverflow) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= columns || columns == 0 {
+        return vec![text.to_string()];
+    }
+    match overflow {
+        LineOverflow::Wrap => chars.chunks(columns).map(|c| c.iter().collect()).collect(),
+        LineOverflow::Clip => {
+            let mut clipped: String = chars[..columns - 1].iter().collect();
+            clipped.push('»');
+            vec![clipped]
+        }
+    }
+}
+
+/// Paper motion requested by a carriage-control character (IBM 1403 style)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Spacing {
+    /// Blank: advance one line before printing
+    Single,
+    /// `0`: advance two lines (one blank line) before printing
+    Double,
+    /// `-`: advance three lines (two blank lines) before printing
+    Triple,
+    /// `1`: skip to the top of a new page before printing
+    NewPage,
+}
+
+impl Spacing {
+    /// Number of blank lines left before the printed line
+    pub fn blank_lines(self) -> usize {
+        match self {
+            Spacing::Double => 1,
+            Spacing::Triple => 2,
+            Spacing::Single | Spacing::NewPage => 0,
+        }
+    }
+}
+
+/// Split a line into its leading carriage-control character and the text to print
+///
+/// Unrecognized control characters are treated as single spacing.
+pub fn carriage_control(line: &str) -> (Spacing, &str) {
+    let mut chars = line.chars();
+    let spacing = match chars.next() {
+        Some('1') => Spacing::NewPage,
+        Some('0') => Spacing::Double,
+        Some('-') => Spacing::Triple,
+        _ => Spacing::Single,
+    };
+    (spacing, chars.as_str())
 }
 
 
+/// Render carriage-control lines as plain text for saving
+///
+/// Double and triple spacing become blank lines and page ejects become form
+/// feeds. Trailing blanks are dropped and the text ends with exactly one newline.
+pub fn printer_text(lines: &[String]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        let (spacing, body) = carriage_control(line);
+        if spacing == Spacing::NewPage && !text.is_empty() {
+            text.push('\x0C');
+        }
+        for _ in 0..spacing.blank_lines() {
+            text.push('\n');
+        }
+        text.push_str(body.trim_end());
+        text.push('\n');
+    }
+
+    let len = text.trim_end_matches('\n').len();
+    text.truncate(len);
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
 #[derive(Properties, PartialEq)]
 pub struct PrinterProps {
     /// Lines to print
@@ -41,6 +137,12 @@ pub struct PrinterProps {
     /// Sound enabled
     #[prop_or(true)]
     pub sound_enabled: bool,
+    /// Print positions per line
+    #[prop_or(DEFAULT_PRINTER_COLUMNS)]
+    pub columns: usize,
+    /// How lines longer than `columns` are printed
+    #[prop_or_default]
+    pub overflow: LineOverflow,
 }
 
 #[function_component(Printer)]
@@ -48,6 +150,7 @@ pub fn printer(props: &PrinterProps) -> Html {
     let state = use_state(PrinterState::default);
     let printing_active = use_state(|| false);
     let audio_initialized = use_state(|| false);
+    let download_url = use_state(|| None::<ObjectUrl>);
 
     // Initialize audio on first interaction
     let init_audio = {
@@ -60,20 +163,44 @@ pub fn printer(props: &PrinterProps) -> Html {
         })
     };
 
+    // Save listing handler
+    let on_save = {
+        let download_url = download_url.clone();
+        let content = props.content.clone();
+        Callback::from(move |_: MouseEvent| {
+            let text = printer_text(&content);
+            let blob = Blob::new_with_options(text.as_str(), Some("text/plain"));
+            download_url.set(Some(ObjectUrl::from(blob)));
+        })
+    };
+
     // Start printing handler
     let start_printing = {
         let state = state.clone();
         let printing_active = printing_active.clone();
         let audio_initialized = audio_initialized.clone();
         let content = props.content.clone();
+        let columns = props.columns;
+        let overflow = props.overflow;
         Callback::from(move |_: MouseEvent| {
             if !*audio_initialized {
                 init_web_audio();
                 audio_initialized.set(true);
             }
 
+            // Continuation lines of a wrapped line are single-spaced
             let mut new_state = (*state).clone();
-            new_state.queue = content.clone();
+            new_state.queue = content
+                .iter()
+                .flat_map(|line| {
+                    let control = line.chars().next().unwrap_or(' ');
+                    let (_, text) = carriage_control(line);
+                    fit_line(text, columns, overflow)
+                        .into_iter()
+                        .enumerate()
+                        .map(move |(i, part)| format!("{}{}", if i == 0 { control } else { ' ' }, part))
+                })
+                .collect();
             new_state.printing = true;
             state.set(new_state);
             printing_active.set(true);
@@ -135,9 +262,22 @@ pub fn printer(props: &PrinterProps) -> Html {
                             state.set(new_state);
                         }
                     } else if !new_state.queue.is_empty() {
-                        // Start next line from queue - trim trailing whitespace for faster printing
+                        // Start next line from queue - apply carriage control, then
+                        // trim trailing whitespace for faster printing
                         let line = new_state.queue.remove(0);
-                        new_state.current_line = line.trim_end().to_string();
+                        let (spacing, text) = carriage_control(&line);
+                        if spacing == Spacing::NewPage && !new_state.completed_lines.is_empty() {
+                            new_state.page_breaks.push(new_state.completed_lines.len());
+                        }
+                        for _ in 0..spacing.blank_lines() {
+                            new_state.completed_lines.push(String::new());
+                        }
+                        let text = text.trim_end();
+                        if text.is_empty() {
+                            // Nothing to type: feed the blank line straight out
+                            new_state.completed_lines.push(String::new());
+                        }
+                        new_state.current_line = text.to_string();
                         new_state.char_position = 0;
                         state.set(new_state);
                     } else {
@@ -185,7 +325,7 @@ pub fn printer(props: &PrinterProps) -> Html {
                 <h2 class="printer-title">{"IBM 1053 Console Printer"}</h2>
                 <div class="printer-info">
                     <span class="paper-info">{"13\" Paper"}</span>
-                    <span class="char-info">{"130 Char/Line"}</span>
+                    <span class="char-info">{format!("{} Char/Line", props.columns)}</span>
                     <span class="cps-info">{"15.5 CPS Selectric"}</span>
                 </div>
             </div>
@@ -195,8 +335,16 @@ pub fn printer(props: &PrinterProps) -> Html {
                     <div class="spacer"></div>
                     <div class="output">
                         // Completed lines
-                        { for state.completed_lines.iter().map(|line| {
-                            html! { <div class="line">{line}</div> }
+                        { for state.completed_lines.iter().enumerate().map(|(idx, line)| {
+                            let page_break = state.page_breaks.iter().position(|&b| b == idx);
+                            html! {
+                                <>
+                                    if let Some(page) = page_break {
+                                        <div class="page-break">{format!("— Page {} —", page + 2)}</div>
+                                    }
+                                    <div class="line">{line}</div>
+                                </>
+                            }
                         })}
                         // Current line being typed
                         if !partial_line.is_empty() {
@@ -217,6 +365,17 @@ pub fn printer(props: &PrinterProps) -> Html {
                 <span class="line-count">
                     {format!("Lines: {}", state.completed_lines.len())}
                 </span>
+                <span class="page-count">
+                    {format!("Page: {}", state.page_breaks.len() + 1)}
+                </span>
+                <button class="save-button" onclick={on_save} disabled={props.content.is_empty()}>
+                    {"Save as .txt"}
+                </button>
+                if let Some(url) = &*download_url {
+                    <a href={url.to_string()} download="listing.txt" class="download-link">
+                        {"Download"}
+                    </a>
+                }
             </div>
         </div>
     }
@@ -224,45 +383,41 @@ pub fn printer(props: &PrinterProps) -> Html {
 
 /// Sample assembler listing for demo
 /// IBM 1053 Console Printer: 13" paper, 130 characters per line
+///
+/// Each line starts with a carriage-control character (see `carriage_control`).
 pub fn sample_assembler_listing() -> Vec<String> {
     vec![
-        "// JOB    T".to_string(),
-        "// ASM".to_string(),
-        "*LIST".to_string(),
-        "*NAME SAMPLE".to_string(),
-        "".to_string(),
-        "                              IBM 1130 ASSEMBLER                              PAGE    1".to_string(),
-        "".to_string(),
-        " STMT   ADDR   OBJECT     LABEL    OP    OPERANDS                        COMMENTS".to_string(),
-        "".to_string(),
-        "    1                               * SAMPLE IBM 1130 PROGRAM - COUNTER LOOP".to_string(),
-        "    2                               * DEMONSTRATES ARITHMETIC AND BRANCHING".to_string(),
-        "    3                               * WRITTEN FOR IBM 1130 WITH 1053 CONSOLE".to_string(),
-        "    4                               *".to_string(),
-        "    5   0100            START       EQU   /100                   PROGRAM ORIGIN".to_string(),
-        "    6   0100   C108                 LD    COUNT                  LOAD COUNTER".to_string(),
-        "    7   0101   8109                 A     ONE                    ADD ONE".to_string(),
-        "    8   0102   D108                 STO   COUNT                  STORE RESULT".to_string(),
-        "    9   0103   C108                 LD    COUNT                  RELOAD FOR COMPARE".to_string(),
-        "   10   0104   110A                 S     LIMIT                  SUBTRACT LIMIT".to_string(),
-        "   11   0105   4C20                 BSC   L     DONE,+-          BRANCH IF >= LIMIT".to_string(),
-        "   12   0106   7000                 MDX         0                NO-OP (LOOP)".to_string(),
-        "   13   0107   4C00                 BSC   L     START            BRANCH TO START".to_string(),
-        "   14   0108   0000     COUNT       DC    0                      COUNTER STORAGE".to_string(),
-        "   15   0109   0001     ONE         DC    1                      CONSTANT ONE".to_string(),
-        "   16   010A   000A     LIMIT       DC    10                     LOOP LIMIT".to_string(),
-        "   17   010B   0000     DONE        DC    0                      END MARKER".to_string(),
-        "   18                               END   START                  ENTRY AT START".to_string(),
-        "".to_string(),
-        "                                    SYMBOL TABLE".to_string(),
-        "".to_string(),
-        "  SYMBOL    VALUE     SYMBOL    VALUE     SYMBOL    VALUE".to_string(),
-        "  COUNT     0108      DONE      010B      LIMIT     010A".to_string(),
-        "  ONE       0109      START     0100".to_string(),
-        "".to_string(),
-        "  ASSEMBLY COMPLETE - NO ERRORS DETECTED           CORE USED: 000C WORDS".to_string(),
-        "".to_string(),
-        "// XEQ".to_string(),
+        " // JOB    T".to_string(),
+        " // ASM".to_string(),
+        " *LIST".to_string(),
+        " *NAME SAMPLE".to_string(),
+        "1                              IBM 1130 ASSEMBLER                              PAGE    1".to_string(),
+        "0 STMT   ADDR   OBJECT     LABEL    OP    OPERANDS                        COMMENTS".to_string(),
+        "0    1                               * SAMPLE IBM 1130 PROGRAM - COUNTER LOOP".to_string(),
+        "     2                               * DEMONSTRATES ARITHMETIC AND BRANCHING".to_string(),
+        "     3                               * WRITTEN FOR IBM 1130 WITH 1053 CONSOLE".to_string(),
+        "     4                               *".to_string(),
+        "     5   0100            START       EQU   /100                   PROGRAM ORIGIN".to_string(),
+        "     6   0100   C108                 LD    COUNT                  LOAD COUNTER".to_string(),
+        "     7   0101   8109                 A     ONE                    ADD ONE".to_string(),
+        "     8   0102   D108                 STO   COUNT                  STORE RESULT".to_string(),
+        "     9   0103   C108                 LD    COUNT                  RELOAD FOR COMPARE".to_string(),
+        "    10   0104   110A                 S     LIMIT                  SUBTRACT LIMIT".to_string(),
+        "    11   0105   4C20                 BSC   L     DONE,+-          BRANCH IF >= LIMIT".to_string(),
+        "    12   0106   7000                 MDX         0                NO-OP (LOOP)".to_string(),
+        "    13   0107   4C00                 BSC   L     START            BRANCH TO START".to_string(),
+        "    14   0108   0000     COUNT       DC    0                      COUNTER STORAGE".to_string(),
+        "    15   0109   0001     ONE         DC    1                      CONSTANT ONE".to_string(),
+        "    16   010A   000A     LIMIT       DC    10                     LOOP LIMIT".to_string(),
+        "    17   010B   0000     DONE        DC    0                      END MARKER".to_string(),
+        "    18                               END   START                  ENTRY AT START".to_string(),
+        "1                              IBM 1130 ASSEMBLER                              PAGE    2".to_string(),
+        "0                                   SYMBOL TABLE".to_string(),
+        "0  SYMBOL    VALUE     SYMBOL    VALUE     SYMBOL    VALUE".to_string(),
+        "   COUNT     0108      DONE      010B      LIMIT     010A".to_string(),
+        "   ONE       0109      START     0100".to_string(),
+        "-  ASSEMBLY COMPLETE - NO ERRORS DETECTED           CORE USED: 000C WORDS".to_string(),
+        "0// XEQ".to_string(),
     ]
 }
 
@@ -405,4 +560,86 @@ mod tests {
         assert!(!listing.is_empty());
         assert!(listing[0].contains("JOB"));
     }
+
+    #[test]
+    fn test_carriage_control_classification() {
+        assert_eq!(carriage_control("1PAGE HEADER"), (Spacing::NewPage, "PAGE HEADER"));
+        assert_eq!(carriage_control("0TOTALS"), (Spacing::Double, "TOTALS"));
+        assert_eq!(carriage_control("-SUMMARY"), (Spacing::Triple, "SUMMARY"));
+        assert_eq!(carriage_control(" LINE"), (Spacing::Single, "LINE"));
+        assert_eq!(carriage_control(""), (Spacing::Single, ""));
+        // Unknown control characters single-space
+        assert_eq!(carriage_control("+OVER"), (Spacing::Single, "OVER"));
+    }
+
+    #[test]
+    fn test_spacing_blank_lines() {
+        assert_eq!(Spacing::Single.blank_lines(), 0);
+        assert_eq!(Spacing::Double.blank_lines(), 1);
+        assert_eq!(Spacing::Triple.blank_lines(), 2);
+        assert_eq!(Spacing::NewPage.blank_lines(), 0);
+    }
+
+    #[test]
+    fn test_sample_listing_ejects_page() {
+        let listing = sample_assembler_listing();
+        let pages = listing
+            .iter()
+            .filter(|line| carriage_control(line).0 == Spacing::NewPage)
+            .count();
+        assert_eq!(pages, 2);
+    }
+
+    #[test]
+    fn test_fit_line_at_limit() {
+        let line = "X".repeat(132);
+        assert_eq!(fit_line(&line, 132, LineOverflow::Wrap), vec![line.clone()]);
+        assert_eq!(fit_line(&line, 132, LineOverflow::Clip), vec![line]);
+    }
+
+    #[test]
+    fn test_fit_line_one_over() {
+        let line = format!("{}Y", "X".repeat(132));
+        assert_eq!(
+            fit_line(&line, 132, LineOverflow::Wrap),
+            vec!["X".repeat(132), "Y".to_string()]
+        );
+        assert_eq!(
+            fit_line(&line, 132, LineOverflow::Clip),
+            vec![format!("{}»", "X".repeat(131))]
+        );
+    }
+
+    #[test]
+    fn test_fit_line_far_over() {
+        let line = "ABCDEFGHIJ".repeat(30); // 300 columns
+        let wrapped = fit_line(&line, 132, LineOverflow::Wrap);
+        assert_eq!(wrapped.len(), 3);
+        assert_eq!(wrapped[2].len(), 36);
+        assert_eq!(wrapped.concat(), line);
+
+        let clipped = fit_line(&line, 132, LineOverflow::Clip);
+        assert_eq!(clipped.len(), 1);
+        assert_eq!(clipped[0].chars().count(), 132);
+    }
+
+    #[test]
+    fn test_printer_text_applies_carriage_control() {
+        let lines: Vec<String> = ["1HEADER", "0FIRST", " SECOND   ", "-THIRD", "1NEXT PAGE"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            printer_text(&lines),
+            "HEADER\n\nFIRST\nSECOND\n\n\nTHIRD\n\x0CNEXT PAGE\n"
+        );
+    }
+
+    #[test]
+    fn test_printer_text_trailing_newline() {
+        let lines: Vec<String> = vec![" LAST".to_string(), " ".to_string(), "".to_string()];
+        assert_eq!(printer_text(&lines), "LAST\n");
+        assert_eq!(printer_text(&[]), "");
+        assert_eq!(printer_text(&[" ".to_string()]), "");
+    }
 }
@@ -1,16 +1,65 @@
 use web_sys::HtmlTextAreaElement;
 use yew::prelude::*;
 
+/// Program area command bound to a keyboard shortcut
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditorAction {
+    /// F5: assemble the editor contents, then run
+    AssembleAndRun,
+    /// F10: execute one instruction
+    Step,
+    /// Ctrl+R: reset the machine
+    Reset,
+}
+
+/// Modifier keys held during a key press (Cmd on macOS counts as `ctrl`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyModifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+/// Map a `KeyboardEvent.key` value and its modifiers to a shortcut
+///
+/// Only function keys and Ctrl combinations are bound, so ordinary typing in
+/// the editor never triggers an action.
+pub fn key_to_action(key: &str, modifiers: KeyModifiers) -> Option<EditorAction> {
+    let plain = modifiers == KeyModifiers::default();
+    let ctrl_only = modifiers
+        == KeyModifiers {
+            ctrl: true,
+            ..KeyModifiers::default()
+        };
+    match key {
+        "F5" if plain => Some(EditorAction::AssembleAndRun),
+        "F10" if plain => Some(EditorAction::Step),
+        "r" | "R" if ctrl_only => Some(EditorAction::Reset),
+        _ => None,
+    }
+}
+
 #[derive(Properties, PartialEq)]
 pub struct ProgramAreaProps {
     pub on_assemble: Callback<String>,
     pub on_step: Callback<()>,
     pub on_run: Callback<()>,
     pub on_reset: Callback<()>,
+    /// Clear the machine, program included; the Clear button is shown only when set
+    #[prop_or_default]
+    pub on_clear: Option<Callback<()>>,
+    /// Undo the last step; the Step Back button is shown only when set
+    #[prop_or_default]
+    pub on_step_back: Option<Callback<()>>,
     pub assembly_output: Option<Html>,
     pub initial_code: Option<String>,
     pub step_enabled: bool,
     pub run_enabled: bool,
+    #[prop_or_default]
+    pub step_back_enabled: bool,
+    /// Called with the editor text on every edit
+    #[prop_or_default]
+    pub on_edit: Option<Callback<String>>,
 }
 
 #[function_component(ProgramArea)]
@@ -30,6 +79,73 @@ pub fn program_area(props: &ProgramAreaProps) -> Html {
         });
     }
 
+    // F5 assembles and then runs once the assembled program has been rendered,
+    // so the run sees the new machine state
+    let run_pending = use_state(|| false);
+    {
+        let run_pending = run_pending.clone();
+        let on_run = props.on_run.clone();
+        let run_enabled = props.run_enabled;
+        use_effect_with(*run_pending, move |&pending| {
+            if pending {
+                run_pending.set(false);
+                if run_enabled {
+                    on_run.emit(());
+                }
+            }
+            || ()
+        });
+    }
+
+    let on_keydown = {
+        let editor_ref = editor_ref.clone();
+        let code = code.clone();
+        let run_pending = run_pending.clone();
+        let on_assemble = props.on_assemble.clone();
+        let on_step = props.on_step.clone();
+        let on_reset = props.on_reset.clone();
+        let step_enabled = props.step_enabled;
+
+        Callback::from(move |e: KeyboardEvent| {
+            let modifiers = KeyModifiers {
+                ctrl: e.ctrl_key() || e.meta_key(),
+                alt: e.alt_key(),
+                shift: e.shift_key(),
+            };
+            let Some(action) = key_to_action(&e.key(), modifiers) else {
+                return;
+            };
+            // Keep the browser from refreshing (F5, Ctrl+R) or focusing its menu (F10)
+            e.prevent_default();
+            match action {
+                EditorAction::AssembleAndRun => {
+                    if let Some(textarea) = editor_ref.cast::<HtmlTextAreaElement>() {
+                        let value = textarea.value();
+                        code.set(value.clone());
+                        on_assemble.emit(value);
+                        run_pending.set(true);
+                    }
+                }
+                EditorAction::Step => {
+                    if step_enabled {
+                        on_step.emit(());
+                    }
+                }
+                EditorAction::Reset => on_reset.emit(()),
+            }
+        })
+    };
+
+    let on_input = {
+        let on_edit = props.on_edit.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(on_edit) = &on_edit {
+                let textarea: HtmlTextAreaElement = e.target_unchecked_into();
+                on_edit.emit(textarea.value());
+            }
+        })
+    };
+
     let on_assemble_click = {
         let editor_ref = editor_ref.clone();
         let on_assemble = props.on_assemble.clone();
@@ -51,6 +167,20 @@ pub fn program_area(props: &ProgramAreaProps) -> Html {
         })
     };
 
+    let step_back_button = props.on_step_back.clone().map(|on_step_back| {
+        let onclick = Callback::from(move |_: MouseEvent| on_step_back.emit(()));
+        html! {
+            <button id="stepBackBtn" {onclick} disabled={!props.step_back_enabled}>{"Step Back"}</button>
+        }
+    });
+
+    let clear_button = props.on_clear.clone().map(|on_clear| {
+        let onclick = Callback::from(move |_: MouseEvent| on_clear.emit(()));
+        html! {
+            <button id="clearBtn" {onclick}>{"Clear"}</button>
+        }
+    });
+
     let on_run_click = {
         let on_run = props.on_run.clone();
         Callback::from(move |_: MouseEvent| {
@@ -66,22 +196,71 @@ pub fn program_area(props: &ProgramAreaProps) -> Html {
     };
 
     html! {
-        <div class="program-area-inner">
+        <div class="program-area-inner" onkeydown={on_keydown}>
             // Source editor (scrollable)
             <textarea
                 ref={editor_ref}
                 id="programEditor"
                 placeholder="Enter assembly code here..."
                 value={(*code).clone()}
+                oninput={on_input}
             />
 
             // Controls bar
             <div class="controls-bar">
                 <button id="assembleBtn" onclick={on_assemble_click}>{"Assemble"}</button>
+                {step_back_button}
                 <button id="stepBtn" onclick={on_step_click} disabled={!props.step_enabled}>{"Step"}</button>
                 <button id="runBtn" onclick={on_run_click} disabled={!props.run_enabled}>{"Run"}</button>
                 <button id="resetBtn" onclick={on_reset_click}>{"Reset"}</button>
+                {clear_button}
             </div>
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CTRL: KeyModifiers = KeyModifiers {
+        ctrl: true,
+        alt: false,
+        shift: false,
+    };
+
+    #[test]
+    fn test_function_keys() {
+        let none = KeyModifiers::default();
+        assert_eq!(
+            key_to_action("F5", none),
+            Some(EditorAction::AssembleAndRun)
+        );
+        assert_eq!(key_to_action("F10", none), Some(EditorAction::Step));
+        assert_eq!(key_to_action("F5", CTRL), None);
+        assert_eq!(key_to_action("F1", none), None);
+    }
+
+    #[test]
+    fn test_ctrl_r_resets() {
+        assert_eq!(key_to_action("r", CTRL), Some(EditorAction::Reset));
+        assert_eq!(key_to_action("R", CTRL), Some(EditorAction::Reset));
+        // Ctrl+Shift+R is left to the browser
+        let ctrl_shift = KeyModifiers {
+            shift: true,
+            ..CTRL
+        };
+        assert_eq!(key_to_action("R", ctrl_shift), None);
+    }
+
+    #[test]
+    fn test_typing_is_not_a_shortcut() {
+        let shift = KeyModifiers {
+            shift: true,
+            ..KeyModifiers::default()
+        };
+        assert_eq!(key_to_action("r", KeyModifiers::default()), None);
+        assert_eq!(key_to_action("R", shift), None);
+        assert_eq!(key_to_action("Enter", KeyModifiers::default()), None);
+    }
+}
@@ -14,6 +14,27 @@ pub struct LegendItem {
     pub changed: bool,
 }
 
+/// Sign flags derived from the accumulator; exactly one is set
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccFlags {
+    /// ACC > 0 as a signed word
+    pub positive: bool,
+    /// ACC == 0
+    pub zero: bool,
+    /// ACC < 0 (sign bit set)
+    pub negative: bool,
+}
+
+/// Classify ACC as positive, zero, or negative
+pub fn acc_flags(acc: u16) -> AccFlags {
+    let negative = acc & 0x8000 != 0;
+    AccFlags {
+        positive: acc != 0 && !negative,
+        zero: acc == 0,
+        negative,
+    }
+}
+
 #[derive(Properties, PartialEq)]
 pub struct RegisterPanelProps {
     pub registers: Vec<Register>,
@@ -65,3 +86,44 @@ pub fn register_panel(props: &RegisterPanelProps) -> Html {
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acc_flags_zero() {
+        assert_eq!(
+            acc_flags(0),
+            AccFlags {
+                positive: false,
+                zero: true,
+                negative: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_acc_flags_positive() {
+        assert_eq!(
+            acc_flags(0x7FFF),
+            AccFlags {
+                positive: true,
+                zero: false,
+                negative: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_acc_flags_negative() {
+        assert_eq!(
+            acc_flags(0x8000),
+            AccFlags {
+                positive: false,
+                zero: false,
+                negative: true,
+            }
+        );
+    }
+}
@@ -91,6 +91,8 @@ pub fn sixteen_bit_panel(props: &SixteenBitPanelProps) -> Html {
 
     let hex_string = format!("{:04X}", *value);
     let decimal_string = format!("{}", *value);
+    // Only worth showing when it differs from the unsigned value
+    let signed_string = (*value & 0x8000 != 0).then(|| signed_decimal(*value));
 
     html! {
         <div class="sixteen-bit-panel">
@@ -103,6 +105,9 @@ pub fn sixteen_bit_panel(props: &SixteenBitPanelProps) -> Html {
                 <div class="value-display">
                     <span class="hex-value">{"0x"}{&hex_string}</span>
                     <span class="decimal-value">{" = "}{&decimal_string}<sub>{"10"}</sub></span>
+                    if let Some(signed) = &signed_string {
+                        <span class="decimal-value signed-value">{" = "}{signed}<sub>{"10 signed"}</sub></span>
+                    }
                 </div>
             }
 
@@ -164,6 +169,11 @@ pub fn set_bit(value: u16, bit: u8, on: bool) -> u16 {
     }
 }
 
+/// Format a word as a signed (two's complement) decimal number
+pub fn signed_decimal(value: u16) -> String {
+    format!("{}", value as i16)
+}
+
 /// Toggle a single bit in a u16 value
 pub fn toggle_bit(value: u16, bit: u8) -> u16 {
     value ^ (1 << (15 - bit))
@@ -199,4 +209,11 @@ mod tests {
     fn test_panel_mode_default() {
         assert_eq!(PanelMode::default(), PanelMode::Interactive);
     }
+
+    #[test]
+    fn test_signed_decimal() {
+        assert_eq!(signed_decimal(0x0005), "5");
+        assert_eq!(signed_decimal(0xFFFF), "-1");
+        assert_eq!(signed_decimal(0x8000), "-32768");
+    }
 }
@@ -2,7 +2,7 @@
 //
 // Maps Hollerith punch patterns to EBCDIC character codes (80-column format)
 
-use crate::hollerith::HollerithCode;
+use crate::hollerith::{HollerithCode, char_to_hollerith, hollerith_to_char};
 
 /// Convert a Hollerith pattern to an EBCDIC byte
 ///
@@ -12,6 +12,7 @@ use crate::hollerith::HollerithCode;
 /// - Letters J-R: 0xD1-0xD9
 /// - Letters S-Z: 0xE2-0xE9
 /// - Space: 0x40
+/// - Special characters: 0x4B-0x7F
 pub fn hollerith_to_ebcdic(code: &HollerithCode) -> u8 {
     // Check for common patterns
     let rows = &code.rows;
@@ -34,7 +35,7 @@ pub fn hollerith_to_ebcdic(code: &HollerithCode) -> u8 {
             7 => return 0xF7,  // '7'
             8 => return 0xF8,  // '8'
             9 => return 0xF9,  // '9'
-            12 => return 0x4C, // '&' ampersand
+            12 => return 0x50, // '&' ampersand
             11 => return 0x60, // '-' hyphen
             _ => return 0x40,  // default to space
         }
@@ -78,13 +79,44 @@ pub fn hollerith_to_ebcdic(code: &HollerithCode) -> u8 {
 
             // Special characters
             (0, 1) => return 0x61, // '/' slash
+            (2, 8) => return 0x7A, // ':' colon
+            (3, 8) => return 0x7B, // '#' hash
+            (4, 8) => return 0x7C, // '@' at sign
+            (5, 8) => return 0x7D, // '\'' apostrophe
+            (6, 8) => return 0x7E, // '=' equals
+            (7, 8) => return 0x7F, // '"' quote
 
             _ => return 0x40, // default to space
         }
     }
 
-    // Three or more punches (special characters with overpunch)
-    // For now, default to space for unsupported patterns
+    // Three punches (special characters with 8) - sorted order
+    if rows.len() == 3 {
+        match (rows[0], rows[1], rows[2]) {
+            (3, 8, 12) => return 0x4B, // '.' period
+            (4, 8, 12) => return 0x4C, // '<' less than
+            (5, 8, 12) => return 0x4D, // '(' left paren
+            (6, 8, 12) => return 0x4E, // '+' plus
+            (7, 8, 12) => return 0x4F, // '|' vertical bar
+
+            (2, 8, 11) => return 0x5A, // '!' exclamation
+            (3, 8, 11) => return 0x5B, // '$' dollar
+            (4, 8, 11) => return 0x5C, // '*' asterisk
+            (5, 8, 11) => return 0x5D, // ')' right paren
+            (6, 8, 11) => return 0x5E, // ';' semicolon
+            (7, 8, 11) => return 0x5F, // '¬' logical not
+
+            (0, 3, 8) => return 0x6B, // ',' comma
+            (0, 4, 8) => return 0x6C, // '%' percent
+            (0, 5, 8) => return 0x6D, // '_' underscore
+            (0, 6, 8) => return 0x6E, // '>' greater than
+            (0, 7, 8) => return 0x6F, // '?' question mark
+
+            _ => return 0x40, // default to space
+        }
+    }
+
+    // Other multi-punch patterns have no character; default to space
     0x40
 }
 
@@ -141,9 +173,33 @@ pub fn ebcdic_to_hollerith(byte: u8) -> HollerithCode {
         0xE9 => vec![0, 9], // 'Z'
 
         // Special characters
-        0x4C => vec![12],   // '&' ampersand
+        0x50 => vec![12],   // '&' ampersand
         0x60 => vec![11],   // '-' hyphen
         0x61 => vec![0, 1], // '/' slash
+        0x7A => vec![2, 8], // ':' colon
+        0x7B => vec![3, 8], // '#' hash
+        0x7C => vec![4, 8], // '@' at sign
+        0x7D => vec![5, 8], // '\'' apostrophe
+        0x7E => vec![6, 8], // '=' equals
+        0x7F => vec![7, 8], // '"' quote
+
+        // Special characters with 8 punch
+        0x4B => vec![12, 3, 8], // '.' period
+        0x4C => vec![12, 4, 8], // '<' less than
+        0x4D => vec![12, 5, 8], // '(' left paren
+        0x4E => vec![12, 6, 8], // '+' plus
+        0x4F => vec![12, 7, 8], // '|' vertical bar
+        0x5A => vec![11, 2, 8], // '!' exclamation
+        0x5B => vec![11, 3, 8], // '$' dollar
+        0x5C => vec![11, 4, 8], // '*' asterisk
+        0x5D => vec![11, 5, 8], // ')' right paren
+        0x5E => vec![11, 6, 8], // ';' semicolon
+        0x5F => vec![11, 7, 8], // '¬' logical not
+        0x6B => vec![0, 3, 8],  // ',' comma
+        0x6C => vec![0, 4, 8],  // '%' percent
+        0x6D => vec![0, 5, 8],  // '_' underscore
+        0x6E => vec![0, 6, 8],  // '>' greater than
+        0x6F => vec![0, 7, 8],  // '?' question mark
 
         // Default to space for unknown codes
         _ => vec![],
@@ -152,6 +208,27 @@ pub fn ebcdic_to_hollerith(byte: u8) -> HollerithCode {
     HollerithCode::new(rows)
 }
 
+/// Convert a character to its EBCDIC byte
+///
+/// Returns None for characters the keypunch cannot punch.
+pub fn char_to_ebcdic(c: char) -> Option<u8> {
+    char_to_hollerith(c.to_ascii_uppercase()).map(|code| hollerith_to_ebcdic(&code))
+}
+
+/// Convert an EBCDIC byte to the character it represents
+///
+/// Returns None for codes with no keypunch character.
+pub fn ebcdic_to_char(byte: u8) -> Option<char> {
+    if byte == 0x40 {
+        return Some(' ');
+    }
+    let code = ebcdic_to_hollerith(byte);
+    if code.rows.is_empty() {
+        return None;
+    }
+    hollerith_to_char(&code)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,4 +305,30 @@ mod tests {
             assert_eq!(result, ebcdic, "Roundtrip failed for 0x{:02X}", ebcdic);
         }
     }
+
+    #[test]
+    fn test_ebcdic_special_characters() {
+        assert_eq!(ebcdic_to_char(0x4B), Some('.'));
+        assert_eq!(ebcdic_to_char(0x50), Some('&'));
+        assert_eq!(ebcdic_to_char(0x7E), Some('='));
+        assert_eq!(char_to_ebcdic('.'), Some(0x4B));
+        assert_eq!(char_to_ebcdic('&'), Some(0x50));
+        assert_eq!(char_to_ebcdic('a'), Some(0xC1));
+        assert_eq!(char_to_ebcdic('~'), None);
+        assert_eq!(ebcdic_to_char(0x00), None);
+    }
+
+    #[test]
+    fn test_ebcdic_char_roundtrip() {
+        for byte in 0x40..=0xFF {
+            if let Some(c) = ebcdic_to_char(byte) {
+                assert_eq!(
+                    char_to_ebcdic(c),
+                    Some(byte),
+                    "Roundtrip failed for {:?}",
+                    c
+                );
+            }
+        }
+    }
 }
@@ -2,7 +2,7 @@
 //
 // Data structures and operations for IBM punch cards
 
-use crate::ebcdic::{ebcdic_to_hollerith, hollerith_to_ebcdic};
+use crate::ebcdic::{ebcdic_to_char, ebcdic_to_hollerith, hollerith_to_ebcdic};
 use crate::hollerith::{HollerithCode, char_to_hollerith, hollerith_to_char};
 use serde::{Deserialize, Serialize};
 
@@ -195,18 +195,9 @@ impl PunchCard {
 
         for (i, &ebcdic_byte) in data.iter().take(80).enumerate() {
             let hollerith = ebcdic_to_hollerith(ebcdic_byte);
-            // Determine the printed character from the EBCDIC code
-            let printed_char = match ebcdic_byte {
-                0x40 => Some(' '),
-                0xF0..=0xF9 => Some((b'0' + (ebcdic_byte - 0xF0)) as char),
-                0xC1..=0xC9 => Some((b'A' + (ebcdic_byte - 0xC1)) as char),
-                0xD1..=0xD9 => Some((b'J' + (ebcdic_byte - 0xD1)) as char),
-                0xE2..=0xE9 => Some((b'S' + (ebcdic_byte - 0xE2)) as char),
-                _ => None,
-            };
             card.columns[i] = Column {
                 punches: hollerith,
-                printed_char,
+                printed_char: ebcdic_to_char(ebcdic_byte),
             };
         }
 
@@ -6,25 +6,35 @@
 use components::{
     // Assembler game components
     Header, LegendItem, Modal, ProgramArea, Register, RegisterPanel, Sidebar, SidebarButton,
-    WordMemoryViewer,
+    WordMemoryViewer, acc_flags,
     // Tab container
     Tab, TabContainer, TabNav,
     // Console panel components
-    ConsolePanel, Registers as ConsoleRegisters,
+    ConsolePanel, ControlState as ConsoleControl, Registers as ConsoleRegisters, SpeedMode,
+    speed_to_batch, RUN_BATCH,
     // Keypunch component
     Keypunch, Deck,
     // Printer component
-    Printer, sample_assembler_listing,
+    Printer, DEFAULT_PRINTER_COLUMNS, sample_assembler_listing,
 };
 use yew::prelude::*;
 
+use crate::assembler::Assembler;
 use crate::challenge::{Challenge, get_all_challenges};
-use crate::wasm::WasmCpu;
+use crate::wasm::{RunStatus, WasmCpu};
 
 #[function_component(App)]
 pub fn app() -> Html {
     // CPU state
-    let cpu = use_state(WasmCpu::new);
+    let cpu = use_state(|| {
+        // Watch the memory-mapped index registers (XR1-XR3 at word addresses 1, 2, 3)
+        let mut cpu = WasmCpu::new();
+        for addr in 1..=3 {
+            cpu.set_watch(addr);
+        }
+        cpu.set_history_enabled(true);
+        cpu
+    });
 
     // Editor code
     let editor_code = use_state(|| {
@@ -36,6 +46,13 @@ pub fn app() -> Html {
     // Assembly output (Vec<String> for line-by-line display with highlighting)
     let assembly_lines = use_state(Vec::<String>::new);
 
+    // Symbol table from the last assembly, one formatted line per symbol
+    let symbol_lines = use_state(Vec::<String>::new);
+
+    // Problems found by checking the editor text as it is typed
+    let source_problems = use_state(Vec::<String>::new);
+    let validate_timer = use_mut_ref(|| None::<gloo::timers::callback::Timeout>);
+
     // Register change tracking
     let last_acc = use_state(|| 0u16);
     let last_ext = use_state(|| 0u16);
@@ -69,6 +86,8 @@ pub fn app() -> Html {
 
     // Keypunch deck state
     let keypunch_deck = use_state(Deck::default);
+    // Deck handed to the keypunch from elsewhere (e.g. a punched object deck)
+    let punched_deck = use_state(|| None::<Deck>);
 
     // Printer state - lines to print
     let printer_content = use_state(sample_assembler_listing);
@@ -81,39 +100,28 @@ pub fn app() -> Html {
     let example_2 = "       ORG  16        ; Start program at address 16\n; Example 2: Indexed Addressing\n       LD   0 40      ; Load index value (3)\n       STX  1         ; Store in XR1\n       LD   1 30      ; Load from 30+XR1 = 33\n       WAIT\n\n       ORG  30        ; Data section\n       DATA 30 100\n       DATA 31 200\n       DATA 32 300\n       DATA 33 400    ; This will be loaded\n       DATA 40 3      ; Index value";
     let example_3 = "       ORG  16        ; Start program at address 16\n; Example 3: Shift Operations\n       LD   0 30      ; Load value (5)\n       SLA  2         ; Shift left 2 bits (5 * 4 = 20)\n       STO  0 31      ; Store result\n       WAIT\n\n       ORG  30        ; Data section\n       DATA 30 5\n       DATA 31 0";
     let example_4 = "       ORG  16        ; Start program at address 16\n; Example 4: Memory-Mapped Registers\n; IBM 1130 stores index registers XR1, XR2, XR3 in memory at word addresses 1, 2, 3\n; Watch these memory locations highlight when registers change!\n\n       LD   0 40      ; Load 100 into ACC\n       STO  0 1       ; Store to XR1 (word address 1)\n       LD   0 41      ; Load 200 into ACC\n       STO  0 2       ; Store to XR2 (word address 2)\n       LD   0 42      ; Load 300 into ACC\n       STO  0 3       ; Store to XR3 (word address 3)\n\n; Now use XR1 for indexed addressing\n       LD   1 50      ; Load from 50+XR1 = 150\n       WAIT\n\n       ORG  40        ; Data section\n       DATA 40 100    ; Value for XR1\n       DATA 41 200    ; Value for XR2\n       DATA 42 300    ; Value for XR3\n       DATA 150 999   ; Value at indexed address";
-    let example_5 = "       ORG  16        ; Start program at address 16\n; Example 5: Special Memory Locations\n; IBM 1130 Architecture:\n;   Location 0: Safety trap (infinite loop if program crashes)\n;   Locations 1-3: Index registers XR1, XR2, XR3\n;   Locations 8-13: Interrupt vectors (priority levels 0-5)\n; By convention, location 0 contains an infinite loop instruction.\n; If a program jumps to 0 (error condition), IAR lights go dark.\n\n; This example demonstrates the special locations:\n       LD   0 50      ; Load a value\n       STO  0 1       ; Store to XR1 (location 1)\n       STO  0 2       ; Store to XR2 (location 2)\n       STO  0 3       ; Store to XR3 (location 3)\n       WAIT\n\n       ORG  50        ; Data section\n       DATA 50 42     ; Test value\n\n; Note: In a real 1130, location 0 would contain:\n; DATA 0 0x4800  ; Opcode for 'BSI 0' (branch to self)";
+    let example_5 = "       ORG  16        ; Start program at address 16\n; Example 5: Special Memory Locations\n; IBM 1130 Architecture:\n;   Location 0: Safety trap (infinite loop if program crashes)\n;   Locations 1-3: Index registers XR1, XR2, XR3\n;   Locations 8-13: Interrupt vectors (priority levels 0-5)\n; By convention, location 0 contains an infinite loop instruction.\n; If a program jumps to 0 (error condition), IAR lights go dark.\n\n; This example demonstrates the special locations:\n       LD   0 50      ; Load a value\n       STO  0 1       ; Store to XR1 (location 1)\n       STO  0 2       ; Store to XR2 (location 2)\n       STO  0 3       ; Store to XR3 (location 3)\n       WAIT\n\n       ORG  50        ; Data section\n       DATA 50 42     ; Test value\n\n; Note: Hard reset seeds location 0 with BRL 0 (0xC100), a branch to itself.\n; A program that executes or stores there stops with a protection fault.";
 
     // Callbacks for ProgramArea
     let on_assemble = {
         let cpu = cpu.clone();
         let assembly_lines = assembly_lines.clone();
+        let symbol_lines = symbol_lines.clone();
+        let source_problems = source_problems.clone();
         let error_message = error_message.clone();
         Callback::from(move |code: String| {
             let mut cpu_mut = (*cpu).clone();
-            cpu_mut.hard_reset();
-
-            // Parse DATA directives and load them into memory
-            for line in code.lines() {
-                let trimmed = line.trim();
-                if trimmed.to_uppercase().starts_with("DATA") {
-                    let parts: Vec<&str> = trimmed.split_whitespace().collect();
-                    if parts.len() >= 3
-                        && let (Ok(addr), Ok(value)) =
-                            (parts[1].parse::<u16>(), parts[2].parse::<u16>())
-                        {
-                            let _ = cpu_mut.write_memory(addr, value);
-                        }
-                }
-            }
 
-            // Assemble the program (load at PROGRAM_START = 0x0010)
-            match cpu_mut.assemble(code, crate::cpu::PROGRAM_START) {
-                Ok(listing_js) => {
-                    // Parse the listing
-                    if let Ok(listing) =
-                        serde_wasm_bindgen::from_value::<Vec<serde_json::Value>>(listing_js)
+            // Assemble the program and its DATA words (start at PROGRAM_START = 0x0010)
+            match cpu_mut.assemble_and_load(code, crate::cpu::PROGRAM_START) {
+                Ok(result_js) => {
+                    // Parse the listing and symbol table
+                    if let Ok(result) =
+                        serde_wasm_bindgen::from_value::<serde_json::Value>(result_js)
                     {
-                        let lines: Vec<String> = listing
+                        let entries =
+                            |key: &str| result[key].as_array().cloned().unwrap_or_default();
+                        let lines: Vec<String> = entries("listing")
                             .iter()
                             .map(|line| {
                                 let addr = line["address"].as_u64().unwrap_or(0) as u16;
@@ -123,18 +131,54 @@ pub fn app() -> Html {
                             })
                             .collect();
                         assembly_lines.set(lines);
+                        let symbols: Vec<String> = entries("symbols")
+                            .iter()
+                            .map(|symbol| {
+                                let name = symbol["name"].as_str().unwrap_or("");
+                                let value = symbol["value"].as_u64().unwrap_or(0) as u16;
+                                format!("{:<8} {:04} (0x{:04X})", name, value, value)
+                            })
+                            .collect();
+                        symbol_lines.set(symbols);
+                        // Warnings don't stop the load; list them under the editor
+                        let warnings: Vec<String> = entries("warnings")
+                            .iter()
+                            .map(|warning| {
+                                let message = warning["message"].as_str().unwrap_or("");
+                                format!("Warning: {}", message)
+                            })
+                            .collect();
+                        source_problems.set(warnings);
                     }
                     cpu.set(cpu_mut);
                     error_message.set(None);
                 }
                 Err(e) => {
-                    error_message.set(Some(format!("Assembly error: {:?}", e)));
+                    error_message.set(Some(format!("Assembly error: {}", error_text(&e))));
                     assembly_lines.set(Vec::new());
                 }
             }
         })
     };
 
+    // Check the source once typing pauses; a new edit replaces (cancels) the pending check
+    let on_edit = {
+        let source_problems = source_problems.clone();
+        let validate_timer = validate_timer.clone();
+        Callback::from(move |code: String| {
+            let source_problems = source_problems.clone();
+            let timeout = gloo::timers::callback::Timeout::new(400, move || {
+                let problems = Assembler::new()
+                    .validate(&code)
+                    .into_iter()
+                    .map(|(line, error)| format!("Line {}: {}", line, error))
+                    .collect();
+                source_problems.set(problems);
+            });
+            *validate_timer.borrow_mut() = Some(timeout);
+        })
+    };
+
     let on_step = {
         let cpu = cpu.clone();
         let error_message = error_message.clone();
@@ -150,45 +194,37 @@ pub fn app() -> Html {
             let mut cpu_mut = (*cpu).clone();
 
             // Save current state for change tracking
-            let prev_xr1 = cpu_mut.get_xr1();
-            let prev_xr2 = cpu_mut.get_xr2();
-            let prev_xr3 = cpu_mut.get_xr3();
-
             last_acc.set(cpu_mut.get_acc());
             last_ext.set(cpu_mut.get_ext());
             last_iar.set(cpu_mut.get_iar());
-            last_xr1.set(prev_xr1);
-            last_xr2.set(prev_xr2);
-            last_xr3.set(prev_xr3);
-
-            let iar = cpu_mut.get_iar();
+            last_xr1.set(cpu_mut.get_xr1());
+            last_xr2.set(cpu_mut.get_xr2());
+            last_xr3.set(cpu_mut.get_xr3());
+            // Drop changes from loads and deposits so only this step is highlighted
+            cpu_mut.take_changed_watches();
+
+            if let Err(e) = cpu_mut.step_auto() {
+                error_message.set(Some(format!("Execution error: {}", error_text(&e))));
+            } else {
+                error_message.set(None);
 
-            match cpu_mut.read_memory(iar) {
-                Ok(opcode) => {
-                    if let Err(e) = cpu_mut.step(opcode) {
-                        error_message.set(Some(format!("Execution error: {:?}", e)));
-                    } else {
-                        error_message.set(None);
+                let changed = cpu_mut.take_changed_watches();
+                changed_memory.set(changed.into_iter().map(usize::from).collect());
+            }
+            cpu.set(cpu_mut);
+        })
+    };
 
-                        // Track memory-mapped register changes (XR1@0x0001, XR2@0x0002, XR3@0x0003)
-                        // IBM 1130 uses word addressing - XR1, XR2, XR3 are at word addresses 1, 2, 3
-                        let mut changed = Vec::new();
-                        if cpu_mut.get_xr1() != prev_xr1 {
-                            changed.push(1); // XR1 at word address 1
-                        }
-                        if cpu_mut.get_xr2() != prev_xr2 {
-                            changed.push(2); // XR2 at word address 2
-                        }
-                        if cpu_mut.get_xr3() != prev_xr3 {
-                            changed.push(3); // XR3 at word address 3
-                        }
-                        changed_memory.set(changed);
-                    }
-                    cpu.set(cpu_mut);
-                }
-                Err(e) => {
-                    error_message.set(Some(format!("Memory read error: {:?}", e)));
-                }
+    let on_step_back = {
+        let cpu = cpu.clone();
+        let error_message = error_message.clone();
+        let changed_memory = changed_memory.clone();
+        Callback::from(move |_| {
+            let mut cpu_mut = (*cpu).clone();
+            if cpu_mut.step_back() {
+                error_message.set(None);
+                changed_memory.set(Vec::new());
+                cpu.set(cpu_mut);
             }
         })
     };
@@ -196,21 +232,46 @@ pub fn app() -> Html {
     let on_run = {
         let cpu = cpu.clone();
         let error_message = error_message.clone();
+        let last_acc = last_acc.clone();
+        let last_ext = last_ext.clone();
         Callback::from(move |_| {
             let mut cpu_mut = (*cpu).clone();
-            match cpu_mut.run(10000) {
+
+            // Highlight ACC/EXT relative to their values before this run
+            last_acc.set(cpu_mut.get_acc());
+            last_ext.set(cpu_mut.get_ext());
+
+            match cpu_mut.run(RUN_BATCH) {
+                Ok(RunStatus::BudgetExhausted) => {
+                    error_message
+                        .set(Some(format!("Program did not halt within {RUN_BATCH} cycles")));
+                }
                 Ok(_) => {
                     error_message.set(None);
                 }
                 Err(e) => {
-                    error_message.set(Some(format!("Run error: {:?}", e)));
+                    error_message.set(Some(format!("Run error: {}", error_text(&e))));
                 }
             }
             cpu.set(cpu_mut);
         })
     };
 
+    // Reset runs the program again from the top; Clear also wipes it from memory
     let on_reset = {
+        let cpu = cpu.clone();
+        let error_message = error_message.clone();
+        let changed_memory = changed_memory.clone();
+        Callback::from(move |_| {
+            let mut cpu_mut = (*cpu).clone();
+            cpu_mut.reset_reload();
+            cpu.set(cpu_mut);
+            error_message.set(None);
+            changed_memory.set(Vec::new());
+        })
+    };
+
+    let on_clear = {
         let cpu = cpu.clone();
         let error_message = error_message.clone();
         let assembly_lines = assembly_lines.clone();
@@ -320,21 +381,24 @@ pub fn app() -> Html {
             assembly_lines.set(Vec::new());
             error_message.set(None);
 
-            // Load challenge template
-            let mut code = format!(
-                "; {}\n; {}\n\n",
-                challenge.title,
-                challenge.description.lines().next().unwrap_or("")
-            );
-
-            // Add initial data if present
-            if !challenge.test_cases.is_empty()
-                && !challenge.test_cases[0].initial_memory.is_empty() {
-                    code.push_str("; Initial data:\n");
-                    for (addr, value) in &challenge.test_cases[0].initial_memory {
-                        code.push_str(&format!("DATA 0x{:02X} {}\n", addr, value));
+            // Load the challenge's starter code, or generate a template
+            let code = challenge.starter_code.clone().unwrap_or_else(|| {
+                let mut code = format!(
+                    "; {}\n; {}\n\n",
+                    challenge.title,
+                    challenge.description.lines().next().unwrap_or("")
+                );
+
+                // Add initial data if present
+                if !challenge.test_cases.is_empty()
+                    && !challenge.test_cases[0].initial_memory.is_empty() {
+                        code.push_str("; Initial data:\n");
+                        for (addr, value) in &challenge.test_cases[0].initial_memory {
+                            code.push_str(&format!("DATA 0x{:02X} {}\n", addr, value));
+                        }
                     }
-                }
+                code
+            });
 
             editor_code.set(code);
             current_challenge.set(Some(challenge.clone()));
@@ -345,12 +409,14 @@ pub fn app() -> Html {
     // Check solution callback
     let check_solution = {
         let cpu = cpu.clone();
+        let editor_code = editor_code.clone();
         let current_challenge = current_challenge.clone();
         let challenge_result = challenge_result.clone();
 
         Callback::from(move |_: MouseEvent| {
             if let Some(challenge) = (*current_challenge).as_ref() {
-                match challenge.validate_solution((*cpu).cpu_state()) {
+                // Assemble and run the editor's code afresh for every test case
+                match (*cpu).run_challenge_solution(challenge.id, &editor_code) {
                     Ok(validation) => {
                         if validation.passed {
                             let mut message =
@@ -447,25 +513,98 @@ pub fn app() -> Html {
         let editor_code = editor_code.clone();
         let active_tab = active_tab.clone();
         Callback::from(move |_: MouseEvent| {
-            // Convert deck cards to assembly source
-            let code: String = keypunch_deck.cards.iter()
-                .map(|card| card.to_text().trim_end().to_string())
-                .collect::<Vec<_>>()
-                .join("\n");
-            editor_code.set(code);
+            // Convert deck cards to assembly source using the deck's encoding
+            editor_code.set(keypunch_deck.to_source());
             active_tab.set(Tab::Assembler);
         })
     };
 
+    // Keypunch: Load binary deck straight into memory, bypassing the assembler
+    let load_deck_to_memory = {
+        let keypunch_deck = keypunch_deck.clone();
+        let cpu = cpu.clone();
+        let error_message = error_message.clone();
+        let active_tab = active_tab.clone();
+        Callback::from(move |_: MouseEvent| {
+            let start = crate::cpu::PROGRAM_START;
+            let mut cpu_mut = (*cpu).clone();
+
+            // Object decks carry their own load addresses; other binary decks
+            // are loaded as a plain word image at the program start
+            let result = match keypunch_deck.to_placements() {
+                Ok(placements) if !placements.is_empty() => placements
+                    .iter()
+                    .try_for_each(|&(addr, word)| cpu_mut.write_memory(addr, word))
+                    .and_then(|_| cpu_mut.set_iar(placements[0].0)),
+                _ => {
+                    let words = keypunch_deck.to_words();
+                    if words.is_empty() {
+                        error_message.set(Some("Deck has no binary cards to load".to_string()));
+                        return;
+                    }
+                    cpu_mut
+                        .load_program(start, words)
+                        .and_then(|_| cpu_mut.set_iar(start))
+                }
+            };
+            match result {
+                Ok(()) => {
+                    error_message.set(None);
+                    cpu.set(cpu_mut);
+                    active_tab.set(Tab::Console);
+                }
+                Err(e) => error_message.set(Some(format!("Load error: {}", error_text(&e)))),
+            }
+        })
+    };
+
+    // Assembler: Punch the assembled program as an object deck
+    let punch_object_deck = {
+        let editor_code = editor_code.clone();
+        let punched_deck = punched_deck.clone();
+        let keypunch_deck = keypunch_deck.clone();
+        let error_message = error_message.clone();
+        let active_tab = active_tab.clone();
+        Callback::from(move |_: MouseEvent| {
+            match crate::assembler::Assembler::new().assemble(&editor_code) {
+                Ok(program) => {
+                    let deck = Deck::from_placements(&program.placements);
+                    error_message.set(None);
+                    keypunch_deck.set(deck.clone());
+                    punched_deck.set(Some(deck));
+                    active_tab.set(Tab::Keypunch);
+                }
+                Err(e) => error_message.set(Some(format!("Assembly error: {}", e))),
+            }
+        })
+    };
+
     // Assembler: Send listing to printer
     let send_to_printer = {
         let assembly_lines = assembly_lines.clone();
+        let editor_code = editor_code.clone();
         let printer_content = printer_content.clone();
+        let error_message = error_message.clone();
         let active_tab = active_tab.clone();
         Callback::from(move |_: MouseEvent| {
             if !assembly_lines.is_empty() {
-                printer_content.set((*assembly_lines).clone());
-                active_tab.set(Tab::Printer);
+                match crate::assembler::Assembler::new().assemble(&editor_code) {
+                    Ok(program) => {
+                        printer_content.set(program.to_printer_listing());
+                        active_tab.set(Tab::Printer);
+                    }
+                    Err(e) => error_message.set(Some(format!("Assembly error: {}", e))),
+                }
+            }
+        })
+    };
+
+    // Assembler: Copy the disassembled program to the clipboard
+    let copy_listing = {
+        let cpu = cpu.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(window) = web_sys::window() {
+                let _ = window.navigator().clipboard().write_text(&cpu.listing_text());
             }
         })
     };
@@ -572,9 +711,7 @@ pub fn app() -> Html {
     let flags_html = if let Some(state) = &cpu_state {
         let carry = state["carry"].as_bool().unwrap_or(false);
         let overflow = state["overflow"].as_bool().unwrap_or(false);
-        let acc = state["acc"].as_u64().unwrap_or(0) as u16;
-        let positive = (acc & 0x8000) == 0 && acc != 0;
-        let zero = acc == 0;
+        let flags = acc_flags(state["acc"].as_u64().unwrap_or(0) as u16);
 
         html! {
             <div class="flags">
@@ -587,13 +724,17 @@ pub fn app() -> Html {
                     <span>{"V (Overflow)"}</span>
                 </div>
                 <div class="flag">
-                    <div class={if positive { "flag-indicator set" } else { "flag-indicator" }}></div>
+                    <div class={if flags.positive { "flag-indicator set" } else { "flag-indicator" }}></div>
                     <span>{"P (Positive)"}</span>
                 </div>
                 <div class="flag">
-                    <div class={if zero { "flag-indicator set" } else { "flag-indicator" }}></div>
+                    <div class={if flags.zero { "flag-indicator set" } else { "flag-indicator" }}></div>
                     <span>{"Z (Zero)"}</span>
                 </div>
+                <div class="flag">
+                    <div class={if flags.negative { "flag-indicator set" } else { "flag-indicator" }}></div>
+                    <span>{"N (Negative)"}</span>
+                </div>
             </div>
         }
     } else {
@@ -636,10 +777,10 @@ pub fn app() -> Html {
                 .map(|v| v.as_u64().unwrap_or(0) as u16)
                 .collect::<Vec<u16>>()
         } else {
-            vec![0; 4096] // 4K words
+            vec![0; cpu.memory_words()]
         }
     } else {
-        vec![0; 4096] // 4K words
+        vec![0; cpu.memory_words()]
     };
 
     // IAR contains word address - use directly for word-based memory viewer
@@ -686,13 +827,20 @@ pub fn app() -> Html {
     // === TAB CONTENTS ===
 
     // Keypunch Tab Content
+    let on_deck_change = {
+        let keypunch_deck = keypunch_deck.clone();
+        Callback::from(move |deck: Deck| keypunch_deck.set(deck))
+    };
     let keypunch_content_html = html! {
         <div class="keypunch-tab">
-            <Keypunch />
+            <Keypunch on_deck_change={on_deck_change} deck={(*punched_deck).clone()} />
             <div class="keypunch-actions">
                 <button class="load-to-assembler-btn" onclick={load_deck_to_editor.clone()}>
                     {"Load Deck → Assembler"}
                 </button>
+                <button class="load-to-assembler-btn" onclick={load_deck_to_memory.clone()}>
+                    {"Load Deck → Memory"}
+                </button>
             </div>
         </div>
     };
@@ -704,6 +852,7 @@ pub fn app() -> Html {
                 content={(*printer_content).clone()}
                 auto_start={false}
                 sound_enabled={true}
+                columns={DEFAULT_PRINTER_COLUMNS}
             />
         </div>
     };
@@ -718,12 +867,93 @@ pub fn app() -> Html {
                 help_active.set(false);
             })
         };
+        let on_console_deposit = {
+            let cpu = cpu.clone();
+            Callback::from(move |(addr, value): (u16, u16)| {
+                let mut cpu_mut = (*cpu).clone();
+                if cpu_mut.write_memory(addr, value).is_ok() {
+                    cpu.set(cpu_mut);
+                }
+            })
+        };
+        let on_console_examine = {
+            let cpu = cpu.clone();
+            Callback::from(move |addr: u16| cpu.read_memory(addr).unwrap_or(0))
+        };
+        let on_console_load = {
+            let cpu = cpu.clone();
+            let error_message = error_message.clone();
+            Callback::from(move |addr: u16| {
+                let mut cpu_mut = (*cpu).clone();
+                match cpu_mut.set_iar(addr) {
+                    Ok(()) => {
+                        error_message.set(None);
+                        cpu.set(cpu_mut);
+                    }
+                    Err(e) => {
+                        error_message.set(Some(format!("Load IAR error: {}", error_text(&e))))
+                    }
+                }
+            })
+        };
+        let on_console_load_acc = {
+            let cpu = cpu.clone();
+            Callback::from(move |value: u16| {
+                let mut cpu_mut = (*cpu).clone();
+                cpu_mut.set_acc(value);
+                cpu.set(cpu_mut);
+            })
+        };
+        let console_control = ConsoleControl {
+            wait: cpu.is_halted(),
+            carry: cpu_state.as_ref().and_then(|s| s["carry"].as_bool()).unwrap_or(false),
+            overflow: cpu_state.as_ref().and_then(|s| s["overflow"].as_bool()).unwrap_or(false),
+            ..ConsoleControl::default()
+        };
+        let on_console_start = {
+            let cpu = cpu.clone();
+            let error_message = error_message.clone();
+            Callback::from(move |mode: SpeedMode| {
+                let mut cpu_mut = (*cpu).clone();
+                // DISP and LOAD are storage modes, not execution modes
+                let batch = speed_to_batch(mode);
+                if batch == 0 {
+                    return;
+                }
+                cpu_mut.resume();
+                match cpu_mut.run(batch) {
+                    // Single-step modes stop after one instruction by design
+                    Ok(RunStatus::BudgetExhausted) if mode.runs_continuously() => error_message
+                        .set(Some(format!("Program did not halt within {batch} cycles"))),
+                    Ok(_) => error_message.set(None),
+                    Err(e) => error_message.set(Some(format!("Run error: {}", error_text(&e)))),
+                }
+                cpu.set(cpu_mut);
+            })
+        };
+        let on_console_start_stop = {
+            let cpu = cpu.clone();
+            Callback::from(move |running: bool| {
+                if !running {
+                    let mut cpu_mut = (*cpu).clone();
+                    cpu_mut.halt();
+                    cpu.set(cpu_mut);
+                }
+            })
+        };
         html! {
             <div class="console-tab">
                 <ConsolePanel
                     external_registers={Some(console_registers)}
                     help_active={help_is_active}
                     on_power_click={on_power_click}
+                    external_control={Some(console_control)}
+                    on_load={on_console_load}
+                    on_load_acc={on_console_load_acc}
+                    on_deposit={on_console_deposit}
+                    on_examine={on_console_examine}
+                    on_program_start={on_console_start}
+                    on_start_stop={on_console_start_stop}
                 />
             </div>
         }
@@ -768,9 +998,22 @@ pub fn app() -> Html {
                             on_step={on_step}
                             on_run={on_run}
                             on_reset={on_reset}
+                            on_clear={Some(on_clear)}
+                            on_step_back={Some(on_step_back)}
                             step_enabled={!cpu.is_halted() && !assembly_lines.is_empty()}
                             run_enabled={!cpu.is_halted() && !assembly_lines.is_empty()}
+                            step_back_enabled={cpu.history_len() > 0}
+                            on_edit={Some(on_edit)}
                         />
+                        {if source_problems.is_empty() {
+                            html! {}
+                        } else {
+                            html! {
+                                <div class="validation-errors">
+                                    {for source_problems.iter().map(|problem| html! { <div>{problem}</div> })}
+                                </div>
+                            }
+                        }}
                     </div>
 
                     // Output Panel (scrollable)
@@ -805,6 +1048,16 @@ pub fn app() -> Html {
                             >
                                 {"Send Listing → Printer"}
                             </button>
+                            <button
+                                class="send-to-printer-btn"
+                                onclick={copy_listing}
+                                disabled={assembly_lines.is_empty()}
+                            >
+                                {"Copy Listing"}
+                            </button>
+                            <button class="send-to-printer-btn" onclick={punch_object_deck}>
+                                {"Punch Object Deck → Keypunch"}
+                            </button>
                         </div>
                     </div>
                 </div>
@@ -826,9 +1079,9 @@ pub fn app() -> Html {
                         <WordMemoryViewer
                             memory={memory_words}
                             pc={pc}
-                            title={Some("Memory (4K Words)".to_string())}
+                            title={Some(format!("Memory ({}K Words)", cpu.memory_words() / 1024))}
                             words_per_row={8}
-                            words_to_show={4096}
+                            words_to_show={cpu.memory_words()}
                             changed_addresses={(*changed_memory).clone()}
                         />
                     </div>
@@ -891,7 +1144,7 @@ pub fn app() -> Html {
                     <li><strong>{"EXT"}</strong>{" - Extension register: for double-precision operations"}</li>
                     <li><strong>{"IAR"}</strong>{" - Instruction Address Register: program counter"}</li>
                     <li><strong>{"XR1-XR3"}</strong>{" - Index Registers: for indexed addressing (memory-mapped)"}</li>
-                    <li><strong>{"Flags"}</strong>{" - C (Carry), V (Overflow), P (Positive), Z (Zero)"}</li>
+                    <li><strong>{"Flags"}</strong>{" - C (Carry), V (Overflow), P (Positive), Z (Zero), N (Negative)"}</li>
                 </ul>
 
                 <h3>{"Getting Started"}</h3>
@@ -908,8 +1161,20 @@ pub fn app() -> Html {
                 <pre>{"MNEMONIC mode address   ; comment"}</pre>
                 <p>{"Example:"}</p>
                 <pre>{"LD 0 10    ; Load from address 10 (direct mode)\nA 1 20     ; Add from address 20+XR1 (indexed mode)"}</pre>
+                <p>{"Numbers are decimal unless prefixed: 0x for hex, 0b for binary, 0o for octal:"}</p>
+                <pre>{"AND 0 =0b1111 ; Keep the low four bits\nSLA 0o10      ; Shift left 8"}</pre>
                 <p>{"Data directives:"}</p>
-                <pre>{"DATA address value   ; Store value at address"}</pre>
+                <pre>{"DATA address value   ; Store value at address\nDATA address 1 2 3   ; Store values at consecutive addresses\nTEXT \"HELLO\"         ; Pack card characters two per word\nBUF: BSS 10          ; Reserve 10 uninitialized words\nALIGN 8              ; Skip to the next multiple of 8"}</pre>
+                <p>{"Literals write a constant in place of its address; the assembler stores each distinct value once after the program:"}</p>
+                <pre>{"A 0 =5      ; Add the constant 5\nAND 0 =0xFF ; Keep the low byte\nLDI -1      ; Load ACC with -1"}</pre>
+                <p>{"Labels name an address and can be used in place of a number:"}</p>
+                <pre>{"LOOP:  A 0 30      ; Label ends with a colon\n       BSC NZ LOOP  ; Branch back to LOOP"}</pre>
+                <p>{"END names the entry point when execution should not begin at the first word:"}</p>
+                <pre>{"       END  MAIN     ; Start execution at the label MAIN"}</pre>
+                <p>{"Conditional assembly keeps a block only when an EQU symbol is non-zero:"}</p>
+                <pre>{"DEBUG  EQU 1\n       IF DEBUG     ; Assembled because DEBUG is non-zero\n       STO 0 40\n       ELSE         ; Optional\n       NOP\n       ENDIF"}</pre>
+                <p>{"Macros name a sequence of lines; writing the name expands it in place:"}</p>
+                <pre>{"       MACRO TIMES4  ; Define TIMES4\n       SLA 2\n       ENDM\n       TIMES4        ; Assembles as SLA 2"}</pre>
             </Modal>
 
             <Modal id="examples" title="Example Programs" active={*examples_open} on_close={close_examples}>
@@ -983,11 +1248,31 @@ pub fn app() -> Html {
                         <td>{"Load ACC from memory"}</td>
                         <td>{"ACC ← memory[addr]"}</td>
                     </tr>
+                    <tr>
+                        <td><code>{"LDI value"}</code></td>
+                        <td>{"Load constant (assembler macro: LD from a word the assembler places after the program)"}</td>
+                        <td>{"ACC ← value"}</td>
+                    </tr>
                     <tr>
                         <td><code>{"STO mode addr"}</code></td>
                         <td>{"Store ACC to memory"}</td>
                         <td>{"memory[addr] ← ACC"}</td>
                     </tr>
+                    <tr>
+                        <td><code>{"LDS mode addr"}</code></td>
+                        <td>{"Load ACC, sign-extended into EXT"}</td>
+                        <td>{"ACC ← memory[addr]; EXT ← 0x0000 or 0xFFFF (ACC's sign)"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"LDD mode addr"}</code></td>
+                        <td>{"Load double word"}</td>
+                        <td>{"ACC ← memory[addr]; EXT ← memory[addr+1]"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"STD mode addr"}</code></td>
+                        <td>{"Store double word"}</td>
+                        <td>{"memory[addr] ← ACC; memory[addr+1] ← EXT"}</td>
+                    </tr>
                     <tr>
                         <td><code>{"LDX addr"}</code></td>
                         <td>{"Load XR1 from memory"}</td>
@@ -1003,46 +1288,116 @@ pub fn app() -> Html {
                         <td>{"Add to ACC"}</td>
                         <td>{"ACC ← ACC + memory[addr]"}</td>
                     </tr>
+                    <tr>
+                        <td><code>{"AC mode addr"}</code></td>
+                        <td>{"Add to ACC with carry in (multi-word addition)"}</td>
+                        <td>{"ACC ← ACC + memory[addr] + C"}</td>
+                    </tr>
                     <tr>
                         <td><code>{"S mode addr"}</code></td>
                         <td>{"Subtract from ACC"}</td>
                         <td>{"ACC ← ACC - memory[addr]"}</td>
                     </tr>
+                    <tr>
+                        <td><code>{"SC mode addr"}</code></td>
+                        <td>{"Subtract from ACC with borrow in (multi-word subtraction)"}</td>
+                        <td>{"ACC ← ACC - memory[addr] - C"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"M mode addr"}</code></td>
+                        <td>{"Multiply ACC (32-bit product)"}</td>
+                        <td>{"ACC:EXT ← ACC × memory[addr]"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"D mode addr"}</code></td>
+                        <td>{"Divide ACC:EXT (V set on divide by zero)"}</td>
+                        <td>{"ACC ← ACC:EXT ÷ memory[addr]; EXT ← remainder"}</td>
+                    </tr>
                     <tr>
                         <td><code>{"AND mode addr"}</code></td>
                         <td>{"Logical AND with ACC"}</td>
-                        <td>{"ACC ← ACC & memory[addr]"}</td>
+                        <td>{"ACC ← ACC & memory[addr]; clears overflow, carry unchanged"}</td>
                     </tr>
                     <tr>
                         <td><code>{"OR mode addr"}</code></td>
                         <td>{"Logical OR with ACC"}</td>
-                        <td>{"ACC ← ACC | memory[addr]"}</td>
+                        <td>{"ACC ← ACC | memory[addr]; clears overflow, carry unchanged"}</td>
                     </tr>
                     <tr>
                         <td><code>{"SLA count"}</code></td>
-                        <td>{"Shift left ACC"}</td>
-                        <td>{"ACC ← ACC << count"}</td>
+                        <td>{"Shift left ACC (count 0-16)"}</td>
+                        <td>{"ACC ← ACC << count; C ← ACC bit 15"}</td>
                     </tr>
                     <tr>
                         <td><code>{"SRA count"}</code></td>
-                        <td>{"Shift right ACC (arithmetic)"}</td>
-                        <td>{"ACC ← ACC >> count"}</td>
+                        <td>{"Shift right ACC (arithmetic, sign bit copied in; count 0-16)"}</td>
+                        <td>{"ACC ← ACC >> count (signed)"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"SRL count"}</code></td>
+                        <td>{"Shift right ACC (logical, zeros shifted in; count 0-16)"}</td>
+                        <td>{"ACC ← ACC >> count (unsigned)"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"SLT count"}</code></td>
+                        <td>{"Shift left ACC:EXT as 32 bits (count 0-31)"}</td>
+                        <td>{"ACC:EXT ← ACC:EXT << count"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"SRT count"}</code></td>
+                        <td>{"Shift right ACC:EXT as 32 bits (arithmetic; count 0-31)"}</td>
+                        <td>{"ACC:EXT ← ACC:EXT >> count (signed)"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"XCH"}</code></td>
+                        <td>{"Exchange ACC and EXT (flags unchanged)"}</td>
+                        <td>{"ACC ↔ EXT"}</td>
                     </tr>
                     <tr>
                         <td><code>{"BSC cond addr"}</code></td>
                         <td>{"Branch on condition"}</td>
                         <td>{"if condition then IAR ← addr"}</td>
                     </tr>
+                    <tr>
+                        <td><code>{"SKP cond"}</code></td>
+                        <td>{"Skip on condition (short-form BSC)"}</td>
+                        <td>{"if condition then IAR ← IAR+2 (skip next word)"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"BRL offset"}</code></td>
+                        <td>{"Branch relative (offset -128 to 127)"}</td>
+                        <td>{"IAR ← IAR + offset"}</td>
+                    </tr>
                     <tr>
                         <td><code>{"BSI addr"}</code></td>
                         <td>{"Branch and store IAR"}</td>
-                        <td>{"memory[addr] ← IAR; IAR ← addr+1"}</td>
+                        <td>{"memory[addr] ← IAR+1; IAR ← addr+1"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"RET addr"}</code></td>
+                        <td>{"Return from a BSI subroutine"}</td>
+                        <td>{"IAR ← memory[addr]"}</td>
                     </tr>
                     <tr>
                         <td><code>{"WAIT"}</code></td>
                         <td>{"Halt execution"}</td>
                         <td>{"Stop CPU"}</td>
                     </tr>
+                    <tr>
+                        <td><code>{"CLRF"}</code></td>
+                        <td>{"Clear carry and overflow"}</td>
+                        <td>{"C ← 0; V ← 0"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"LDSTAT"}</code></td>
+                        <td>{"Load status word (bit 0 carry, bit 1 overflow, bit 2 halted)"}</td>
+                        <td>{"ACC ← status"}</td>
+                    </tr>
+                    <tr>
+                        <td><code>{"STSTAT"}</code></td>
+                        <td>{"Set carry and overflow from ACC; bit 2 halts"}</td>
+                        <td>{"C ← ACC[0]; V ← ACC[1]"}</td>
+                    </tr>
                     <tr>
                         <td><code>{"NOP"}</code></td>
                         <td>{"No operation"}</td>
@@ -1054,22 +1409,32 @@ pub fn app() -> Html {
                 <ul>
                     <li><code>{"0"}</code>{" - Direct: use address as-is"}</li>
                     <li><code>{"1"}</code>{" - Indexed: add XR1 to address"}</li>
+                    <li><code>{"I"}</code>{" - Indirect: address holds a pointer to the operand"}</li>
+                    <li><code>{"1I"}</code>{" - Indexed indirect: address+XR1 holds a pointer to the operand"}</li>
                 </ul>
 
                 <h3>{"Branch Conditions"}</h3>
+                <p>{"BSC (long form) jumps to an address when its condition holds; SKP (short form) tests the same conditions but only skips the next instruction, which is usually a branch."}</p>
                 <ul>
                     <li><code>{"Z"}</code>{" - Zero: ACC == 0"}</li>
                     <li><code>{"NZ"}</code>{" - Non-zero: ACC != 0"}</li>
                     <li><code>{"P"}</code>{" - Positive: ACC > 0"}</li>
                     <li><code>{"N"}</code>{" - Negative: ACC < 0"}</li>
                     <li><code>{"C"}</code>{" - Carry flag set"}</li>
-                    <li><code>{"V"}</code>{" - Overflow flag set"}</li>
+                    <li><code>{"V"}</code>{" - Overflow flag set (testing V clears the flag)"}</li>
+                    <li><code>{"E"}</code>{" - Even: ACC low bit is 0"}</li>
+                    <li><code>{"DZ"}</code>{" - Double zero: ACC:EXT == 0"}</li>
+                    <li><code>{"LT"}</code>{" - Signed less than: N differs from V"}</li>
+                    <li><code>{"GE"}</code>{" - Signed greater or equal: N matches V"}</li>
+                    <li><code>{"XN"}</code>{" - EXT negative: EXT < 0 (ACC is not tested)"}</li>
+                    <li><code>{"XZ"}</code>{" - EXT zero: EXT == 0 (ACC is not tested)"}</li>
                 </ul>
+                <p>{"To compare ACC with a memory word, subtract it and branch on LT or GE: S 0 B then BSC LT SMALLER. Plain N gives the wrong answer when the subtract overflows; LT and GE account for it. Because V is sticky, clear it first (e.g. SKP V) if an earlier operation may have set it."}</p>
 
                 <h3>{"Flags"}</h3>
                 <ul>
                     <li><strong>{"C (Carry)"}</strong>{" - Set on unsigned overflow"}</li>
-                    <li><strong>{"V (Overflow)"}</strong>{" - Set on signed overflow"}</li>
+                    <li><strong>{"V (Overflow)"}</strong>{" - Set on signed overflow and stays set (sticky) until a BSC/SKP V test or a logical operation clears it"}</li>
                     <li><strong>{"P (Positive)"}</strong>{" - Set when ACC > 0"}</li>
                     <li><strong>{"Z (Zero)"}</strong>{" - Set when ACC == 0"}</li>
                 </ul>
@@ -1081,10 +1446,18 @@ pub fn app() -> Html {
                     <li><strong>{"Assemble"}</strong>{" - Convert assembly code to machine code"}</li>
                     <li><strong>{"Step"}</strong>{" - Execute one instruction"}</li>
                     <li><strong>{"Run"}</strong>{" - Execute until WAIT or error"}</li>
-                    <li><strong>{"Reset"}</strong>{" - Clear CPU state (keep program)"}</li>
+                    <li><strong>{"Reset"}</strong>{" - Clear CPU state and restart at the program's entry point (keep program)"}</li>
+                    <li><strong>{"Clear"}</strong>{" - Clear CPU state and all memory, program included"}</li>
                     <li><strong>{"Check"}</strong>{" - Validate challenge solution"}</li>
                 </ul>
 
+                <h3>{"Keyboard Shortcuts"}</h3>
+                <ul>
+                    <li><strong>{"F5"}</strong>{" - Assemble and run"}</li>
+                    <li><strong>{"F10"}</strong>{" - Step"}</li>
+                    <li><strong>{"Ctrl+R"}</strong>{" - Reset"}</li>
+                </ul>
+
                 <h3>{"Tips"}</h3>
                 <ul>
                     <li>{"Comments start with semicolon (;)"}</li>
@@ -1125,3 +1498,8 @@ pub fn app() -> Html {
         </div>
     }
 }
+
+/// Message carried by an error from the WASM CPU bindings
+fn error_text(e: &wasm_bindgen::JsValue) -> String {
+    e.as_string().unwrap_or_else(|| format!("{e:?}"))
+}
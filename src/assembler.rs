@@ -2,7 +2,10 @@
 //!
 //! This module provides assembly parsing, opcode encoding, and decoding functionality.
 
-use crate::cpu::{AddressingMode, BranchCondition, Instruction};
+use crate::cpu::{AddressingMode, BranchCondition, Instruction, MAX_DOUBLE_SHIFT, MAX_SHIFT};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use thiserror::Error;
 
 /// Assembly errors
@@ -37,6 +40,98 @@ pub enum AssemblerError {
 
     #[error("Invalid DATA directive value: {0}")]
     InvalidDataValue(String),
+
+    #[error("Undefined label: {0}")]
+    UndefinedLabel(String),
+
+    #[error("Duplicate symbol: {0}")]
+    DuplicateSymbol(String),
+
+    #[error("Invalid BSS block size: {0}")]
+    InvalidBlockSize(String),
+
+    #[error("Invalid ALIGN boundary: {0}")]
+    InvalidAlignment(String),
+
+    #[error("Instruction placed in reserved address 0x{0:04X}")]
+    ReservedAddress(u16),
+
+    #[error("Address 0x{0:04X} is assembled more than once")]
+    OverlappingPlacement(u16),
+
+    #[error("Undefined macro: {0}")]
+    UndefinedMacro(String),
+
+    #[error("Macro {0} invokes itself")]
+    RecursiveMacro(String),
+
+    #[error("Location counter passes the end of the address space at 0x{0:04X}")]
+    LocationOverflow(u16),
+
+    #[error("Operand out of range: 0x{0:04X} does not fit the 8-bit address field")]
+    OperandOutOfRange(u16),
+}
+
+/// An assembler error together with the source line that caused it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    /// 1-based line number, counting blank and comment lines
+    pub line: usize,
+    /// The offending source line, trimmed
+    pub source: String,
+    pub error: AssemblerError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {} ({})", self.line, self.error, self.source)
+    }
+}
+
+impl std::error::Error for LineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A likely mistake the assembler found that does not stop assembly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssemblerWarning {
+    /// A branch on `line` (1-based) transfers control into DATA, TEXT, or BSS words
+    BranchIntoData { line: usize, target: u16 },
+    /// The instruction on `line` (1-based) can fall through into data at `address`
+    FallsIntoData { line: usize, address: u16 },
+}
+
+impl AssemblerWarning {
+    /// 1-based source line the warning refers to
+    pub fn line(&self) -> usize {
+        match self {
+            AssemblerWarning::BranchIntoData { line, .. }
+            | AssemblerWarning::FallsIntoData { line, .. } => *line,
+        }
+    }
+
+    fn line_mut(&mut self) -> &mut usize {
+        match self {
+            AssemblerWarning::BranchIntoData { line, .. }
+            | AssemblerWarning::FallsIntoData { line, .. } => line,
+        }
+    }
+}
+
+impl fmt::Display for AssemblerWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssemblerWarning::BranchIntoData { line, target } => {
+                write!(f, "Line {line}: branch target 0x{target:04X} is data")
+            }
+            AssemblerWarning::FallsIntoData { line, address } => write!(
+                f,
+                "Line {line}: execution falls through into data at 0x{address:04X} (missing WAIT?)"
+            ),
+        }
+    }
 }
 
 /// Assembled program result
@@ -44,86 +139,653 @@ pub enum AssemblerError {
 pub struct AssembledProgram {
     /// Machine code words
     pub code: Vec<u16>,
-    /// Starting address
+    /// Entry point: the `END` operand if given, otherwise the assembler's origin
     pub start_addr: u16,
     /// Assembly listing (address, opcode, source line)
     pub listing: Vec<AssemblyLine>,
+    /// Every emitted word (instructions and DATA) as (address, word), in source order
+    pub placements: Vec<(u16, u16)>,
+    /// Labels and EQU constants (upper-cased name, value), sorted by name
+    pub symbols: Vec<(String, u16)>,
+    /// Non-fatal problems such as branches into data, sorted by line
+    pub warnings: Vec<AssemblerWarning>,
+}
+
+impl AssembledProgram {
+    /// Every placed word (instructions, DATA, TEXT, and literals) as
+    /// (address, word), in address order
+    ///
+    /// Addresses skipped by `ORG` or reserved by `BSS` are absent. Assembly
+    /// rejects placing two words at one address, so each address appears once.
+    pub fn to_image(&self) -> Vec<(u16, u16)> {
+        let mut image = self.placements.clone();
+        image.sort_by_key(|&(addr, _)| addr);
+        image
+    }
+
+    /// Format the listing and symbol table for the line printer
+    ///
+    /// Every line starts with a carriage-control character (`1` new page,
+    /// `0` double space, blank single space). Listing lines give the location
+    /// and object word in hex, then the source statement as written.
+    pub fn to_printer_listing(&self) -> Vec<String> {
+        let mut lines = vec![format!("1{LISTING_HEADER}")];
+        lines.extend(self.listing.iter().enumerate().map(|(i, line)| {
+            let control = if i == 0 { '0' } else { ' ' };
+            format!(
+                "{control}{:04X}  {:04X}  {}",
+                line.address,
+                line.opcode,
+                line.source.trim_end()
+            )
+        }));
+
+        // Symbol table on its own page
+        if !self.symbols.is_empty() {
+            lines.push("1SYMBOL TABLE".to_string());
+            lines.extend(self.symbols.iter().enumerate().map(|(i, (name, value))| {
+                let control = if i == 0 { '0' } else { ' ' };
+                format!("{control}{name:<8} {value:04} (0x{value:04X})")
+            }));
+        }
+        lines
+    }
 }
 
+/// Column headings of the printer listing, aligned with its fields
+const LISTING_HEADER: &str = "LOC   OBJ   SOURCE STATEMENT";
+
 /// Single line of assembly listing
 #[derive(Debug, Clone)]
 pub struct AssemblyLine {
     pub address: u16,
     pub opcode: u16,
+    /// The source line as written, with its label, indentation, and comment
     pub source: String,
 }
 
+/// One decoded memory word in a disassembly listing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DisassembledWord {
+    pub address: u16,
+    pub word: u16,
+    pub text: String,
+}
+
+/// EBCDIC blank, used to pad an odd-length TEXT string
+const EBCDIC_BLANK: u8 = 0x40;
+
+/// Highest value the location counter may reach
+///
+/// Programs are assembled for the whole 16-bit address space; whether they
+/// fit the machine's configured memory is checked when they are loaded.
+const LOCATION_LIMIT: usize = u16::MAX as usize;
+
+/// Instruction mnemonics recognized by the assembler
+const MNEMONICS: &[&str] = &[
+    "LD", "STO", "LDS", "LDD", "STD", "LDX", "STX", "A", "AC", "S", "SC", "M", "D", "AND", "OR",
+    "SLA", "SRA", "SRL", "SLT", "SRT", "XCH", "BSC", "BSI", "RET", "SKP", "BRL", "WAIT", "CLRF",
+    "LDSTAT", "STSTAT", "NOP", "LDI",
+];
+
+/// Assembler directives (pseudo-operations)
+const DIRECTIVES: &[&str] = &[
+    "ORG", "DATA", "EQU", "TEXT", "BSS", "ALIGN", "IF", "ELSE", "ENDIF", "END", "MACRO", "ENDM",
+];
+
+/// Check whether a token names an instruction or directive
+fn is_keyword(token: &str) -> bool {
+    let upper = token.to_uppercase();
+    MNEMONICS.contains(&upper.as_str()) || DIRECTIVES.contains(&upper.as_str())
+}
+
+/// Check whether a token is a valid symbol name (letter or underscore, then alphanumerics)
+fn is_valid_symbol(token: &str) -> bool {
+    let mut chars = token.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parse a numeric literal: decimal, or hex, binary, or octal with a `0x`,
+/// `0b`, or `0o` prefix (either case)
+fn parse_number(s: &str) -> Result<u16, AssemblerError> {
+    let (digits, radix) = match s.get(..2) {
+        Some("0x" | "0X") => (&s[2..], 16),
+        Some("0b" | "0B") => (&s[2..], 2),
+        Some("0o" | "0O") => (&s[2..], 8),
+        _ => (s, 10),
+    };
+    // from_str_radix would also take a sign
+    if digits.starts_with(['+', '-']) {
+        return Err(AssemblerError::InvalidOperand(s.to_string()));
+    }
+    u16::from_str_radix(digits, radix).map_err(|_| AssemblerError::InvalidOperand(s.to_string()))
+}
+
+/// Find the `;` that starts a comment, ignoring any inside a string literal
+fn find_comment(line: &str) -> Option<usize> {
+    let mut in_string = false;
+    for (pos, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ';' if !in_string => return Some(pos),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A source line split into its optional label and the code that follows it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Statement<'a> {
+    label: Option<&'a str>,
+    code: &'a str,
+}
+
+impl<'a> Statement<'a> {
+    /// Split a raw source line into label and code, dropping any comment
+    ///
+    /// A label is either a first token ending in `:` (`LOOP: A 0 31`), a
+    /// token starting in column 1 that is not a mnemonic and is followed by a
+    /// mnemonic or nothing (`LOOP A 0 31`), or the name before an `EQU`.
+    fn parse(line: &'a str) -> Self {
+        let line = match find_comment(line) {
+            Some(pos) => &line[..pos],
+            None => line,
+        };
+        let in_label_column = !line.starts_with(char::is_whitespace);
+        let trimmed = line.trim();
+
+        let (first, rest) = match trimmed.split_once(char::is_whitespace) {
+            Some((first, rest)) => (first, rest.trim_start()),
+            None => (trimmed, ""),
+        };
+
+        if let Some(label) = first.strip_suffix(':') {
+            return Self {
+                label: Some(label),
+                code: rest,
+            };
+        }
+
+        if !first.is_empty() && !is_keyword(first) {
+            let next = rest.split_whitespace().next();
+            let is_equ = next.is_some_and(|t| t.eq_ignore_ascii_case("EQU"));
+            if is_equ || (in_label_column && next.is_none_or(is_keyword)) {
+                return Self {
+                    label: Some(first),
+                    code: rest,
+                };
+            }
+        }
+
+        Self {
+            label: None,
+            code: trimmed,
+        }
+    }
+
+    /// Upper-cased first token of the code portion (mnemonic or directive)
+    fn keyword(&self) -> String {
+        self.code
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_uppercase()
+    }
+
+    /// Value text of a literal the statement needs in the pool: the operand
+    /// of `LDI value`, or an instruction operand written `=value`
+    fn literal(&self) -> Option<&'a str> {
+        let mut tokens = self.code.split_whitespace();
+        match tokens.next()?.to_uppercase().as_str() {
+            "LDI" => tokens.next(),
+            mnemonic if MNEMONICS.contains(&mnemonic) => {
+                tokens.find_map(|token| token.strip_prefix('='))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An IF block that has not yet been closed by ENDIF
+#[derive(Debug, Clone, Copy)]
+struct Conditional {
+    /// 0-based index of the IF statement, for reporting a missing ENDIF
+    line: usize,
+    /// Whether the code around the block is being assembled
+    outer: bool,
+    /// Whether the IF symbol was non-zero
+    condition: bool,
+    /// Whether ELSE has been seen
+    in_else: bool,
+}
+
+impl Conditional {
+    /// Whether statements at this point in the block are assembled
+    fn active(&self) -> bool {
+        self.outer && self.condition != self.in_else
+    }
+}
+
 /// IBM 1130 Assembler
 pub struct Assembler {
+    /// Address assembly starts at when the source has no leading ORG
+    origin: u16,
     current_addr: u16,
+    /// Symbol table (label or EQU name -> value), keyed by upper-cased name
+    symbols: HashMap<String, u16>,
+    /// Literal pool addresses, keyed by the literal's value text
+    literals: HashMap<String, u16>,
 }
 
 impl Assembler {
     /// Create a new assembler starting at program start address
     pub fn new() -> Self {
+        Self::with_origin(crate::cpu::PROGRAM_START)
+    }
+
+    /// Create an assembler whose default origin is `addr` (in-source ORG still applies)
+    pub fn with_origin(addr: u16) -> Self {
         Self {
-            current_addr: crate::cpu::PROGRAM_START,
+            origin: addr,
+            current_addr: addr,
+            symbols: HashMap::new(),
+            literals: HashMap::new(),
         }
     }
 
     /// Assemble a complete program from source text
-    pub fn assemble(&mut self, source: &str) -> Result<AssembledProgram, AssemblerError> {
-        let mut code = Vec::new();
-        let mut listing = Vec::new();
-        let start_addr = self.current_addr;
+    ///
+    /// Assembly runs in two passes: the first assigns addresses to labels,
+    /// the second encodes instructions with label operands resolved. Literal
+    /// values (from `LDI` and `=value` operands) are pooled after the highest
+    /// address the program occupies, one word per distinct value; the pool
+    /// must end inside the 8-bit address field. Macros
+    /// (`MACRO name` ... `ENDM`) are expanded before the first pass. Errors
+    /// report the 1-based source line they occurred on.
+    pub fn assemble(&mut self, source: &str) -> Result<AssembledProgram, LineError> {
+        let lines: Vec<&str> = source.lines().collect();
+        let (program, errors) = self.run_source(&lines);
+        match errors.into_iter().next() {
+            Some((index, error)) => Err(LineError {
+                line: index + 1,
+                source: lines[index].trim().to_string(),
+                error,
+            }),
+            None => Ok(program),
+        }
+    }
 
-        for line in source.lines() {
-            // Remove comments
-            let line = if let Some(pos) = line.find(';') {
-                &line[..pos]
-            } else {
-                line
-            };
+    /// Check source text without stopping at the first problem
+    ///
+    /// Returns every error as (1-based line, error), sorted by line, with at
+    /// most one error per line. An empty result means `assemble` succeeds.
+    pub fn validate(&mut self, source: &str) -> Vec<(usize, AssemblerError)> {
+        let lines: Vec<&str> = source.lines().collect();
+        let (_, errors) = self.run_source(&lines);
 
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
+        let mut by_line: Vec<(usize, AssemblerError)> = Vec::new();
+        for (index, error) in errors {
+            if !by_line.iter().any(|&(line, _)| line == index + 1) {
+                by_line.push((index + 1, error));
+            }
+        }
+        by_line.sort_by_key(|&(line, _)| line);
+        by_line
+    }
+
+    /// Expand macros in `lines`, then run both passes over the result
+    ///
+    /// Errors and warnings refer to the original lines; anything inside an
+    /// expanded body is reported on the line that invoked the macro.
+    fn run_source(&mut self, lines: &[&str]) -> (AssembledProgram, Vec<(usize, AssemblerError)>) {
+        let (expanded, origins, mut errors) = expand_macros(lines);
+        let expanded: Vec<&str> = expanded.iter().map(String::as_str).collect();
+        let (mut program, pass_errors) = self.run_passes(&expanded);
+
+        errors.extend(
+            pass_errors
+                .into_iter()
+                .map(|(index, error)| (origins[index], error)),
+        );
+        for warning in &mut program.warnings {
+            let line = warning.line_mut();
+            *line = origins[*line - 1] + 1;
+        }
+        (program, errors)
+    }
+
+    /// Run both passes over `lines`, carrying on past errors
+    ///
+    /// Errors are returned as (0-based line index, error) in the order they
+    /// were found, so the first one is what a stop-at-first assembler reports.
+    fn run_passes(&mut self, lines: &[&str]) -> (AssembledProgram, Vec<(usize, AssemblerError)>) {
+        let statements: Vec<Statement> = lines.iter().map(|line| Statement::parse(line)).collect();
+        let mut errors = Vec::new();
+
+        // Pass 1: record label addresses and EQU constants, and decide which
+        // statements conditional assembly keeps
+        let start_addr = self.origin;
+        self.current_addr = start_addr;
+        self.symbols.clear();
+        self.literals.clear();
+        let mut open_ifs = Vec::new();
+        let mut included = Vec::with_capacity(statements.len());
+        let mut literal_uses = Vec::new();
+        let mut end_addr = start_addr;
+        for (index, stmt) in statements.iter().enumerate() {
+            let keep = self
+                .track_conditional(stmt, index, &mut open_ifs)
+                .unwrap_or_else(|error| {
+                    errors.push((index, error));
+                    false
+                });
+            if keep {
+                if let Err(error) = self.define_labels(stmt) {
+                    errors.push((index, error));
+                }
+                end_addr = end_addr.max(self.current_addr);
+                if let Some(text) = stmt.literal() {
+                    literal_uses.push((text, index));
+                }
             }
+            included.push(keep);
+        }
+        if let Some(open) = open_ifs.first() {
+            errors.push((
+                open.line,
+                AssemblerError::SyntaxError("IF without matching ENDIF".to_string()),
+            ));
+        }
 
-            // Check if this is an ORG directive
-            if line.to_uppercase().starts_with("ORG") {
-                let new_addr = self.parse_org_directive(line)?;
-                self.current_addr = new_addr;
-                continue;
+        let mut symbols: Vec<(String, u16)> = self
+            .symbols
+            .iter()
+            .map(|(name, &value)| (name.clone(), value))
+            .collect();
+        symbols.sort();
+
+        // Assign each literal a pool word, now that every symbol is known
+        let mut pool: Vec<(u16, &str, usize)> = Vec::new();
+        for (text, index) in literal_uses {
+            let value = match self.parse_word_value(text) {
+                Ok(value) => value,
+                Err(error) => {
+                    errors.push((index, error));
+                    continue;
+                }
+            };
+            let slot = match pool.iter().position(|&(pooled, _, _)| pooled == value) {
+                Some(slot) => slot,
+                None => {
+                    pool.push((value, text, index));
+                    pool.len() - 1
+                }
+            };
+            // Operands can only reach pool words inside the address field
+            let address = u16::try_from(slot)
+                .ok()
+                .and_then(|slot| end_addr.checked_add(slot))
+                .ok_or(AssemblerError::LocationOverflow(end_addr))
+                .and_then(address_field);
+            match address {
+                Ok(address) => {
+                    self.literals.insert(text.to_string(), address);
+                }
+                Err(error) => errors.push((index, error)),
             }
+        }
 
-            // Check if this is a DATA directive
-            if line.to_uppercase().starts_with("DATA") {
-                let (_addr, _value) = self.parse_data_directive(line)?;
-                // DATA directives set values at specific addresses, not sequential
-                // For now, just skip them in the listing
+        // Pass 2: encode instructions
+        let mut program = AssembledProgram {
+            code: Vec::new(),
+            start_addr,
+            listing: Vec::new(),
+            placements: Vec::new(),
+            symbols,
+            warnings: Vec::new(),
+        };
+        // Instruction words as (address, opcode, line index), and every
+        // address holding DATA, TEXT, BSS, or pooled literal words
+        let mut code = Vec::new();
+        let mut data = HashSet::new();
+        let mut occupied = HashSet::new();
+        self.current_addr = start_addr;
+        for (index, stmt) in statements.iter().enumerate() {
+            if !included[index] {
                 continue;
             }
+            let source = lines[index].trim_end();
+            let placed = program.placements.len();
+            let reserved_from = self.current_addr;
+            if let Err(error) = self.emit_statement(stmt, source, &mut program) {
+                errors.push((index, error));
+            }
+            let words = &program.placements[placed..];
+            for &(addr, _) in words {
+                if !occupied.insert(addr) {
+                    errors.push((index, AssemblerError::OverlappingPlacement(addr)));
+                }
+            }
+            match stmt.keyword().as_str() {
+                "BSS" => data.extend(reserved_from..self.current_addr),
+                "DATA" | "TEXT" => data.extend(words.iter().map(|&(addr, _)| addr)),
+                keyword if MNEMONICS.contains(&keyword) => {
+                    code.extend(words.iter().map(|&(addr, word)| (addr, word, index)));
+                }
+                _ => {}
+            }
+        }
+        self.current_addr = end_addr;
+        for (value, text, index) in pool {
+            if !occupied.insert(self.current_addr) {
+                errors.push((
+                    index,
+                    AssemblerError::OverlappingPlacement(self.current_addr),
+                ));
+            }
+            data.insert(self.current_addr);
+            if let Err(error) = self.emit_word(value, &format!("={text}"), &mut program) {
+                errors.push((index, error));
+                break;
+            }
+        }
+        program.warnings = flow_warnings(&code, &data);
+
+        (program, errors)
+    }
+
+    /// Apply an IF, ELSE, or ENDIF directive to the open blocks and report
+    /// whether the statement should be assembled
+    ///
+    /// Conditional directives themselves are never assembled. Inside a
+    /// skipped block only nesting is tracked; IF conditions there are not
+    /// evaluated.
+    fn track_conditional(
+        &self,
+        stmt: &Statement,
+        index: usize,
+        open_ifs: &mut Vec<Conditional>,
+    ) -> Result<bool, AssemblerError> {
+        let active = open_ifs.last().is_none_or(Conditional::active);
+        match stmt.keyword().as_str() {
+            "IF" => {
+                let condition = active && self.parse_if_directive(stmt.code)? != 0;
+                open_ifs.push(Conditional {
+                    line: index,
+                    outer: active,
+                    condition,
+                    in_else: false,
+                });
+            }
+            "ELSE" => {
+                let open = open_ifs
+                    .last_mut()
+                    .filter(|open| !open.in_else)
+                    .ok_or_else(|| {
+                        AssemblerError::SyntaxError("ELSE without matching IF".to_string())
+                    })?;
+                open.in_else = true;
+            }
+            "ENDIF" => {
+                open_ifs.pop().ok_or_else(|| {
+                    AssemblerError::SyntaxError("ENDIF without matching IF".to_string())
+                })?;
+            }
+            _ => return Ok(active),
+        }
+        Ok(false)
+    }
 
-            // Parse instruction
-            let instr = self.parse_line(line)?;
-            let opcode = encode_instruction(&instr)?;
+    /// First pass over one statement: advance the location counter and
+    /// record any label or EQU constant it defines
+    fn define_labels(&mut self, stmt: &Statement) -> Result<(), AssemblerError> {
+        let label_addr = match stmt.keyword().as_str() {
+            "EQU" => {
+                let name = stmt.label.ok_or_else(|| {
+                    AssemblerError::SyntaxError("EQU directive requires a name".to_string())
+                })?;
+                let value = self.parse_equ_directive(stmt.code)?;
+                return self.define_symbol(name, value);
+            }
+            "ORG" => {
+                self.current_addr = self.parse_org_directive(stmt.code)?;
+                self.current_addr
+            }
+            "DATA" => self.parse_data_directive(stmt.code)?[0].0,
+            "TEXT" => {
+                let addr = self.current_addr;
+                let words = self.parse_text_directive(stmt.code)?.len();
+                self.advance(words)?;
+                addr
+            }
+            "BSS" => {
+                let addr = self.current_addr;
+                let words = self.parse_bss_directive(stmt.code)?;
+                self.advance(words as usize)?;
+                addr
+            }
+            "ALIGN" => {
+                self.current_addr = self.parse_align_directive(stmt.code)?;
+                self.current_addr
+            }
+            "" | "END" => self.current_addr,
+            _ => {
+                let addr = self.current_addr;
+                self.advance(1)?;
+                addr
+            }
+        };
 
-            listing.push(AssemblyLine {
-                address: self.current_addr,
-                opcode,
-                source: line.to_string(),
-            });
+        match stmt.label {
+            Some(label) => self.define_symbol(label, label_addr),
+            None => Ok(()),
+        }
+    }
 
-            code.push(opcode);
-            self.current_addr += 1;
+    /// Second pass over one statement: encode it and append its words to
+    /// the program
+    ///
+    /// `source` is the full line, which is kept in the listing.
+    fn emit_statement(
+        &mut self,
+        stmt: &Statement,
+        source: &str,
+        program: &mut AssembledProgram,
+    ) -> Result<(), AssemblerError> {
+        match stmt.keyword().as_str() {
+            "" | "EQU" => {}
+            "BSS" => {
+                // Reserved words are left uninitialized
+                let words = self.parse_bss_directive(stmt.code)?;
+                self.advance(words as usize)?;
+            }
+            "ORG" => {
+                self.current_addr = self.parse_org_directive(stmt.code)?;
+            }
+            "ALIGN" => {
+                // Skipped words are left uninitialized, as for BSS
+                self.current_addr = self.parse_align_directive(stmt.code)?;
+            }
+            "END" => {
+                if let Some(entry) = self.parse_end_directive(stmt.code)? {
+                    program.start_addr = entry;
+                }
+            }
+            "DATA" => {
+                // DATA directives set values at specific addresses, not sequential,
+                // so they are placed without advancing the location counter
+                program
+                    .placements
+                    .extend(self.parse_data_directive(stmt.code)?);
+            }
+            "TEXT" => {
+                for (i, word) in self
+                    .parse_text_directive(stmt.code)?
+                    .into_iter()
+                    .enumerate()
+                {
+                    let source = if i == 0 { source } else { "" };
+                    self.emit_word(word, source, program)?;
+                }
+            }
+            _ => {
+                // Addresses 0-3 hold the trap and index registers; DATA may set
+                // them, but code placed there would be clobbered
+                if self.current_addr <= crate::cpu::state::XR3_ADDR {
+                    return Err(AssemblerError::ReservedAddress(self.current_addr));
+                }
+                let instr = self.parse_line(stmt.code)?;
+                let opcode = encode_instruction(&instr)?;
+                self.emit_word(opcode, source, program)?;
+            }
         }
+        Ok(())
+    }
 
-        Ok(AssembledProgram {
-            code,
-            start_addr,
-            listing,
-        })
+    /// Place a word at the current address, list it, and advance
+    fn emit_word(
+        &mut self,
+        word: u16,
+        source: &str,
+        program: &mut AssembledProgram,
+    ) -> Result<(), AssemblerError> {
+        let addr = self.current_addr;
+        self.advance(1)?;
+        program.listing.push(AssemblyLine {
+            address: addr,
+            opcode: word,
+            source: source.to_string(),
+        });
+        program.code.push(word);
+        program.placements.push((addr, word));
+        Ok(())
+    }
+
+    /// Move the location counter past `words` words, failing rather than
+    /// wrapping at the top of the address space
+    fn advance(&mut self, words: usize) -> Result<(), AssemblerError> {
+        self.current_addr = u16::try_from(words)
+            .ok()
+            .and_then(|words| self.current_addr.checked_add(words))
+            .ok_or(AssemblerError::LocationOverflow(self.current_addr))?;
+        Ok(())
+    }
+
+    /// Record a label or constant definition in the symbol table
+    fn define_symbol(&mut self, name: &str, value: u16) -> Result<(), AssemblerError> {
+        if !is_valid_symbol(name) {
+            return Err(AssemblerError::SyntaxError(format!(
+                "Invalid symbol name: {name}"
+            )));
+        }
+        let key = name.to_uppercase();
+        if self.symbols.contains_key(&key) {
+            return Err(AssemblerError::DuplicateSymbol(name.to_string()));
+        }
+        self.symbols.insert(key, value);
+        Ok(())
     }
 
     /// Parse a single line of assembly into an Instruction
@@ -145,6 +807,30 @@ impl Assembler {
                 let addr = self.parse_address(parts[2])?;
                 Ok(Instruction::LD { addr, mode })
             }
+            "LDS" => {
+                if parts.len() < 3 {
+                    return Err(AssemblerError::MissingOperand("LDS".to_string()));
+                }
+                let mode = self.parse_mode(parts[1])?;
+                let addr = self.parse_address(parts[2])?;
+                Ok(Instruction::LDS { addr, mode })
+            }
+            "LDD" => {
+                if parts.len() < 3 {
+                    return Err(AssemblerError::MissingOperand("LDD".to_string()));
+                }
+                let mode = self.parse_mode(parts[1])?;
+                let addr = self.parse_address(parts[2])?;
+                Ok(Instruction::LDD { addr, mode })
+            }
+            "STD" => {
+                if parts.len() < 3 {
+                    return Err(AssemblerError::MissingOperand("STD".to_string()));
+                }
+                let mode = self.parse_mode(parts[1])?;
+                let addr = self.parse_address(parts[2])?;
+                Ok(Instruction::STD { addr, mode })
+            }
             "STO" => {
                 if parts.len() < 3 {
                     return Err(AssemblerError::MissingOperand("STO".to_string()));
@@ -161,6 +847,14 @@ impl Assembler {
                 let addr = self.parse_address(parts[2])?;
                 Ok(Instruction::A { addr, mode })
             }
+            "AC" => {
+                if parts.len() < 3 {
+                    return Err(AssemblerError::MissingOperand("AC".to_string()));
+                }
+                let mode = self.parse_mode(parts[1])?;
+                let addr = self.parse_address(parts[2])?;
+                Ok(Instruction::AC { addr, mode })
+            }
             "S" => {
                 if parts.len() < 3 {
                     return Err(AssemblerError::MissingOperand("S".to_string()));
@@ -169,6 +863,30 @@ impl Assembler {
                 let addr = self.parse_address(parts[2])?;
                 Ok(Instruction::S { addr, mode })
             }
+            "SC" => {
+                if parts.len() < 3 {
+                    return Err(AssemblerError::MissingOperand("SC".to_string()));
+                }
+                let mode = self.parse_mode(parts[1])?;
+                let addr = self.parse_address(parts[2])?;
+                Ok(Instruction::SC { addr, mode })
+            }
+            "M" => {
+                if parts.len() < 3 {
+                    return Err(AssemblerError::MissingOperand("M".to_string()));
+                }
+                let mode = self.parse_mode(parts[1])?;
+                let addr = self.parse_address(parts[2])?;
+                Ok(Instruction::M { addr, mode })
+            }
+            "D" => {
+                if parts.len() < 3 {
+                    return Err(AssemblerError::MissingOperand("D".to_string()));
+                }
+                let mode = self.parse_mode(parts[1])?;
+                let addr = self.parse_address(parts[2])?;
+                Ok(Instruction::D { addr, mode })
+            }
             "AND" => {
                 if parts.len() < 3 {
                     return Err(AssemblerError::MissingOperand("AND".to_string()));
@@ -186,6 +904,18 @@ impl Assembler {
                 Ok(Instruction::OR { addr, mode })
             }
 
+            // Load a constant: LD from the literal's pool word
+            "LDI" => {
+                if parts.len() < 2 {
+                    return Err(AssemblerError::MissingOperand("LDI".to_string()));
+                }
+                let addr = self.literal_address(parts[1])?;
+                Ok(Instruction::LD {
+                    addr,
+                    mode: AddressingMode::Direct,
+                })
+            }
+
             // Index register operations (direct addressing only)
             "LDX" => {
                 if parts.len() < 2 {
@@ -207,16 +937,37 @@ impl Assembler {
                 if parts.len() < 2 {
                     return Err(AssemblerError::MissingOperand("SLA".to_string()));
                 }
-                let count = self.parse_shift_count(parts[1])?;
+                let count = self.parse_shift_count(parts[1], MAX_SHIFT)?;
                 Ok(Instruction::SLA { count })
             }
             "SRA" => {
                 if parts.len() < 2 {
                     return Err(AssemblerError::MissingOperand("SRA".to_string()));
                 }
-                let count = self.parse_shift_count(parts[1])?;
+                let count = self.parse_shift_count(parts[1], MAX_SHIFT)?;
                 Ok(Instruction::SRA { count })
             }
+            "SRL" => {
+                if parts.len() < 2 {
+                    return Err(AssemblerError::MissingOperand("SRL".to_string()));
+                }
+                let count = self.parse_shift_count(parts[1], MAX_SHIFT)?;
+                Ok(Instruction::SRL { count })
+            }
+            "SLT" => {
+                if parts.len() < 2 {
+                    return Err(AssemblerError::MissingOperand("SLT".to_string()));
+                }
+                let count = self.parse_shift_count(parts[1], MAX_DOUBLE_SHIFT)?;
+                Ok(Instruction::SLT { count })
+            }
+            "SRT" => {
+                if parts.len() < 2 {
+                    return Err(AssemblerError::MissingOperand("SRT".to_string()));
+                }
+                let count = self.parse_shift_count(parts[1], MAX_DOUBLE_SHIFT)?;
+                Ok(Instruction::SRT { count })
+            }
 
             // Branch operations
             "BSC" => {
@@ -235,38 +986,91 @@ impl Assembler {
                 let addr = self.parse_address(parts[1])?;
                 Ok(Instruction::BSI { addr })
             }
+            "RET" => {
+                if parts.len() < 2 {
+                    return Err(AssemblerError::MissingOperand("RET".to_string()));
+                }
+                let addr = self.parse_address(parts[1])?;
+                Ok(Instruction::RET { addr })
+            }
+            "SKP" => {
+                if parts.len() < 2 {
+                    return Err(AssemblerError::MissingOperand("SKP".to_string()));
+                }
+                let condition = BranchCondition::parse(parts[1])
+                    .ok_or_else(|| AssemblerError::InvalidCondition(parts[1].to_string()))?;
+                Ok(Instruction::SKP { condition })
+            }
+            "BRL" => {
+                if parts.len() < 2 {
+                    return Err(AssemblerError::MissingOperand("BRL".to_string()));
+                }
+                let offset = parts[1]
+                    .parse::<i8>()
+                    .map_err(|_| AssemblerError::InvalidOperand(parts[1].to_string()))?;
+                Ok(Instruction::BRL { offset })
+            }
+
+            "XCH" => Ok(Instruction::XCH),
 
             // Control
             "WAIT" => Ok(Instruction::WAIT),
+            "CLRF" => Ok(Instruction::CLRF),
+            "LDSTAT" => Ok(Instruction::LDSTAT),
+            "STSTAT" => Ok(Instruction::STSTAT),
             "NOP" => Ok(Instruction::NOP),
 
+            // A lone unknown name reads as a call to a macro that was never defined
+            _ if parts.len() == 1 && is_valid_symbol(parts[0]) => {
+                Err(AssemblerError::UndefinedMacro(parts[0].to_string()))
+            }
             _ => Err(AssemblerError::InvalidMnemonic(mnemonic)),
         }
     }
 
-    /// Parse addressing mode (0 = direct, 1 = indexed)
+    /// Parse addressing mode (0 = direct, 1 = indexed, I = indirect, 1I = indexed indirect)
     fn parse_mode(&self, s: &str) -> Result<AddressingMode, AssemblerError> {
-        match s {
+        match s.to_uppercase().as_str() {
             "0" => Ok(AddressingMode::Direct),
             "1" => Ok(AddressingMode::Indexed),
+            "I" | "0I" => Ok(AddressingMode::DirectIndirect),
+            "1I" => Ok(AddressingMode::IndexedIndirect),
             _ => Err(AssemblerError::InvalidMode(s.to_string())),
         }
     }
 
-    /// Parse address (supports decimal and hex with 0x prefix)
+    /// Pool address of a literal collected in the first pass
+    fn literal_address(&self, text: &str) -> Result<u16, AssemblerError> {
+        self.literals.get(text).copied().ok_or_else(|| {
+            AssemblerError::SyntaxError(format!("Literal ={text} is not in the pool"))
+        })
+    }
+
+    /// Parse address (a number as `parse_number` reads it, a label, or an
+    /// `=value` literal)
     fn parse_address(&self, s: &str) -> Result<u16, AssemblerError> {
-        if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
-            u16::from_str_radix(hex, 16).map_err(|_| AssemblerError::InvalidAddress(s.to_string()))
+        if let Some(text) = s.strip_prefix('=') {
+            self.literal_address(text)
+        } else if is_valid_symbol(s) {
+            self.symbols
+                .get(&s.to_uppercase())
+                .copied()
+                .ok_or_else(|| AssemblerError::UndefinedLabel(s.to_string()))
         } else {
-            s.parse::<u16>()
-                .map_err(|_| AssemblerError::InvalidAddress(s.to_string()))
+            parse_number(s)
         }
     }
 
-    /// Parse shift count
-    fn parse_shift_count(&self, s: &str) -> Result<u8, AssemblerError> {
-        s.parse::<u8>()
-            .map_err(|_| AssemblerError::InvalidShiftCount(s.to_string()))
+    /// Parse shift count (a number as `parse_number` reads it, or an EQU constant)
+    fn parse_shift_count(&self, s: &str, max: u8) -> Result<u8, AssemblerError> {
+        let count = if is_valid_symbol(s) {
+            u8::try_from(self.parse_address(s)?).ok()
+        } else {
+            parse_number(s).ok().and_then(|n| u8::try_from(n).ok())
+        };
+        count
+            .filter(|&count| count <= max)
+            .ok_or_else(|| AssemblerError::InvalidShiftCount(s.to_string()))
     }
 
     /// Parse ORG directive (e.g., "ORG 16" or "ORG 0x10")
@@ -282,197 +1086,749 @@ impl Assembler {
             .map_err(|_| AssemblerError::InvalidDataAddress(parts[1].to_string()))
     }
 
-    /// Parse DATA directive (e.g., "DATA 10 5")
-    fn parse_data_directive(&self, line: &str) -> Result<(u16, u16), AssemblerError> {
+    /// Parse EQU directive value (e.g., "EQU 10" or "EQU 0x1F")
+    fn parse_equ_directive(&self, line: &str) -> Result<u16, AssemblerError> {
         let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 3 {
+        if parts.len() < 2 {
             return Err(AssemblerError::SyntaxError(
-                "DATA directive requires address and value".to_string(),
+                "EQU directive requires a value".to_string(),
             ));
         }
 
-        let addr = self
-            .parse_address(parts[1])
-            .map_err(|_| AssemblerError::InvalidDataAddress(parts[1].to_string()))?;
-        let value = self
-            .parse_address(parts[2])
-            .map_err(|_| AssemblerError::InvalidDataValue(parts[2].to_string()))?;
+        self.parse_address(parts[1])
+    }
 
-        Ok((addr, value))
+    /// Parse END directive entry point (e.g., "END MAIN"); the operand is optional
+    fn parse_end_directive(&self, line: &str) -> Result<Option<u16>, AssemblerError> {
+        line.split_whitespace()
+            .nth(1)
+            .map(|entry| self.parse_address(entry))
+            .transpose()
     }
-}
 
-impl Default for Assembler {
-    fn default() -> Self {
-        Self::new()
+    /// Parse IF directive condition (e.g., "IF DEBUG"), an EQU symbol or number
+    fn parse_if_directive(&self, line: &str) -> Result<u16, AssemblerError> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 2 {
+            return Err(AssemblerError::SyntaxError(
+                "IF directive requires a symbol".to_string(),
+            ));
+        }
+
+        self.parse_address(parts[1])
     }
-}
 
-/// Encode an instruction into a 16-bit opcode
-///
-/// Simplified encoding scheme for educational purposes:
-/// - Bits 15-12: Opcode
-/// - Bits 11-8: Subopcode/modifier
-/// - Bits 7-0: Address/operand
-pub fn encode_instruction(instr: &Instruction) -> Result<u16, AssemblerError> {
+    /// Parse BSS directive word count (e.g., "BSS 10")
+    ///
+    /// The count must be positive and the block must fit in the address space
+    /// starting at the current address.
+    fn parse_bss_directive(&self, line: &str) -> Result<u16, AssemblerError> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 2 {
+            return Err(AssemblerError::SyntaxError(
+                "BSS directive requires a word count".to_string(),
+            ));
+        }
+
+        let count = self.parse_address(parts[1]).map_err(|_| {
+            AssemblerError::InvalidBlockSize(format!("{} is not a positive word count", parts[1]))
+        })?;
+        if count == 0 {
+            return Err(AssemblerError::InvalidBlockSize(
+                "word count must be positive".to_string(),
+            ));
+        }
+        if self.current_addr as usize + count as usize > LOCATION_LIMIT {
+            return Err(AssemblerError::InvalidBlockSize(format!(
+                "{} words at 0x{:04X} extends past the end of the address space",
+                count, self.current_addr
+            )));
+        }
+
+        Ok(count)
+    }
+
+    /// Parse ALIGN directive boundary (e.g., "ALIGN 8"), returning the
+    /// aligned address
+    ///
+    /// The boundary must be a power of two; the counter is left alone if it
+    /// is already a multiple of it.
+    fn parse_align_directive(&self, line: &str) -> Result<u16, AssemblerError> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 2 {
+            return Err(AssemblerError::SyntaxError(
+                "ALIGN directive requires a boundary".to_string(),
+            ));
+        }
+
+        let boundary = self.parse_address(parts[1]).map_err(|_| {
+            AssemblerError::InvalidAlignment(format!("{} is not a power of two", parts[1]))
+        })?;
+        if !boundary.is_power_of_two() {
+            return Err(AssemblerError::InvalidAlignment(format!(
+                "{boundary} is not a power of two"
+            )));
+        }
+        let aligned = (self.current_addr as usize).next_multiple_of(boundary as usize);
+        if aligned > LOCATION_LIMIT {
+            return Err(AssemblerError::InvalidAlignment(format!(
+                "aligning 0x{:04X} to {} passes the end of the address space",
+                self.current_addr, boundary
+            )));
+        }
+
+        Ok(aligned as u16)
+    }
+
+    /// Parse DATA directive (e.g., "DATA 10 5" or "DATA 10 1 -2 0x3")
+    ///
+    /// Values after the first go at consecutive addresses. Returns the
+    /// (address, value) placements in order.
+    fn parse_data_directive(&self, line: &str) -> Result<Vec<(u16, u16)>, AssemblerError> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 3 {
+            return Err(AssemblerError::SyntaxError(
+                "DATA directive requires address and value".to_string(),
+            ));
+        }
+
+        let addr = self
+            .parse_address(parts[1])
+            .map_err(|_| AssemblerError::InvalidDataAddress(parts[1].to_string()))?;
+        (0u16..)
+            .zip(&parts[2..])
+            .map(|(offset, value)| {
+                let at = addr
+                    .checked_add(offset)
+                    .ok_or_else(|| AssemblerError::InvalidDataAddress(parts[1].to_string()))?;
+                Ok((at, self.parse_word_value(value)?))
+            })
+            .collect()
+    }
+
+    /// Parse TEXT directive (e.g., `TEXT "HELLO"`) into packed words
+    ///
+    /// Characters are encoded in EBCDIC, as punched on a card, two per word
+    /// with the first in the high byte. An odd final character is padded
+    /// with a blank.
+    fn parse_text_directive(&self, line: &str) -> Result<Vec<u16>, AssemblerError> {
+        let operand = line
+            .trim_start()
+            .split_once(char::is_whitespace)
+            .map(|(_, rest)| rest.trim())
+            .unwrap_or("");
+
+        let text = operand
+            .strip_prefix('"')
+            .ok_or_else(|| {
+                AssemblerError::SyntaxError("TEXT directive requires a quoted string".to_string())
+            })?
+            .strip_suffix('"')
+            .ok_or_else(|| {
+                AssemblerError::SyntaxError(format!("Unterminated string literal: {operand}"))
+            })?;
+
+        let bytes = text
+            .chars()
+            .map(|c| {
+                punch_card_core::hollerith::char_to_hollerith(c.to_ascii_uppercase())
+                    .map(|code| punch_card_core::ebcdic::hollerith_to_ebcdic(&code))
+                    .ok_or_else(|| {
+                        AssemblerError::InvalidOperand(format!(
+                            "Unsupported character in TEXT: {c:?}"
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+
+        Ok(bytes
+            .chunks(2)
+            .map(|pair| {
+                let low = pair.get(1).copied().unwrap_or(EBCDIC_BLANK);
+                ((pair[0] as u16) << 8) | low as u16
+            })
+            .collect())
+    }
+
+    /// Parse a 16-bit data word
+    ///
+    /// Accepts everything `parse_address` does plus signed decimal, stored as
+    /// two's complement (`-1` becomes `0xFFFF`).
+    fn parse_word_value(&self, s: &str) -> Result<u16, AssemblerError> {
+        let invalid = || AssemblerError::InvalidDataValue(s.to_string());
+
+        if s.starts_with(['-', '+']) {
+            let value = s.parse::<i32>().map_err(|_| invalid())?;
+            if !(i16::MIN as i32..=u16::MAX as i32).contains(&value) {
+                return Err(invalid());
+            }
+            return Ok(value as u16);
+        }
+
+        self.parse_address(s).map_err(|_| invalid())
+    }
+}
+
+impl Default for Assembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Modifier bit selecting the double-word variant of LD/STO (LDD/STD)
+const DOUBLE_WORD: u16 = 0b0100;
+
+/// Modifier bit selecting the sign-extending variant of LD (LDS)
+const SIGN_EXTEND: u16 = 0b1000;
+
+/// Modifier bit selecting the carry-in variants of A and S (AC, SC)
+const WITH_CARRY: u16 = 0b0100;
+
+/// Encode an addressing mode into modifier bits (bit 0 = indexed, bit 1 = indirect)
+fn mode_bits(mode: AddressingMode) -> u16 {
+    match mode {
+        AddressingMode::Direct => 0b00,
+        AddressingMode::Indexed => 0b01,
+        AddressingMode::DirectIndirect => 0b10,
+        AddressingMode::IndexedIndirect => 0b11,
+    }
+}
+
+/// Decode the addressing mode from an instruction's modifier bits
+fn decode_mode(modifier: u16) -> AddressingMode {
+    match modifier & 0b11 {
+        0b01 => AddressingMode::Indexed,
+        0b10 => AddressingMode::DirectIndirect,
+        0b11 => AddressingMode::IndexedIndirect,
+        _ => AddressingMode::Direct,
+    }
+}
+
+/// Encode a branch condition as its 4-bit condition code
+fn condition_bits(condition: BranchCondition) -> u16 {
+    match condition {
+        BranchCondition::Zero => 0,
+        BranchCondition::NonZero => 1,
+        BranchCondition::Positive => 2,
+        BranchCondition::Negative => 3,
+        BranchCondition::Overflow => 4,
+        BranchCondition::Carry => 5,
+        BranchCondition::EvenAcc => 6,
+        BranchCondition::DoubleZero => 7,
+        BranchCondition::LessSigned => 8,
+        BranchCondition::GreaterEqualSigned => 9,
+        BranchCondition::ExtNegative => 10,
+        BranchCondition::ExtZero => 11,
+    }
+}
+
+/// Decode a condition code into a branch condition
+fn decode_condition(bits: u16) -> Result<BranchCondition, AssemblerError> {
+    match bits {
+        0 => Ok(BranchCondition::Zero),
+        1 => Ok(BranchCondition::NonZero),
+        2 => Ok(BranchCondition::Positive),
+        3 => Ok(BranchCondition::Negative),
+        4 => Ok(BranchCondition::Overflow),
+        5 => Ok(BranchCondition::Carry),
+        6 => Ok(BranchCondition::EvenAcc),
+        7 => Ok(BranchCondition::DoubleZero),
+        8 => Ok(BranchCondition::LessSigned),
+        9 => Ok(BranchCondition::GreaterEqualSigned),
+        10 => Ok(BranchCondition::ExtNegative),
+        11 => Ok(BranchCondition::ExtZero),
+        _ => Err(AssemblerError::InvalidCondition(format!(
+            "Unknown condition code: {bits}"
+        ))),
+    }
+}
+
+/// Check that a resolved address fits the 8-bit address field
+fn address_field(addr: u16) -> Result<u16, AssemblerError> {
+    if addr > 0xFF {
+        return Err(AssemblerError::OperandOutOfRange(addr));
+    }
+    Ok(addr)
+}
+
+/// Encode an instruction into a 16-bit opcode
+///
+/// Simplified encoding scheme for educational purposes:
+/// - Bits 15-12: Opcode
+/// - Bits 11-8: Subopcode/modifier
+/// - Bits 7-0: Address/operand
+///
+/// An address that does not fit bits 7-0 is an `OperandOutOfRange` error.
+pub fn encode_instruction(instr: &Instruction) -> Result<u16, AssemblerError> {
     match instr {
         Instruction::LD { addr, mode } => {
-            let mode_bit = if matches!(mode, AddressingMode::Indexed) {
-                1
-            } else {
-                0
-            };
-            Ok(0x1000 | (mode_bit << 8) | (addr & 0xFF))
+            Ok(0x1000 | (mode_bits(*mode) << 8) | address_field(*addr)?)
         }
         Instruction::STO { addr, mode } => {
-            let mode_bit = if matches!(mode, AddressingMode::Indexed) {
-                1
-            } else {
-                0
-            };
-            Ok(0x2000 | (mode_bit << 8) | (addr & 0xFF))
+            Ok(0x2000 | (mode_bits(*mode) << 8) | address_field(*addr)?)
         }
-        Instruction::LDX { addr } => Ok(0x3000 | (addr & 0xFF)),
-        Instruction::STX { addr } => Ok(0x4000 | (addr & 0xFF)),
+        Instruction::LDD { addr, mode } => {
+            Ok(0x1000 | ((DOUBLE_WORD | mode_bits(*mode)) << 8) | address_field(*addr)?)
+        }
+        Instruction::LDS { addr, mode } => {
+            Ok(0x1000 | ((SIGN_EXTEND | mode_bits(*mode)) << 8) | address_field(*addr)?)
+        }
+        Instruction::STD { addr, mode } => {
+            Ok(0x2000 | ((DOUBLE_WORD | mode_bits(*mode)) << 8) | address_field(*addr)?)
+        }
+        Instruction::LDX { addr } => Ok(0x3000 | address_field(*addr)?),
+        Instruction::STX { addr } => Ok(0x4000 | address_field(*addr)?),
         Instruction::A { addr, mode } => {
-            let mode_bit = if matches!(mode, AddressingMode::Indexed) {
-                1
-            } else {
-                0
-            };
-            Ok(0x5000 | (mode_bit << 8) | (addr & 0xFF))
+            Ok(0x5000 | (mode_bits(*mode) << 8) | address_field(*addr)?)
+        }
+        Instruction::AC { addr, mode } => {
+            Ok(0x5000 | ((WITH_CARRY | mode_bits(*mode)) << 8) | address_field(*addr)?)
         }
         Instruction::S { addr, mode } => {
-            let mode_bit = if matches!(mode, AddressingMode::Indexed) {
-                1
-            } else {
-                0
-            };
-            Ok(0x6000 | (mode_bit << 8) | (addr & 0xFF))
+            Ok(0x6000 | (mode_bits(*mode) << 8) | address_field(*addr)?)
+        }
+        Instruction::SC { addr, mode } => {
+            Ok(0x6000 | ((WITH_CARRY | mode_bits(*mode)) << 8) | address_field(*addr)?)
+        }
+        Instruction::M { addr, mode } => {
+            Ok(0xD000 | (mode_bits(*mode) << 8) | address_field(*addr)?)
+        }
+        Instruction::D { addr, mode } => {
+            Ok(0xE000 | (mode_bits(*mode) << 8) | address_field(*addr)?)
         }
         Instruction::AND { addr, mode } => {
-            let mode_bit = if matches!(mode, AddressingMode::Indexed) {
-                1
-            } else {
-                0
-            };
-            Ok(0x7000 | (mode_bit << 8) | (addr & 0xFF))
+            Ok(0x7000 | (mode_bits(*mode) << 8) | address_field(*addr)?)
         }
         Instruction::OR { addr, mode } => {
-            let mode_bit = if matches!(mode, AddressingMode::Indexed) {
-                1
-            } else {
-                0
-            };
-            Ok(0x8000 | (mode_bit << 8) | (addr & 0xFF))
+            Ok(0x8000 | (mode_bits(*mode) << 8) | address_field(*addr)?)
         }
         Instruction::SLA { count } => Ok(0x9000 | (*count as u16)),
         Instruction::SRA { count } => Ok(0xA000 | (*count as u16)),
+        Instruction::SRL { count } => Ok(0xA100 | (*count as u16)),
+        Instruction::SLT { count } => Ok(0x9100 | (*count as u16)),
+        Instruction::SRT { count } => Ok(0xA200 | (*count as u16)),
         Instruction::BSC { addr, condition } => {
-            let cond_bits = match condition {
-                BranchCondition::Zero => 0,
-                BranchCondition::NonZero => 1,
-                BranchCondition::Positive => 2,
-                BranchCondition::Negative => 3,
-                BranchCondition::Overflow => 4,
-                BranchCondition::Carry => 5,
-            };
-            Ok(0xB000 | (cond_bits << 8) | (addr & 0xFF))
+            Ok(0xB000 | (condition_bits(*condition) << 8) | address_field(*addr)?)
         }
-        Instruction::BSI { addr } => Ok(0xC000 | (addr & 0xFF)),
+        Instruction::BSI { addr } => Ok(0xC000 | address_field(*addr)?),
+        Instruction::RET { addr } => Ok(0xC200 | address_field(*addr)?),
+        Instruction::SKP { condition } => Ok(0xC300 | condition_bits(*condition)),
+        Instruction::BRL { offset } => Ok(0xC100 | (*offset as u8 as u16)),
         Instruction::WAIT => Ok(0xF000),
+        Instruction::XCH => Ok(0xF100),
+        Instruction::CLRF => Ok(0xF200),
+        Instruction::LDSTAT => Ok(0xF300),
+        Instruction::STSTAT => Ok(0xF400),
         Instruction::NOP => Ok(0x0000),
+        Instruction::Data { word } => Ok(*word),
     }
 }
 
+/// Shift count from the low byte of a shift opcode, rejecting counts past
+/// `MAX_SHIFT` (or `MAX_DOUBLE_SHIFT` for a double-word shift)
+fn decode_shift_count(opcode: u16, double: bool) -> Result<u8, AssemblerError> {
+    let count = (opcode & 0xFF) as u8;
+    let max = if double { MAX_DOUBLE_SHIFT } else { MAX_SHIFT };
+    if count > max {
+        return Err(AssemblerError::InvalidShiftCount(count.to_string()));
+    }
+    Ok(count)
+}
+
 /// Decode a 16-bit opcode into an Instruction
+///
+/// Words that are not the exact encoding of an instruction (an unknown
+/// condition code, stray modifier or operand bits) decode to
+/// `Instruction::Data` holding the raw word, so data in memory can always
+/// be shown.
 pub fn decode_instruction(opcode: u16) -> Result<Instruction, AssemblerError> {
+    let data = Instruction::Data { word: opcode };
+    let Ok(instr) = decode_fields(opcode) else {
+        return Ok(data);
+    };
+    if encode_instruction(&instr)? == opcode {
+        Ok(instr)
+    } else {
+        Ok(data)
+    }
+}
+
+/// Decode opcode, modifier, and operand fields, ignoring bits the
+/// instruction does not use
+fn decode_fields(opcode: u16) -> Result<Instruction, AssemblerError> {
     let op = (opcode >> 12) & 0xF;
     let modifier = (opcode >> 8) & 0xF;
     let addr = opcode & 0xFF;
 
     match op {
         0x0 => Ok(Instruction::NOP),
-        0x1 => {
-            let mode = if modifier == 1 {
-                AddressingMode::Indexed
-            } else {
-                AddressingMode::Direct
-            };
-            Ok(Instruction::LD { addr, mode })
-        }
-        0x2 => {
-            let mode = if modifier == 1 {
-                AddressingMode::Indexed
-            } else {
-                AddressingMode::Direct
-            };
-            Ok(Instruction::STO { addr, mode })
-        }
+        0x1 if modifier & SIGN_EXTEND != 0 => Ok(Instruction::LDS {
+            addr,
+            mode: decode_mode(modifier),
+        }),
+        0x1 if modifier & DOUBLE_WORD != 0 => Ok(Instruction::LDD {
+            addr,
+            mode: decode_mode(modifier),
+        }),
+        0x1 => Ok(Instruction::LD {
+            addr,
+            mode: decode_mode(modifier),
+        }),
+        0x2 if modifier & DOUBLE_WORD != 0 => Ok(Instruction::STD {
+            addr,
+            mode: decode_mode(modifier),
+        }),
+        0x2 => Ok(Instruction::STO {
+            addr,
+            mode: decode_mode(modifier),
+        }),
         0x3 => Ok(Instruction::LDX { addr }),
         0x4 => Ok(Instruction::STX { addr }),
-        0x5 => {
-            let mode = if modifier == 1 {
-                AddressingMode::Indexed
-            } else {
-                AddressingMode::Direct
-            };
-            Ok(Instruction::A { addr, mode })
-        }
-        0x6 => {
-            let mode = if modifier == 1 {
-                AddressingMode::Indexed
-            } else {
-                AddressingMode::Direct
-            };
-            Ok(Instruction::S { addr, mode })
-        }
-        0x7 => {
-            let mode = if modifier == 1 {
-                AddressingMode::Indexed
+        0x5 if modifier & WITH_CARRY != 0 => Ok(Instruction::AC {
+            addr,
+            mode: decode_mode(modifier),
+        }),
+        0x5 => Ok(Instruction::A {
+            addr,
+            mode: decode_mode(modifier),
+        }),
+        0x6 if modifier & WITH_CARRY != 0 => Ok(Instruction::SC {
+            addr,
+            mode: decode_mode(modifier),
+        }),
+        0x6 => Ok(Instruction::S {
+            addr,
+            mode: decode_mode(modifier),
+        }),
+        0x7 => Ok(Instruction::AND {
+            addr,
+            mode: decode_mode(modifier),
+        }),
+        0x8 => Ok(Instruction::OR {
+            addr,
+            mode: decode_mode(modifier),
+        }),
+        0x9 => {
+            let double = modifier == 1;
+            let count = decode_shift_count(opcode, double)?;
+            if double {
+                Ok(Instruction::SLT { count })
             } else {
-                AddressingMode::Direct
-            };
-            Ok(Instruction::AND { addr, mode })
+                Ok(Instruction::SLA { count })
+            }
         }
-        0x8 => {
-            let mode = if modifier == 1 {
-                AddressingMode::Indexed
-            } else {
-                AddressingMode::Direct
-            };
-            Ok(Instruction::OR { addr, mode })
+        0xA => {
+            let count = decode_shift_count(opcode, modifier == 2)?;
+            match modifier {
+                1 => Ok(Instruction::SRL { count }),
+                2 => Ok(Instruction::SRT { count }),
+                _ => Ok(Instruction::SRA { count }),
+            }
         }
-        0x9 => Ok(Instruction::SLA {
-            count: (opcode & 0xFF) as u8,
+        0xB => Ok(Instruction::BSC {
+            addr,
+            condition: decode_condition(modifier)?,
         }),
-        0xA => Ok(Instruction::SRA {
-            count: (opcode & 0xFF) as u8,
+        0xC => match modifier {
+            1 => Ok(Instruction::BRL {
+                offset: addr as u8 as i8,
+            }),
+            2 => Ok(Instruction::RET { addr }),
+            3 => Ok(Instruction::SKP {
+                condition: decode_condition(addr)?,
+            }),
+            _ => Ok(Instruction::BSI { addr }),
+        },
+        0xD => Ok(Instruction::M {
+            addr,
+            mode: decode_mode(modifier),
         }),
-        0xB => {
-            let condition = match modifier {
-                0 => BranchCondition::Zero,
-                1 => BranchCondition::NonZero,
-                2 => BranchCondition::Positive,
-                3 => BranchCondition::Negative,
-                4 => BranchCondition::Overflow,
-                5 => BranchCondition::Carry,
-                _ => {
-                    return Err(AssemblerError::InvalidCondition(format!(
-                        "Unknown condition code: {modifier}"
-                    )));
-                }
-            };
-            Ok(Instruction::BSC { addr, condition })
-        }
-        0xC => Ok(Instruction::BSI { addr }),
-        0xF => Ok(Instruction::WAIT),
+        0xE => Ok(Instruction::D {
+            addr,
+            mode: decode_mode(modifier),
+        }),
+        0xF => match modifier {
+            1 => Ok(Instruction::XCH),
+            2 => Ok(Instruction::CLRF),
+            3 => Ok(Instruction::LDSTAT),
+            4 => Ok(Instruction::STSTAT),
+            _ => Ok(Instruction::WAIT),
+        },
         _ => Err(AssemblerError::InvalidMnemonic(format!(
             "Unknown opcode: 0x{op:X}"
         ))),
     }
 }
 
+/// Render an addressing mode as its assembler operand ("0", "1", "I", "1I")
+fn mode_str(mode: AddressingMode) -> &'static str {
+    match mode {
+        AddressingMode::Direct => "0",
+        AddressingMode::Indexed => "1",
+        AddressingMode::DirectIndirect => "I",
+        AddressingMode::IndexedIndirect => "1I",
+    }
+}
+
+/// Expand zero-argument macros ahead of the two assembly passes
+///
+/// `MACRO name` ... `ENDM` defines a body and is removed from the source. A
+/// line whose code is just a macro's name (optionally labelled) is replaced by
+/// the body, with the label kept on a line of its own; bodies may invoke
+/// other macros but not, directly or indirectly, themselves. Returns the
+/// expanded lines, the 0-based source line each came from, and any errors.
+fn expand_macros(lines: &[&str]) -> (Vec<String>, Vec<usize>, Vec<(usize, AssemblerError)>) {
+    let mut errors = Vec::new();
+    let mut macros: HashMap<String, Vec<&str>> = HashMap::new();
+    let mut kept = Vec::new();
+    // (line of the MACRO, name if valid, body so far)
+    let mut open: Option<(usize, Option<String>, Vec<&str>)> = None;
+
+    for (index, &line) in lines.iter().enumerate() {
+        let stmt = Statement::parse(line);
+        let keyword = stmt.keyword();
+        if let Some((start, name, body)) = &mut open {
+            match keyword.as_str() {
+                "ENDM" => {
+                    if let Some(name) = name.take()
+                        && macros.insert(name.clone(), std::mem::take(body)).is_some()
+                    {
+                        errors.push((*start, AssemblerError::DuplicateSymbol(name)));
+                    }
+                    open = None;
+                }
+                "MACRO" => errors.push((
+                    index,
+                    AssemblerError::SyntaxError("MACRO inside a macro definition".to_string()),
+                )),
+                _ => body.push(line),
+            }
+            continue;
+        }
+
+        match keyword.as_str() {
+            "MACRO" => {
+                let name = stmt.code.split_whitespace().nth(1);
+                let name = match name {
+                    Some(name) if is_valid_symbol(name) && !is_keyword(name) => {
+                        Some(name.to_uppercase())
+                    }
+                    _ => {
+                        errors.push((
+                            index,
+                            AssemblerError::SyntaxError("MACRO requires a name".to_string()),
+                        ));
+                        None
+                    }
+                };
+                open = Some((index, name, Vec::new()));
+            }
+            "ENDM" => errors.push((
+                index,
+                AssemblerError::SyntaxError("ENDM without matching MACRO".to_string()),
+            )),
+            _ => kept.push(index),
+        }
+    }
+    if let Some((start, ..)) = open {
+        errors.push((
+            start,
+            AssemblerError::SyntaxError("MACRO without matching ENDM".to_string()),
+        ));
+    }
+
+    let mut expanded = Vec::new();
+    let mut origins = Vec::new();
+    for index in kept {
+        let mut emit = |line: String| {
+            expanded.push(line);
+            origins.push(index);
+        };
+        if let Err(error) = expand_line(lines[index], &macros, &mut Vec::new(), &mut emit) {
+            errors.push((index, error));
+        }
+    }
+    (expanded, origins, errors)
+}
+
+/// Pass one line to `emit`, replacing a macro invocation with its body
+///
+/// `active` holds the macros being expanded, to catch recursion.
+fn expand_line(
+    line: &str,
+    macros: &HashMap<String, Vec<&str>>,
+    active: &mut Vec<String>,
+    emit: &mut impl FnMut(String),
+) -> Result<(), AssemblerError> {
+    let stmt = Statement::parse(line);
+    // A lone name in the label column parses as a label; treat it as a call
+    let first = line.split_whitespace().next().unwrap_or("");
+    let (label, name) = match (stmt.label, stmt.code) {
+        (Some(name), "") if !first.ends_with(':') => (None, name),
+        (label, code) => (label, code),
+    };
+    let name = name.to_uppercase();
+    let Some(body) = macros.get(&name) else {
+        emit(line.to_string());
+        return Ok(());
+    };
+
+    if active.contains(&name) {
+        return Err(AssemblerError::RecursiveMacro(name));
+    }
+    if let Some(label) = label {
+        emit(format!("{label}:"));
+    }
+    active.push(name);
+    for body_line in body {
+        expand_line(body_line, macros, active, emit)?;
+    }
+    active.pop();
+    Ok(())
+}
+
+/// Find branches into data and instructions that run on into data
+///
+/// `code` holds (address, opcode, 0-based line index) for each instruction
+/// and `data` every address holding data. Only targets known at assembly
+/// time are checked, so indexed and indirect branches are not. A BSS word
+/// that code is later assembled over (after an `ORG`) is treated as code.
+fn flow_warnings(code: &[(u16, u16, usize)], data: &HashSet<u16>) -> Vec<AssemblerWarning> {
+    let code_addrs: HashSet<u16> = code.iter().map(|&(addr, _, _)| addr).collect();
+    let is_data = |addr: u16| data.contains(&addr) && !code_addrs.contains(&addr);
+
+    let mut warnings = Vec::new();
+    for &(addr, opcode, index) in code {
+        let line = index + 1;
+        let Ok(instr) = decode_instruction(opcode) else {
+            continue;
+        };
+        let target = match instr {
+            Instruction::BSC { addr, .. } => Some(addr),
+            // BSI stores the return address at its operand and enters the word after it
+            Instruction::BSI { addr } => Some(addr.wrapping_add(1)),
+            Instruction::BRL { offset } => Some(addr.wrapping_add(offset as i16 as u16)),
+            _ => None,
+        };
+        if let Some(target) = target.filter(|&target| is_data(target)) {
+            warnings.push(AssemblerWarning::BranchIntoData { line, target });
+        }
+
+        let ends_flow = matches!(
+            instr,
+            Instruction::WAIT | Instruction::RET { .. } | Instruction::BRL { .. }
+        );
+        let next = addr.wrapping_add(1);
+        if !ends_flow && is_data(next) {
+            warnings.push(AssemblerWarning::FallsIntoData {
+                line,
+                address: next,
+            });
+        }
+    }
+    warnings.sort_by_key(AssemblerWarning::line);
+    warnings
+}
+
+/// Render an instruction as canonical assembly text (e.g. `LD 0 50`, `BSC Z 100`)
+///
+/// The output parses back to an equal instruction with the assembler, except
+/// for `Instruction::Data`: a raw word has no address of its own, so it renders
+/// as `DATA 0xWORD`, which is not a complete DATA directive. Callers that know
+/// where the word lives (`disassemble_range`) add the address.
+pub fn disassemble(instr: &Instruction) -> String {
+    let mnemonic = instr.mnemonic();
+    match instr {
+        Instruction::LD { addr, mode }
+        | Instruction::STO { addr, mode }
+        | Instruction::LDS { addr, mode }
+        | Instruction::LDD { addr, mode }
+        | Instruction::STD { addr, mode }
+        | Instruction::A { addr, mode }
+        | Instruction::AC { addr, mode }
+        | Instruction::S { addr, mode }
+        | Instruction::SC { addr, mode }
+        | Instruction::M { addr, mode }
+        | Instruction::D { addr, mode }
+        | Instruction::AND { addr, mode }
+        | Instruction::OR { addr, mode } => format!("{mnemonic} {} {addr}", mode_str(*mode)),
+        Instruction::LDX { addr }
+        | Instruction::STX { addr }
+        | Instruction::BSI { addr }
+        | Instruction::RET { addr } => format!("{mnemonic} {addr}"),
+        Instruction::SLA { count }
+        | Instruction::SRA { count }
+        | Instruction::SRL { count }
+        | Instruction::SLT { count }
+        | Instruction::SRT { count } => format!("{mnemonic} {count}"),
+        Instruction::BSC { addr, condition } => {
+            format!("{mnemonic} {} {addr}", condition.to_str())
+        }
+        Instruction::SKP { condition } => format!("{mnemonic} {}", condition.to_str()),
+        Instruction::BRL { offset } => format!("{mnemonic} {offset}"),
+        Instruction::WAIT
+        | Instruction::XCH
+        | Instruction::CLRF
+        | Instruction::LDSTAT
+        | Instruction::STSTAT
+        | Instruction::NOP => mnemonic.to_string(),
+        Instruction::Data { word } => format!("{mnemonic} 0x{word:04X}"),
+    }
+}
+
+/// Render the instruction held at `address` as assembly text that reassembles
+/// to the same word there; data words become `DATA <address> 0xWORD`
+fn disassemble_at(instr: &Instruction, address: u16) -> String {
+    match instr {
+        Instruction::Data { word } => format!("DATA {address} 0x{word:04X}"),
+        instr => disassemble(instr),
+    }
+}
+
+/// Disassemble a range of memory, clamped to the end of `memory`
+///
+/// Words that do not decode to an instruction are rendered as `DATA`.
+pub fn disassemble_range(memory: &[u16], start: u16, count: u16) -> Vec<DisassembledWord> {
+    let start = (start as usize).min(memory.len());
+    let end = (start + count as usize).min(memory.len());
+
+    memory[start..end]
+        .iter()
+        .enumerate()
+        .map(|(offset, &word)| {
+            let address = (start + offset) as u16;
+            let instr = decode_instruction(word).unwrap_or(Instruction::Data { word });
+            let text = disassemble_at(&instr, address);
+            DisassembledWord {
+                address,
+                word,
+                text,
+            }
+        })
+        .collect()
+}
+
+/// Render memory from `start` as a copyable listing, one word per line
+/// (`0xADDR  0xWORD  TEXT`), ending with the first WAIT or the end of memory
+///
+/// Words that do not decode to an instruction are rendered as `DATA <address> 0xWORD`,
+/// so each line assembled at its address gives back its word.
+pub fn listing_text(memory: &[u16], start: u16) -> String {
+    let mut lines = Vec::new();
+    for (offset, &word) in memory.iter().skip(start as usize).enumerate() {
+        let address = (start as usize + offset) as u16;
+        let instr = decode_instruction(word).unwrap_or(Instruction::Data { word });
+        lines.push(format!(
+            "0x{address:04X}  0x{word:04X}  {}",
+            disassemble_at(&instr, address)
+        ));
+        if instr == Instruction::WAIT {
+            break;
+        }
+    }
+    lines.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -515,6 +1871,10 @@ mod tests {
                 mode: AddressingMode::Indexed,
             },
             Instruction::SLA { count: 3 },
+            Instruction::SRA { count: 2 },
+            Instruction::SRL { count: 5 },
+            Instruction::SLT { count: 16 },
+            Instruction::SRT { count: 31 },
             Instruction::BSC {
                 addr: 100,
                 condition: BranchCondition::Zero,
@@ -530,6 +1890,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_multiply_divide_roundtrip() {
+        let instructions = vec![
+            Instruction::M {
+                addr: 30,
+                mode: AddressingMode::Direct,
+            },
+            Instruction::M {
+                addr: 31,
+                mode: AddressingMode::Indexed,
+            },
+            Instruction::D {
+                addr: 32,
+                mode: AddressingMode::Direct,
+            },
+            Instruction::D {
+                addr: 33,
+                mode: AddressingMode::Indexed,
+            },
+        ];
+
+        for original in instructions {
+            let opcode = encode_instruction(&original).unwrap();
+            let decoded = decode_instruction(opcode).unwrap();
+            assert_eq!(original, decoded);
+        }
+
+        let asm = Assembler::new();
+        assert_eq!(
+            asm.parse_line("M 0 30").unwrap(),
+            Instruction::M {
+                addr: 30,
+                mode: AddressingMode::Direct
+            }
+        );
+        assert_eq!(
+            asm.parse_line("D 1 32").unwrap(),
+            Instruction::D {
+                addr: 32,
+                mode: AddressingMode::Indexed
+            }
+        );
+    }
+
     #[test]
     fn test_assemble_program() {
         let source = r#"
@@ -547,6 +1951,44 @@ mod tests {
         assert_eq!(result.listing.len(), 4);
     }
 
+    #[test]
+    fn test_parse_indirect_modes() {
+        let asm = Assembler::new();
+
+        assert_eq!(
+            asm.parse_line("LD I 50").unwrap(),
+            Instruction::LD {
+                addr: 50,
+                mode: AddressingMode::DirectIndirect
+            }
+        );
+        assert_eq!(
+            asm.parse_line("STO 1I 60").unwrap(),
+            Instruction::STO {
+                addr: 60,
+                mode: AddressingMode::IndexedIndirect
+            }
+        );
+        assert!(matches!(
+            asm.parse_line("LD X 50"),
+            Err(AssemblerError::InvalidMode(_))
+        ));
+    }
+
+    #[test]
+    fn test_indirect_encode_decode_roundtrip() {
+        for mode in [
+            AddressingMode::Direct,
+            AddressingMode::Indexed,
+            AddressingMode::DirectIndirect,
+            AddressingMode::IndexedIndirect,
+        ] {
+            let original = Instruction::A { addr: 42, mode };
+            let opcode = encode_instruction(&original).unwrap();
+            assert_eq!(decode_instruction(opcode).unwrap(), original);
+        }
+    }
+
     #[test]
     fn test_parse_hex_addresses() {
         let asm = Assembler::new();
@@ -560,6 +2002,27 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_parse_srl() {
+        let asm = Assembler::new();
+        let instr = asm.parse_line("SRL 1").unwrap();
+        assert_eq!(instr, Instruction::SRL { count: 1 });
+        assert_eq!(encode_instruction(&instr).unwrap(), 0xA101);
+    }
+
+    #[test]
+    fn test_parse_double_shifts() {
+        let asm = Assembler::new();
+        assert_eq!(
+            asm.parse_line("SLT 4").unwrap(),
+            Instruction::SLT { count: 4 }
+        );
+        assert_eq!(
+            asm.parse_line("SRT 12").unwrap(),
+            Instruction::SRT { count: 12 }
+        );
+    }
+
     #[test]
     fn test_parse_branch_conditions() {
         let asm = Assembler::new();
@@ -582,4 +2045,1284 @@ mod tests {
             }
         ));
     }
+
+    #[test]
+    fn test_forward_label_reference() {
+        let mut asm = Assembler::new();
+        let source = "ORG 16\n\
+                      BSC Z DONE\n\
+                      LD 0 0x30\n\
+                      DONE: WAIT";
+        let program = asm.assemble(source).unwrap();
+
+        assert_eq!(program.code.len(), 3);
+        assert_eq!(
+            decode_instruction(program.code[0]).unwrap(),
+            Instruction::BSC {
+                addr: 18,
+                condition: BranchCondition::Zero
+            }
+        );
+    }
+
+    #[test]
+    fn test_backward_label_loop() {
+        let mut asm = Assembler::new();
+        let source = "ORG 0x20\n\
+                      LOOP  A 0 0x30  ; label in column 1\n\
+                      BSC NZ LOOP\n\
+                      WAIT";
+        let program = asm.assemble(source).unwrap();
+
+        assert_eq!(program.listing[0].address, 0x20);
+        assert_eq!(
+            program.listing[0].source,
+            "LOOP  A 0 0x30  ; label in column 1"
+        );
+        assert_eq!(
+            decode_instruction(program.code[1]).unwrap(),
+            Instruction::BSC {
+                addr: 0x20,
+                condition: BranchCondition::NonZero
+            }
+        );
+    }
+
+    #[test]
+    fn test_labels_are_case_insensitive() {
+        let mut asm = Assembler::new();
+        let program = asm.assemble("Start: NOP\nBSC Z start").unwrap();
+
+        assert_eq!(
+            decode_instruction(program.code[1]).unwrap(),
+            Instruction::BSC {
+                addr: crate::cpu::PROGRAM_START,
+                condition: BranchCondition::Zero
+            }
+        );
+    }
+
+    #[test]
+    fn test_undefined_label() {
+        let mut asm = Assembler::new();
+        let err = asm.assemble("BSC Z NOWHERE\nWAIT").unwrap_err();
+        assert_eq!(err.line, 1);
+        assert_eq!(
+            err.error,
+            AssemblerError::UndefinedLabel("NOWHERE".to_string())
+        );
+    }
+
+    #[test]
+    fn test_label_past_address_field() {
+        let mut asm = Assembler::new();
+        let err = asm
+            .assemble("ORG 0x300\nLOOP: NOP\nBSC Z LOOP")
+            .unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.error, AssemblerError::OperandOutOfRange(0x300));
+
+        // The last address the field can hold still assembles
+        let mut asm = Assembler::new();
+        let program = asm.assemble("ORG 0xFF\nLOOP: NOP\nBSC Z LOOP").unwrap();
+        assert_eq!(program.placements[1], (0x100, 0xB0FF));
+    }
+
+    #[test]
+    fn test_equ_constants() {
+        let mut asm = Assembler::new();
+        let source = "COUNT EQU 3\n\
+                      BUF   EQU 0x30\n\
+                      ORG 16\n\
+                      LD 0 BUF\n\
+                      SLA COUNT\n\
+                      WAIT\n\
+                      DATA BUF COUNT";
+        let program = asm.assemble(source).unwrap();
+
+        assert_eq!(
+            decode_instruction(program.code[0]).unwrap(),
+            Instruction::LD {
+                addr: 0x30,
+                mode: AddressingMode::Direct
+            }
+        );
+        assert_eq!(
+            decode_instruction(program.code[1]).unwrap(),
+            Instruction::SLA { count: 3 }
+        );
+        assert_eq!(
+            asm.parse_data_directive("DATA BUF COUNT").unwrap(),
+            vec![(0x30, 3)]
+        );
+    }
+
+    #[test]
+    fn test_equ_operand_past_address_field() {
+        let mut asm = Assembler::new();
+        let err = asm.assemble("X EQU 300\nLD 0 X").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.error, AssemblerError::OperandOutOfRange(300));
+
+        // A large constant is still fine where the full word is used
+        let mut asm = Assembler::new();
+        let program = asm.assemble("X EQU 300\nORG 0x10\nDATA 0x20 X").unwrap();
+        assert_eq!(program.placements, vec![(0x20, 300)]);
+    }
+
+    #[test]
+    fn test_duplicate_symbol() {
+        let mut asm = Assembler::new();
+        let err = asm.assemble("COUNT EQU 3\nCOUNT EQU 4").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(
+            err.error,
+            AssemblerError::DuplicateSymbol("COUNT".to_string())
+        );
+
+        let err = asm.assemble("LOOP: NOP\nLOOP: WAIT").unwrap_err();
+        assert_eq!(
+            err.error,
+            AssemblerError::DuplicateSymbol("LOOP".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_signed_data_values() {
+        let asm = Assembler::new();
+
+        assert_eq!(asm.parse_word_value("-1").unwrap(), 0xFFFF);
+        assert_eq!(asm.parse_word_value("-5").unwrap(), 0xFFFB);
+        assert_eq!(asm.parse_word_value("-32768").unwrap(), 0x8000);
+        assert_eq!(asm.parse_word_value("32767").unwrap(), 0x7FFF);
+        assert_eq!(asm.parse_word_value("0xFFFF").unwrap(), 0xFFFF);
+        assert_eq!(
+            asm.parse_data_directive("DATA 30 -5").unwrap(),
+            vec![(30, 0xFFFB)]
+        );
+    }
+
+    #[test]
+    fn test_data_value_out_of_range() {
+        let asm = Assembler::new();
+
+        assert_eq!(
+            asm.parse_word_value("-40000").unwrap_err(),
+            AssemblerError::InvalidDataValue("-40000".to_string())
+        );
+        assert_eq!(
+            asm.parse_word_value("70000").unwrap_err(),
+            AssemblerError::InvalidDataValue("70000".to_string())
+        );
+    }
+
+    #[test]
+    fn test_disassemble_text() {
+        assert_eq!(
+            disassemble(&Instruction::LD {
+                addr: 50,
+                mode: AddressingMode::Direct
+            }),
+            "LD 0 50"
+        );
+        assert_eq!(
+            disassemble(&Instruction::BSC {
+                addr: 100,
+                condition: BranchCondition::Zero
+            }),
+            "BSC Z 100"
+        );
+        assert_eq!(disassemble(&Instruction::SLA { count: 3 }), "SLA 3");
+        assert_eq!(disassemble(&Instruction::WAIT), "WAIT");
+    }
+
+    #[test]
+    fn test_disassemble_roundtrip() {
+        let asm = Assembler::new();
+        let modes = [
+            AddressingMode::Direct,
+            AddressingMode::Indexed,
+            AddressingMode::DirectIndirect,
+            AddressingMode::IndexedIndirect,
+        ];
+        let conditions = [
+            BranchCondition::Zero,
+            BranchCondition::NonZero,
+            BranchCondition::Positive,
+            BranchCondition::Negative,
+            BranchCondition::Overflow,
+            BranchCondition::Carry,
+            BranchCondition::EvenAcc,
+            BranchCondition::DoubleZero,
+            BranchCondition::LessSigned,
+            BranchCondition::GreaterEqualSigned,
+            BranchCondition::ExtNegative,
+            BranchCondition::ExtZero,
+        ];
+
+        let mut instructions = vec![
+            Instruction::LDX { addr: 0x20 },
+            Instruction::STX { addr: 33 },
+            Instruction::SLA { count: 1 },
+            Instruction::SRA { count: 2 },
+            Instruction::SRL { count: 15 },
+            Instruction::SLT { count: 16 },
+            Instruction::SRT { count: 31 },
+            Instruction::BSI { addr: 200 },
+            Instruction::RET { addr: 200 },
+            Instruction::XCH,
+            Instruction::CLRF,
+            Instruction::LDSTAT,
+            Instruction::STSTAT,
+            Instruction::WAIT,
+            Instruction::NOP,
+        ];
+        for mode in modes {
+            instructions.extend([
+                Instruction::LD { addr: 10, mode },
+                Instruction::STO { addr: 11, mode },
+                Instruction::LDS { addr: 20, mode },
+                Instruction::LDD { addr: 18, mode },
+                Instruction::STD { addr: 19, mode },
+                Instruction::A { addr: 12, mode },
+                Instruction::AC { addr: 12, mode },
+                Instruction::S { addr: 13, mode },
+                Instruction::SC { addr: 13, mode },
+                Instruction::M { addr: 14, mode },
+                Instruction::D { addr: 15, mode },
+                Instruction::AND { addr: 16, mode },
+                Instruction::OR { addr: 17, mode },
+            ]);
+        }
+        for condition in conditions {
+            instructions.push(Instruction::BSC {
+                addr: 0xFF,
+                condition,
+            });
+            instructions.push(Instruction::SKP { condition });
+        }
+        for offset in [i8::MIN, -3, 0, 5, i8::MAX] {
+            instructions.push(Instruction::BRL { offset });
+        }
+
+        for instr in instructions {
+            let text = disassemble(&instr);
+            assert_eq!(asm.parse_line(&text).unwrap(), instr, "{text}");
+        }
+
+        // Data words need an address before they assemble
+        let text = disassemble(&Instruction::Data { word: 0xBF00 });
+        assert!(Assembler::new().assemble(&text).is_err(), "{text}");
+        let with_address = text.replacen("DATA", "DATA 0x30", 1);
+        let program = Assembler::new().assemble(&with_address).unwrap();
+        assert_eq!(program.placements, vec![(0x30, 0xBF00)]);
+    }
+
+    #[test]
+    fn test_disassemble_range() {
+        let mut memory = [0u16; 8];
+        memory[2] = 0x1032; // LD 0 50
+        memory[3] = 0xBF00; // BSC with unknown condition 15
+        memory[4] = 0xF000; // WAIT
+
+        let words = disassemble_range(&memory, 2, 3);
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, ["LD 0 50", "DATA 3 0xBF00", "WAIT"]);
+        assert_eq!(words[1].address, 3);
+        assert_eq!(words[1].word, 0xBF00);
+    }
+
+    #[test]
+    fn test_disassemble_range_clamps_to_memory() {
+        let memory = [0u16; 8];
+
+        assert_eq!(disassemble_range(&memory, 6, 10).len(), 2);
+        assert!(disassemble_range(&memory, 20, 4).is_empty());
+    }
+
+    #[test]
+    fn test_skp_encode_decode_roundtrip() {
+        let asm = Assembler::new();
+        let instr = asm.parse_line("SKP C").unwrap();
+        assert_eq!(
+            instr,
+            Instruction::SKP {
+                condition: BranchCondition::Carry
+            }
+        );
+
+        let opcode = encode_instruction(&instr).unwrap();
+        assert_eq!(opcode, 0xC305);
+        assert_eq!(decode_instruction(opcode).unwrap(), instr);
+        assert_eq!(
+            decode_instruction(0xC000).unwrap(),
+            Instruction::BSI { addr: 0 }
+        );
+    }
+
+    #[test]
+    fn test_double_word_encoding() {
+        let asm = Assembler::new();
+        let ldd = asm.parse_line("LDD 1 0x20").unwrap();
+        let std = asm.parse_line("STD I 0x20").unwrap();
+
+        assert_eq!(encode_instruction(&ldd).unwrap(), 0x1520);
+        assert_eq!(encode_instruction(&std).unwrap(), 0x2620);
+        assert_eq!(decode_instruction(0x1520).unwrap(), ldd);
+        assert_eq!(decode_instruction(0x2620).unwrap(), std);
+        assert!(matches!(
+            decode_instruction(0x1120).unwrap(),
+            Instruction::LD { .. }
+        ));
+
+        let lds = asm.parse_line("LDS 1I 0x20").unwrap();
+        assert_eq!(encode_instruction(&lds).unwrap(), 0x1B20);
+        assert_eq!(decode_instruction(0x1B20).unwrap(), lds);
+        // Sign-extending and double-word bits together are not an instruction
+        assert_eq!(
+            decode_instruction(0x1C20).unwrap(),
+            Instruction::Data { word: 0x1C20 }
+        );
+    }
+
+    #[test]
+    fn test_text_directive_packs_ebcdic() {
+        let mut asm = Assembler::new();
+        let source = "ORG 0x20\n\
+                      MSG: TEXT \"HI\"  ; greeting\n\
+                      ODD: TEXT \"ABC\"\n\
+                      END: WAIT";
+        let program = asm.assemble(source).unwrap();
+
+        assert_eq!(
+            program.placements,
+            vec![
+                (0x20, 0xC8C9),
+                (0x21, 0xC1C2),
+                (0x22, 0xC340),
+                (0x23, 0xF000)
+            ]
+        );
+        assert_eq!(asm.symbols["ODD"], 0x21);
+        assert_eq!(asm.symbols["END"], 0x23);
+    }
+
+    #[test]
+    fn test_text_directive_errors() {
+        let asm = Assembler::new();
+
+        assert!(matches!(
+            asm.parse_text_directive("TEXT \"HI"),
+            Err(AssemblerError::SyntaxError(_))
+        ));
+        assert!(matches!(
+            asm.parse_text_directive("TEXT HI"),
+            Err(AssemblerError::SyntaxError(_))
+        ));
+        assert_eq!(asm.parse_text_directive("TEXT \"a; b\"").unwrap().len(), 2);
+
+        // Text running off the top of the address space is an error, not a wrap
+        assert_eq!(
+            Assembler::new()
+                .assemble("ORG 0xFFFF\nTEXT \"ABCD\"")
+                .unwrap_err()
+                .error,
+            AssemblerError::LocationOverflow(0xFFFF)
+        );
+    }
+
+    #[test]
+    fn test_bss_reserves_words() {
+        let mut asm = Assembler::new();
+        let source = "ORG 0x20\n\
+                      BUF: BSS 10\n\
+                      NEXT: WAIT\n\
+                      LD 0 NEXT";
+        let program = asm.assemble(source).unwrap();
+
+        assert_eq!(asm.symbols["BUF"], 0x20);
+        assert_eq!(asm.symbols["NEXT"], 0x2A);
+        assert_eq!(program.placements, vec![(0x2A, 0xF000), (0x2B, 0x102A)]);
+    }
+
+    #[test]
+    fn test_bss_invalid_counts() {
+        let mut asm = Assembler::new();
+
+        for source in ["BSS 0", "BSS -3", "BSS X"] {
+            assert!(matches!(
+                asm.assemble(source).map_err(|e| e.error),
+                Err(AssemblerError::InvalidBlockSize(_))
+            ));
+        }
+        assert!(matches!(
+            asm.assemble("BSS").map_err(|e| e.error),
+            Err(AssemblerError::SyntaxError(_))
+        ));
+
+        let mut asm = Assembler::new();
+        assert!(matches!(
+            asm.assemble("ORG 0xFFFC\nBSS 4").map_err(|e| e.error),
+            Err(AssemblerError::InvalidBlockSize(_))
+        ));
+        assert!(asm.assemble("ORG 0xFFFC\nBSS 3").is_ok());
+    }
+
+    #[test]
+    fn test_bss_above_4k_loads_on_larger_memory() {
+        let mut asm = Assembler::new();
+        let program = asm
+            .assemble("ORG 0x1FF0\nBUF: BSS 8\nSTART: WAIT\nEND START")
+            .unwrap();
+        assert_eq!(asm.symbols["START"], 0x1FF8);
+
+        let mut cpu = crate::cpu::CpuState::with_memory_words(8192).unwrap();
+        cpu.load_assembled(&program).unwrap();
+        assert_eq!(cpu.read_word(0x1FF8).unwrap(), 0xF000);
+        assert!(
+            crate::cpu::CpuState::new()
+                .load_assembled(&program)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_code_after_bss_at_top_of_address_space() {
+        // BSS may fill up to the last address, but nothing fits after it
+        let error = Assembler::new()
+            .assemble("ORG 0xFFF0\nBSS 15\nNOP")
+            .unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.error, AssemblerError::LocationOverflow(0xFFFF));
+
+        let error = Assembler::new()
+            .assemble("ORG 0xFFFE\nNOP\nNOP\nWAIT")
+            .unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.error, AssemblerError::LocationOverflow(0xFFFF));
+    }
+
+    #[test]
+    fn test_error_reports_line_number() {
+        let mut asm = Assembler::new();
+        let source = "; Sum two values\n\
+                      \n\
+                      LD 0 30\n\
+                      A 0 31\n\
+                      \n\
+                      FOO 0 10   ; not an instruction\n\
+                      WAIT";
+        let err = asm.assemble(source).unwrap_err();
+
+        assert_eq!(err.line, 6);
+        assert_eq!(err.source, "FOO 0 10   ; not an instruction");
+        assert_eq!(
+            err.error,
+            AssemblerError::InvalidMnemonic("FOO".to_string())
+        );
+        assert_eq!(
+            err.to_string(),
+            "Line 6: Invalid instruction mnemonic: FOO (FOO 0 10   ; not an instruction)"
+        );
+    }
+
+    #[test]
+    fn test_object_deck_roundtrip() {
+        let source = "ORG 0x20\n\
+                      LOOP: LD 0 VALUE\n\
+                      A 0 VALUE\n\
+                      STO 0 VALUE\n\
+                      BSC NZ LOOP\n\
+                      WAIT\n\
+                      VALUE EQU 0x40";
+        let program = Assembler::new().assemble(source).unwrap();
+
+        let deck = components::Deck::from_placements(&program.placements);
+        let placements = deck.to_placements().unwrap();
+        let words: Vec<u16> = placements.iter().map(|&(_, word)| word).collect();
+
+        assert_eq!(words, program.code);
+        assert_eq!(placements, program.placements);
+    }
+
+    #[test]
+    fn test_with_origin() {
+        let source = "LD 0 X\nWAIT\nX: BSS 1";
+        let mut assembler = Assembler::with_origin(0x0004);
+        let program = assembler.assemble(source).unwrap();
+        assert_eq!(program.start_addr, 0x0004);
+        assert_eq!(program.listing[0].address, 0x0004);
+        assert_eq!(program.placements[0], (0x0004, 0x1006));
+
+        // Reusing the assembler starts from the origin again
+        let program = assembler.assemble("WAIT").unwrap();
+        assert_eq!(program.listing[0].address, 0x0004);
+
+        // ORG in the source still wins
+        let program = Assembler::with_origin(0x0004)
+            .assemble("ORG 0x100\nWAIT")
+            .unwrap();
+        assert_eq!(program.listing[0].address, 0x0100);
+    }
+
+    #[test]
+    fn test_reserved_address() {
+        let mut asm = Assembler::new();
+        let err = asm.assemble("ORG 1\nLD 0 0x20").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.error, AssemblerError::ReservedAddress(1));
+
+        // DATA is how index registers are initialized
+        let program = asm.assemble("DATA 1 5\nDATA 0 0x4800\nWAIT").unwrap();
+        assert!(program.placements.contains(&(1, 5)));
+
+        // The first address past the index registers is allowed
+        assert!(asm.assemble("ORG 4\nWAIT").is_ok());
+    }
+
+    #[test]
+    fn test_brl_encoding() {
+        let asm = Assembler::new();
+        for (offset, opcode) in [(-3i8, 0xC1FD), (127, 0xC17F), (-128, 0xC180)] {
+            let instr = Instruction::BRL { offset };
+            assert_eq!(encode_instruction(&instr).unwrap(), opcode);
+            assert_eq!(decode_instruction(opcode).unwrap(), instr);
+        }
+        assert_eq!(
+            asm.parse_line("BRL +7").unwrap(),
+            Instruction::BRL { offset: 7 }
+        );
+        assert!(matches!(
+            asm.parse_line("BRL 128"),
+            Err(AssemblerError::InvalidOperand(_))
+        ));
+        assert!(matches!(
+            asm.parse_line("BRL"),
+            Err(AssemblerError::MissingOperand(_))
+        ));
+    }
+
+    #[test]
+    fn test_symbol_table() {
+        let source = "SIZE EQU 3\n\
+                      START: LD 0 COUNT\n\
+                      BSC NZ START\n\
+                      WAIT\n\
+                      count: BSS SIZE";
+        let program = Assembler::new().assemble(source).unwrap();
+        assert_eq!(
+            program.symbols,
+            vec![
+                ("COUNT".to_string(), 0x13),
+                ("SIZE".to_string(), 3),
+                ("START".to_string(), 0x10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conditional_assembly() {
+        let source = "DEBUG EQU 1\n\
+                      FAST EQU 0\n\
+                      IF DEBUG\n\
+                      LD 0 0x30\n\
+                      ELSE\n\
+                      LD 0 0x31\n\
+                      ENDIF\n\
+                      IF FAST\n\
+                      SLA 1\n\
+                      SKIPPED: NOP\n\
+                      ELSE\n\
+                      SLA 2\n\
+                      ENDIF\n\
+                      WAIT";
+        let program = Assembler::new().assemble(source).unwrap();
+        assert_eq!(program.code, vec![0x1030, 0x9002, 0xF000]);
+        assert!(!program.symbols.iter().any(|(name, _)| name == "SKIPPED"));
+    }
+
+    #[test]
+    fn test_nested_conditional_assembly() {
+        let source = "ON EQU 1\n\
+                      OFF EQU 0\n\
+                      IF ON\n\
+                      IF OFF\n\
+                      LD 0 1\n\
+                      ELSE\n\
+                      LD 0 2\n\
+                      ENDIF\n\
+                      ENDIF\n\
+                      IF OFF\n\
+                      IF UNDEFINED\n\
+                      LD 0 3\n\
+                      ENDIF\n\
+                      ENDIF";
+        let program = Assembler::new().assemble(source).unwrap();
+        assert_eq!(program.code, vec![0x1002]);
+    }
+
+    #[test]
+    fn test_unbalanced_conditionals() {
+        let mut asm = Assembler::new();
+        for (source, line) in [
+            ("NOP\nENDIF", 2),
+            ("ELSE", 1),
+            ("X EQU 1\nIF X\nELSE\nELSE\nENDIF", 4),
+            ("X EQU 1\nIF X\nNOP", 2),
+        ] {
+            let err = asm.assemble(source).unwrap_err();
+            assert!(
+                matches!(err.error, AssemblerError::SyntaxError(_)),
+                "{source}: {err}"
+            );
+            assert_eq!(err.line, line, "{source}");
+        }
+    }
+
+    #[test]
+    fn test_decode_non_instruction_words_as_data() {
+        // NOP with junk in the low bits
+        assert_eq!(
+            decode_instruction(0x00FF).unwrap(),
+            Instruction::Data { word: 0x00FF }
+        );
+        // BSC with an unassigned condition code
+        assert_eq!(
+            decode_instruction(0xBF00).unwrap(),
+            Instruction::Data { word: 0xBF00 }
+        );
+        assert_eq!(decode_instruction(0x0000).unwrap(), Instruction::NOP);
+
+        for word in [0x00FF, 0xBF00, 0xC8C9] {
+            let instr = decode_instruction(word).unwrap();
+            assert_eq!(encode_instruction(&instr).unwrap(), word);
+        }
+        assert_eq!(
+            disassemble(&Instruction::Data { word: 0x00FF }),
+            "DATA 0x00FF"
+        );
+    }
+
+    #[test]
+    fn test_listing_keeps_comments() {
+        let source = "       LD   0 30      ; Load value   \n\
+                      DONE:  WAIT";
+        let program = Assembler::new().assemble(source).unwrap();
+        assert_eq!(
+            program.listing[0].source,
+            "       LD   0 30      ; Load value"
+        );
+        assert_eq!(program.listing[1].source, "DONE:  WAIT");
+        assert_eq!(program.code, vec![0x101E, 0xF000]);
+    }
+
+    #[test]
+    fn test_ret_encoding() {
+        let asm = Assembler::new();
+        let instr = asm.parse_line("RET 0x40").unwrap();
+        assert_eq!(instr, Instruction::RET { addr: 0x40 });
+        assert_eq!(encode_instruction(&instr).unwrap(), 0xC240);
+        assert_eq!(decode_instruction(0xC240).unwrap(), instr);
+        assert!(matches!(
+            asm.parse_line("RET"),
+            Err(AssemblerError::MissingOperand(_))
+        ));
+    }
+
+    #[test]
+    fn test_ldi_pools_literal_after_program() {
+        let program = Assembler::new()
+            .assemble("LDI -1\nSTO 0 0x30\nLDI 0xFFFF\nWAIT")
+            .unwrap();
+        // Both LDIs name the same value, so they share the pool word at 0x14
+        assert_eq!(program.code, vec![0x1014, 0x2030, 0x1014, 0xF000, 0xFFFF]);
+        assert_eq!(program.placements.last(), Some(&(0x14, 0xFFFF)));
+        assert_eq!(program.listing.last().unwrap().source, "=-1");
+
+        let mut cpu = crate::cpu::CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        cpu.run_until_halt_or_break(10).unwrap();
+        assert_eq!(cpu.read_acc(), 0xFFFF);
+        assert_eq!(cpu.read_word(0x30).unwrap(), 0xFFFF);
+    }
+
+    #[test]
+    fn test_ldi_literal_errors() {
+        let mut asm = Assembler::new();
+        assert!(matches!(
+            asm.assemble("LDI 70000").unwrap_err().error,
+            AssemblerError::InvalidDataValue(_)
+        ));
+        assert!(matches!(
+            asm.assemble("LDI").unwrap_err().error,
+            AssemblerError::MissingOperand(_)
+        ));
+    }
+
+    #[test]
+    fn test_literal_operands_share_pool_words() {
+        let source = "LD 0 =5\n\
+                      A 0 =5\n\
+                      S 0 =-2\n\
+                      AND 0 =0x00FF\n\
+                      LDI 5\n\
+                      WAIT";
+        let program = Assembler::new().assemble(source).unwrap();
+        // Instructions at 0x10-0x15, then one word per distinct value
+        assert_eq!(
+            &program.placements[6..],
+            &[(0x16, 5), (0x17, 0xFFFE), (0x18, 0x00FF)]
+        );
+        assert_eq!(
+            &program.code[..5],
+            &[0x1016, 0x5016, 0x6017, 0x7018, 0x1016]
+        );
+
+        let mut cpu = crate::cpu::CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        cpu.run_until_halt_or_break(10).unwrap();
+        assert_eq!(cpu.read_acc(), 5);
+    }
+
+    #[test]
+    fn test_literal_pool_must_fit_address_field() {
+        // The pool starts at 0x101, past what an operand can address
+        let err = Assembler::new()
+            .assemble("ORG 0xFE\nLD 0 =5\nLD 0 =6\nWAIT")
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.error, AssemblerError::OperandOutOfRange(0x101));
+
+        let program = Assembler::new()
+            .assemble("ORG 0xFD\nLD 0 =5\nWAIT")
+            .unwrap();
+        assert_eq!(program.code[0], 0x10FF);
+        assert_eq!(program.placements[2], (0xFF, 5));
+    }
+
+    #[test]
+    fn test_literal_outside_instruction_is_rejected() {
+        assert!(matches!(
+            Assembler::new().assemble("DATA 0x30 =5").unwrap_err().error,
+            AssemblerError::InvalidDataValue(_)
+        ));
+    }
+
+    #[test]
+    fn test_listing_text_stops_at_wait() {
+        let mut memory = vec![0u16; 0x20];
+        // LD 0 0x30; A 0 0x31; raw word; WAIT; (past the end of the listing) LD 0 0x30
+        memory[0x10..0x15].copy_from_slice(&[0x1030, 0x5031, 0xBF00, 0xF000, 0x1030]);
+
+        assert_eq!(
+            listing_text(&memory, 0x10),
+            "0x0010  0x1030  LD 0 48\n\
+             0x0011  0x5031  A 0 49\n\
+             0x0012  0xBF00  DATA 18 0xBF00\n\
+             0x0013  0xF000  WAIT"
+        );
+        // Without a WAIT the listing runs to the end of memory
+        assert_eq!(listing_text(&memory, 0x1E).lines().count(), 2);
+        assert_eq!(listing_text(&memory, 0x20), "");
+    }
+
+    #[test]
+    fn test_listing_text_reassembles() {
+        let mut memory = vec![0u16; 0x20];
+        memory[0x10..0x14].copy_from_slice(&[0x1030, 0x5031, 0xBF00, 0xF000]);
+
+        // Each line's text, assembled at its address, gives back its word
+        let listing = listing_text(&memory, 0x10);
+        for line in listing.lines() {
+            let columns: Vec<&str> = line.splitn(3, "  ").collect();
+            let address = u16::from_str_radix(&columns[0][2..], 16).unwrap();
+            let word = u16::from_str_radix(&columns[1][2..], 16).unwrap();
+            let program = Assembler::with_origin(address)
+                .assemble(columns[2])
+                .unwrap();
+            assert_eq!(program.placements, vec![(address, word)], "{line}");
+        }
+        assert_eq!(listing.lines().count(), 4);
+    }
+
+    #[test]
+    fn test_validate_reports_every_error() {
+        let source = "LD 0 10\n\
+                      FOO 1 2\n\
+                      A 0 20\n\
+                      BSC XX 10\n\
+                      WAIT";
+        let errors = Assembler::new().validate(source);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].0, 2);
+        assert!(matches!(errors[0].1, AssemblerError::InvalidMnemonic(_)));
+        assert_eq!(errors[1].0, 4);
+        assert!(matches!(errors[1].1, AssemblerError::InvalidCondition(_)));
+
+        // assemble still reports only the first one
+        let error = Assembler::new().assemble(source).unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn test_validate_clean_source() {
+        let source = "LOOP: A 0 ONE\nBSC NZ LOOP\nWAIT\nONE EQU 0x41";
+        assert!(Assembler::new().validate(source).is_empty());
+
+        // A line that fails in both passes is reported once
+        let errors = Assembler::new().validate("BSS 0\nWAIT");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 1);
+    }
+
+    #[test]
+    fn test_data_value_list() {
+        let asm = Assembler::new();
+        assert_eq!(
+            asm.parse_data_directive("DATA 0x30 7 -1 0x10").unwrap(),
+            vec![(0x30, 7), (0x31, 0xFFFF), (0x32, 0x10)]
+        );
+
+        let program = Assembler::new()
+            .assemble("TABLE EQU 0x40\nDATA TABLE 1 2 3\nWAIT")
+            .unwrap();
+        assert_eq!(&program.placements[..3], &[(0x40, 1), (0x41, 2), (0x42, 3)]);
+
+        // The address needs at least one value, and every value must parse
+        assert!(matches!(
+            asm.parse_data_directive("DATA 0x30"),
+            Err(AssemblerError::SyntaxError(_))
+        ));
+        assert_eq!(
+            asm.parse_data_directive("DATA 0x30 1 x!"),
+            Err(AssemblerError::InvalidDataValue("x!".to_string()))
+        );
+        assert!(matches!(
+            asm.parse_data_directive("DATA 0xFFFF 1 2"),
+            Err(AssemblerError::InvalidDataAddress(_))
+        ));
+    }
+
+    #[test]
+    fn test_end_sets_entry_point() {
+        let source = "ORG 0x10\n\
+                      HELPER: LD 0 0x30\n\
+                      WAIT\n\
+                      MAIN: LD 0 0x31\n\
+                      WAIT\n\
+                      DATA 0x30 1 2\n\
+                      END MAIN";
+        let program = Assembler::new().assemble(source).unwrap();
+        assert_eq!(program.start_addr, 0x12);
+
+        let mut cpu = crate::cpu::CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        assert_eq!(cpu.iar(), 0x12);
+        cpu.run_until_halt_or_break(10).unwrap();
+        assert_eq!(cpu.read_acc(), 2);
+        assert_eq!(cpu.instruction_count(), 2);
+
+        // Without an operand END leaves the entry at the origin
+        let program = Assembler::new().assemble("WAIT\nEND").unwrap();
+        assert_eq!(program.start_addr, crate::cpu::PROGRAM_START);
+        assert!(matches!(
+            Assembler::new()
+                .assemble("WAIT\nEND NOWHERE")
+                .unwrap_err()
+                .error,
+            AssemblerError::UndefinedLabel(_)
+        ));
+    }
+
+    #[test]
+    fn test_warns_on_branch_into_data() {
+        let source = "ORG 0x10\n\
+                      LD 0 VALUE\n\
+                      BSC Z VALUE\n\
+                      WAIT\n\
+                      VALUE: BSS 1";
+        let program = Assembler::new().assemble(source).unwrap();
+        assert_eq!(
+            program.warnings,
+            vec![AssemblerWarning::BranchIntoData {
+                line: 3,
+                target: 0x13
+            }]
+        );
+        assert_eq!(
+            program.warnings[0].to_string(),
+            "Line 3: branch target 0x0013 is data"
+        );
+    }
+
+    #[test]
+    fn test_warns_on_fall_through_into_data() {
+        let source = "ORG 0x10\n\
+                      LD 0 VALUE\n\
+                      A 0 VALUE\n\
+                      VALUE: TEXT \"HI\"";
+        let program = Assembler::new().assemble(source).unwrap();
+        assert_eq!(
+            program.warnings,
+            vec![AssemblerWarning::FallsIntoData {
+                line: 3,
+                address: 0x12
+            }]
+        );
+    }
+
+    #[test]
+    fn test_no_warnings_for_subroutine_and_halted_code() {
+        let source = "ORG 0x10\n\
+                      BSI DOUBLE\n\
+                      WAIT\n\
+                      DOUBLE: BSS 1\n\
+                      A 0 VALUE\n\
+                      RET DOUBLE\n\
+                      VALUE: BSS 1\n\
+                      LDI 7\n\
+                      WAIT";
+        let program = Assembler::new().assemble(source).unwrap();
+        assert!(program.warnings.is_empty(), "{:?}", program.warnings);
+    }
+
+    #[test]
+    fn test_xch_encoding() {
+        let instr = Assembler::new().parse_line("XCH").unwrap();
+        assert_eq!(instr, Instruction::XCH);
+        assert_eq!(encode_instruction(&instr).unwrap(), 0xF100);
+        assert_eq!(decode_instruction(0xF100).unwrap(), instr);
+        // Other 0xF words are not XCH
+        assert_eq!(decode_instruction(0xF000).unwrap(), Instruction::WAIT);
+        assert_eq!(
+            decode_instruction(0xF105).unwrap(),
+            Instruction::Data { word: 0xF105 }
+        );
+    }
+
+    #[test]
+    fn test_to_image_orders_words_across_org_gap() {
+        let source = "ORG 0x20\n\
+                      LD 0 0x40\n\
+                      WAIT\n\
+                      ORG 0x10\n\
+                      A 0 0x41\n\
+                      DATA 0x40 7 8";
+        let program = Assembler::new().assemble(source).unwrap();
+        assert_eq!(
+            program.to_image(),
+            vec![
+                (0x10, 0x5041),
+                (0x20, 0x1040),
+                (0x21, 0xF000),
+                (0x40, 7),
+                (0x41, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_placement_is_an_error() {
+        let err = Assembler::new()
+            .assemble("LD 0 0x40\nWAIT\nDATA 0x11 5")
+            .unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.error, AssemblerError::OverlappingPlacement(0x11));
+
+        // A DATA word where the literal pool goes is reported on the literal's line
+        let err = Assembler::new()
+            .assemble("DATA 0x12 5\nLDI 7\nWAIT")
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.error, AssemblerError::OverlappingPlacement(0x12));
+    }
+
+    #[test]
+    fn test_macro_expands_at_invocation() {
+        let source = "MACRO DOUBLE\n\
+                      A 0 0x30\n\
+                      A 0 0x30\n\
+                      ENDM\n\
+                      LD 0 0x30\n\
+                      TWICE: DOUBLE\n\
+                      WAIT";
+        let program = Assembler::new().assemble(source).unwrap();
+        assert_eq!(program.code, vec![0x1030, 0x5030, 0x5030, 0xF000]);
+        assert_eq!(program.listing[1].address, 0x11);
+        assert_eq!(program.listing[1].source, "A 0 0x30");
+        assert!(program.symbols.contains(&("TWICE".to_string(), 0x11)));
+    }
+
+    #[test]
+    fn test_macro_errors_report_invocation_line() {
+        let source = "MACRO BAD\n\
+                      FOO 1 2\n\
+                      ENDM\n\
+                      LD 0 0x30\n\
+                      BAD";
+        let err = Assembler::new().assemble(source).unwrap_err();
+        assert_eq!(err.line, 5);
+        assert_eq!(
+            err.error,
+            AssemblerError::InvalidMnemonic("FOO".to_string())
+        );
+
+        let err = Assembler::new()
+            .assemble("LD 0 0x30\n  MISSING")
+            .unwrap_err();
+        assert_eq!(
+            err.error,
+            AssemblerError::UndefinedMacro("MISSING".to_string())
+        );
+    }
+
+    #[test]
+    fn test_recursive_macro_rejected() {
+        let source = "MACRO PING\n\
+                      PONG\n\
+                      ENDM\n\
+                      MACRO PONG\n\
+                      NOP\n\
+                      PING\n\
+                      ENDM\n\
+                      PING\n\
+                      WAIT";
+        let err = Assembler::new().assemble(source).unwrap_err();
+        assert_eq!(err.line, 8);
+        assert_eq!(
+            err.error,
+            AssemblerError::RecursiveMacro("PING".to_string())
+        );
+
+        let err = Assembler::new()
+            .assemble("MACRO LOOP\nLOOP\nENDM\nLOOP")
+            .unwrap_err();
+        assert_eq!(
+            err.error,
+            AssemblerError::RecursiveMacro("LOOP".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unterminated_macro() {
+        let errors = Assembler::new().validate("NOP\nMACRO OPEN\nNOP");
+        assert_eq!(
+            errors,
+            vec![(
+                2,
+                AssemblerError::SyntaxError("MACRO without matching ENDM".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_shift_count_range() {
+        let asm = Assembler::new();
+        assert_eq!(
+            asm.parse_line("SLA 16").unwrap(),
+            Instruction::SLA { count: 16 }
+        );
+        assert_eq!(
+            asm.parse_line("SRT 31").unwrap(),
+            Instruction::SRT { count: 31 }
+        );
+        for line in ["SLA 100", "SRA 17", "SRL 255", "SLT 32", "SRT 100"] {
+            assert!(
+                matches!(
+                    asm.parse_line(line),
+                    Err(AssemblerError::InvalidShiftCount(_))
+                ),
+                "{line}"
+            );
+        }
+
+        // Over-range counts in memory are data, not shifts
+        assert_eq!(
+            decode_instruction(0x9010).unwrap(),
+            Instruction::SLA { count: 16 }
+        );
+        assert_eq!(
+            decode_instruction(0x9064).unwrap(),
+            Instruction::Data { word: 0x9064 }
+        );
+        assert_eq!(
+            decode_instruction(0xA220).unwrap(),
+            Instruction::Data { word: 0xA220 }
+        );
+    }
+
+    #[test]
+    fn test_clrf_encoding() {
+        let instr = Assembler::new().parse_line("CLRF").unwrap();
+        assert_eq!(instr, Instruction::CLRF);
+        assert_eq!(encode_instruction(&instr).unwrap(), 0xF200);
+        assert_eq!(decode_instruction(0xF200).unwrap(), instr);
+    }
+
+    #[test]
+    fn test_ac_encoding() {
+        let asm = Assembler::new();
+        let instr = asm.parse_line("AC 1 0x30").unwrap();
+        assert_eq!(
+            instr,
+            Instruction::AC {
+                addr: 0x30,
+                mode: AddressingMode::Indexed
+            }
+        );
+        assert_eq!(encode_instruction(&instr).unwrap(), 0x5530);
+        assert_eq!(decode_instruction(0x5530).unwrap(), instr);
+        assert!(matches!(
+            decode_instruction(0x5130).unwrap(),
+            Instruction::A { .. }
+        ));
+    }
+
+    #[test]
+    fn test_sc_encoding() {
+        let asm = Assembler::new();
+        let instr = asm.parse_line("SC I 0x40").unwrap();
+        assert_eq!(encode_instruction(&instr).unwrap(), 0x6640);
+        assert_eq!(decode_instruction(0x6640).unwrap(), instr);
+        assert_eq!(disassemble(&instr), "SC I 64");
+    }
+
+    #[test]
+    fn test_status_word_encoding() {
+        let asm = Assembler::new();
+        for (source, opcode) in [("LDSTAT", 0xF300), ("STSTAT", 0xF400)] {
+            let instr = asm.parse_line(source).unwrap();
+            assert_eq!(encode_instruction(&instr).unwrap(), opcode);
+            assert_eq!(decode_instruction(opcode).unwrap(), instr);
+            assert_eq!(disassemble(&instr), source);
+        }
+    }
+
+    #[test]
+    fn test_printer_listing_columns() {
+        let source = "START: LDSTAT      ; save flags\n\
+                      A 0 0x30\n\
+                      WAIT\n\
+                      END START";
+        let program = Assembler::new().assemble(source).unwrap();
+        let lines = program.to_printer_listing();
+
+        assert_eq!(lines[0], "1LOC   OBJ   SOURCE STATEMENT");
+        assert_eq!(lines[1], "00010  F300  START: LDSTAT      ; save flags");
+        assert_eq!(lines[2], " 0011  5030  A 0 0x30");
+        assert_eq!(lines[3], " 0012  F000  WAIT");
+
+        // Every field starts in the column of its heading, whatever the mnemonic
+        let column_of = |heading: &str| lines[0].find(heading).unwrap();
+        let fields = [
+            ("0010", "F300", "START"),
+            ("0011", "5030", "A 0"),
+            ("0012", "F000", "WAIT"),
+        ];
+        for (line, (loc, obj, text)) in lines[1..4].iter().zip(fields) {
+            assert_eq!(line.find(loc), Some(column_of("LOC")), "{line}");
+            assert_eq!(line.find(obj), Some(column_of("OBJ")), "{line}");
+            assert_eq!(line.find(text), Some(column_of("SOURCE")), "{line}");
+        }
+
+        assert_eq!(lines[4], "1SYMBOL TABLE");
+        assert_eq!(lines[5], "0START    0016 (0x0010)");
+    }
+
+    #[test]
+    fn test_binary_and_octal_numbers() {
+        assert_eq!(parse_number("0b1010"), Ok(10));
+        assert_eq!(parse_number("0B1111000011110000"), Ok(0xF0F0));
+        assert_eq!(parse_number("0o17"), Ok(15));
+        assert_eq!(parse_number("0O177777"), Ok(0xFFFF));
+        assert_eq!(parse_number("0x1F"), Ok(31));
+        assert_eq!(parse_number("42"), Ok(42));
+        for bad in ["0b1012", "0o8", "0b", "0x+1", "0b10000000000000000", "12a"] {
+            assert_eq!(
+                parse_number(bad),
+                Err(AssemblerError::InvalidOperand(bad.to_string()))
+            );
+        }
+
+        // Addresses, DATA values, and shift counts all accept the prefixes
+        let program = Assembler::new()
+            .assemble("LD 0 0b110000\nSLA 0o17\nWAIT\nDATA 0b110000 0b1010")
+            .unwrap();
+        assert_eq!(program.code, vec![0x1030, 0x900F, 0xF000]);
+        assert!(program.placements.contains(&(0x30, 10)));
+
+        let err = Assembler::new().assemble("LD 0 0b1012").unwrap_err();
+        assert_eq!(
+            err.error,
+            AssemblerError::InvalidOperand("0b1012".to_string())
+        );
+    }
+
+    #[test]
+    fn test_align_directive() {
+        let mut asm = Assembler::new();
+        let source = "ORG 0x13\n\
+                      ALIGN 8\n\
+                      TABLE: WAIT\n\
+                      ALIGN 8\n\
+                      NEXT: WAIT";
+        let program = asm.assemble(source).unwrap();
+
+        assert_eq!(asm.symbols["TABLE"], 0x18);
+        // TABLE leaves the counter at 0x19, so the next ALIGN 8 moves it to 0x20
+        assert_eq!(asm.symbols["NEXT"], 0x20);
+        assert_eq!(program.placements, vec![(0x18, 0xF000), (0x20, 0xF000)]);
+
+        // A label on the ALIGN line names the aligned address
+        let mut asm = Assembler::new();
+        asm.assemble("ORG 0x13\nBUF: ALIGN 4\nBSS 2\nWAIT").unwrap();
+        assert_eq!(asm.symbols["BUF"], 0x14);
+
+        let mut asm = Assembler::new();
+        asm.assemble("ORG 0x20\nHERE: ALIGN 16\nWAIT").unwrap();
+        assert_eq!(asm.symbols["HERE"], 0x20);
+    }
+
+    #[test]
+    fn test_align_rejects_bad_boundaries() {
+        let mut asm = Assembler::new();
+
+        for source in ["ALIGN 6", "ALIGN 0", "ALIGN X"] {
+            assert!(matches!(
+                asm.assemble(source).map_err(|e| e.error),
+                Err(AssemblerError::InvalidAlignment(_))
+            ));
+        }
+        assert!(matches!(
+            asm.assemble("ALIGN").map_err(|e| e.error),
+            Err(AssemblerError::SyntaxError(_))
+        ));
+        assert!(matches!(
+            asm.assemble("ORG 0xFFF1\nALIGN 16\nWAIT")
+                .map_err(|e| e.error),
+            Err(AssemblerError::InvalidAlignment(_))
+        ));
+
+        // Above 4K is fine; the loader checks the machine's memory size
+        let mut asm = Assembler::new();
+        asm.assemble("ORG 0x0FF1\nTOP: ALIGN 16\nWAIT").unwrap();
+        assert_eq!(asm.symbols["TOP"], 0x1000);
+    }
+
+    #[test]
+    fn test_code_after_align_near_top_of_address_space() {
+        let mut asm = Assembler::new();
+        let program = asm
+            .assemble("ORG 0xFFE1\nALIGN 16\nHERE: WAIT\nWAIT")
+            .unwrap();
+        assert_eq!(asm.symbols["HERE"], 0xFFF0);
+        assert_eq!(program.placements, vec![(0xFFF0, 0xF000), (0xFFF1, 0xF000)]);
+
+        // The ALIGN itself fits, but the code after it runs off the end
+        let error = Assembler::new()
+            .assemble("ORG 0xFFFD\nALIGN 2\nWAIT\nWAIT\nWAIT")
+            .unwrap_err();
+        assert_eq!(error.line, 4);
+        assert_eq!(error.error, AssemblerError::LocationOverflow(0xFFFF));
+    }
 }
@@ -2,9 +2,13 @@
 //!
 //! Defines puzzles, test cases, and validation logic.
 
-use crate::cpu::CpuState;
+use crate::assembler::AssembledProgram;
+use crate::cpu::{CpuError, CpuState};
 use serde::{Deserialize, Serialize};
 
+/// Instructions a solution may run per test case when the challenge sets no budget
+pub const CHALLENGE_STEP_LIMIT: u64 = 10_000;
+
 /// Difficulty levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Difficulty {
@@ -32,6 +36,26 @@ pub struct TestCase {
 
     /// Expected index register 1 value
     pub expected_xr1: Option<u16>,
+
+    /// Expected index register 2 value
+    #[serde(default)]
+    pub expected_xr2: Option<u16>,
+
+    /// Expected index register 3 value
+    #[serde(default)]
+    pub expected_xr3: Option<u16>,
+
+    /// Expected extension register value
+    #[serde(default)]
+    pub expected_ext: Option<u16>,
+
+    /// Expected carry flag
+    #[serde(default)]
+    pub expected_carry: Option<bool>,
+
+    /// Expected overflow flag
+    #[serde(default)]
+    pub expected_overflow: Option<bool>,
 }
 
 impl TestCase {
@@ -48,6 +72,36 @@ impl TestCase {
             }
         }
 
+        // Check EXT
+        if let Some(expected_ext) = self.expected_ext {
+            let actual = cpu.read_ext();
+            if actual != expected_ext {
+                return Err(format!(
+                    "EXT mismatch: expected 0x{:04X} ({}), got 0x{:04X} ({})",
+                    expected_ext, expected_ext as i16, actual, actual as i16
+                ));
+            }
+        }
+
+        // Check flags
+        if let Some(expected_carry) = self.expected_carry {
+            let actual = cpu.carry();
+            if actual != expected_carry {
+                return Err(format!(
+                    "Carry flag mismatch: expected {expected_carry}, got {actual}"
+                ));
+            }
+        }
+
+        if let Some(expected_overflow) = self.expected_overflow {
+            let actual = cpu.overflow();
+            if actual != expected_overflow {
+                return Err(format!(
+                    "Overflow flag mismatch: expected {expected_overflow}, got {actual}"
+                ));
+            }
+        }
+
         // Check XR1
         if let Some(expected_xr1) = self.expected_xr1 {
             let actual = cpu.read_xr1();
@@ -58,6 +112,26 @@ impl TestCase {
             }
         }
 
+        // Check XR2
+        if let Some(expected_xr2) = self.expected_xr2 {
+            let actual = cpu.read_xr2();
+            if actual != expected_xr2 {
+                return Err(format!(
+                    "XR2 mismatch: expected 0x{expected_xr2:04X} ({expected_xr2}), got 0x{actual:04X} ({actual})"
+                ));
+            }
+        }
+
+        // Check XR3
+        if let Some(expected_xr3) = self.expected_xr3 {
+            let actual = cpu.read_xr3();
+            if actual != expected_xr3 {
+                return Err(format!(
+                    "XR3 mismatch: expected 0x{expected_xr3:04X} ({expected_xr3}), got 0x{actual:04X} ({actual})"
+                ));
+            }
+        }
+
         // Check memory
         for (addr, expected) in &self.expected_memory {
             let actual = cpu
@@ -107,40 +181,123 @@ pub struct Challenge {
     /// Learning objectives
     #[serde(default)]
     pub learning_objectives: Vec<String>,
+
+    /// Program skeleton loaded into the editor (None = generate one from the description)
+    #[serde(default)]
+    pub starter_code: Option<String>,
 }
 
 impl Challenge {
-    /// Run all test cases against a CPU state
-    pub fn validate_solution(&self, cpu: &CpuState) -> Result<ValidationResult, String> {
-        let mut results = Vec::new();
-
-        for test_case in self.test_cases.iter() {
-            // Validate test case
-            match test_case.validate(cpu) {
-                Ok(()) => results.push(TestResult {
-                    test_name: test_case.name.clone(),
-                    passed: true,
-                    error: None,
-                    cycles: cpu.cycle_count(),
-                    instructions: cpu.instruction_count(),
-                }),
-                Err(e) => results.push(TestResult {
-                    test_name: test_case.name.clone(),
-                    passed: false,
-                    error: Some(e),
-                    cycles: cpu.cycle_count(),
-                    instructions: cpu.instruction_count(),
-                }),
+    /// Check the CPU's cycle and instruction counts against this challenge's limits
+    fn check_limits(&self, cpu: &CpuState) -> Result<(), String> {
+        if let Some(max_cycles) = self.max_cycles {
+            let actual = cpu.cycle_count();
+            if actual > max_cycles {
+                return Err(format!("Exceeded cycle budget: {actual} > {max_cycles}"));
             }
         }
 
-        let all_passed = results.iter().all(|r| r.passed);
+        if let Some(max_instructions) = self.max_instructions {
+            let actual = cpu.instruction_count();
+            if actual > max_instructions {
+                return Err(format!(
+                    "Exceeded instruction budget: {actual} > {max_instructions}"
+                ));
+            }
+        }
+
+        Ok(())
+    }
 
-        Ok(ValidationResult {
+    /// Instructions a solution may run per test case before it is stopped
+    ///
+    /// One more than the instruction budget, so a run that exceeds it is
+    /// reported by the budget check rather than cut off silently.
+    pub fn step_limit(&self) -> u64 {
+        self.max_instructions
+            .map_or(CHALLENGE_STEP_LIMIT, |max| max.saturating_add(1))
+    }
+
+    /// Check one test case against a CPU state, including the run budgets
+    pub fn check_test_case(&self, test_case: &TestCase, cpu: &CpuState) -> TestResult {
+        // Budgets first: a run cut off at its step limit fails for that reason
+        let outcome = self
+            .check_limits(cpu)
+            .and_then(|()| test_case.validate(cpu));
+        TestResult {
+            test_name: test_case.name.clone(),
+            passed: outcome.is_ok(),
+            error: outcome.err(),
+            cycles: cpu.cycle_count(),
+            instructions: cpu.instruction_count(),
+        }
+    }
+
+    /// Run `program` against every test case on a fresh 4K-word CPU
+    ///
+    /// A test case fails if the expected values don't match, if the run
+    /// exceeded the challenge's cycle or instruction budget, or if the
+    /// program faulted or never reached WAIT.
+    pub fn validate_solution(&self, program: &AssembledProgram) -> ValidationResult {
+        self.validate_solution_with(program, CpuState::new)
+    }
+
+    /// Like `validate_solution`, with `new_cpu` building each test case's machine
+    ///
+    /// Every test case starts from its own CPU holding only that case's initial
+    /// memory and the program, so cases cannot see each other's results.
+    pub fn validate_solution_with(
+        &self,
+        program: &AssembledProgram,
+        new_cpu: impl Fn() -> CpuState,
+    ) -> ValidationResult {
+        let test_results: Vec<TestResult> = self
+            .test_cases
+            .iter()
+            .map(|test_case| {
+                let mut cpu = new_cpu();
+                let run = self.run_test_case(&mut cpu, test_case, program);
+                let mut result = self.check_test_case(test_case, &cpu);
+                // A passing check still fails if the program faulted or never halted
+                if result.passed {
+                    let error = match run {
+                        Err(e) => Some(format!("Execution error: {e}")),
+                        Ok(()) if !cpu.is_halted() => Some(format!(
+                            "Program did not halt within {} instructions",
+                            self.step_limit()
+                        )),
+                        Ok(()) => None,
+                    };
+                    result.passed = error.is_none();
+                    result.error = error;
+                }
+                result
+            })
+            .collect();
+
+        ValidationResult {
             challenge_id: self.id,
-            passed: all_passed,
-            test_results: results,
-        })
+            passed: test_results.iter().all(|r| r.passed),
+            test_results,
+        }
+    }
+
+    /// Load the program, apply a test case's initial memory, and run it to WAIT
+    ///
+    /// The inputs go in after the program so that a `DATA` word at an input
+    /// address cannot replace the test case's value.
+    fn run_test_case(
+        &self,
+        cpu: &mut CpuState,
+        test_case: &TestCase,
+        program: &AssembledProgram,
+    ) -> Result<(), CpuError> {
+        cpu.load_assembled(program)?;
+        for &(addr, value) in &test_case.initial_memory {
+            cpu.write_word(addr, value)?;
+        }
+        cpu.run_until_halt_or_break(self.step_limit())?;
+        Ok(())
     }
 }
 
@@ -168,9 +325,62 @@ pub fn get_all_challenges() -> Vec<Challenge> {
         challenge_1_load_value(),
         challenge_2_add_numbers(),
         challenge_3_use_index_register(),
+        challenge_4_multiply(),
+        challenge_5_all_index_registers(),
+        challenge_6_double(),
+        challenge_7_larger(),
     ]
 }
 
+/// Check that challenge IDs are unique and every challenge has a test case
+pub fn validate_challenges(challenges: &[Challenge]) -> Result<(), String> {
+    let mut seen = std::collections::HashSet::new();
+    for challenge in challenges {
+        if !seen.insert(challenge.id) {
+            return Err(format!("Duplicate challenge ID: {}", challenge.id));
+        }
+        if challenge.test_cases.is_empty() {
+            return Err(format!(
+                "Challenge {} ({}) has no test cases",
+                challenge.id, challenge.title
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Parse a JSON array of challenges, validating IDs and test cases
+pub fn challenges_from_json(json: &str) -> Result<Vec<Challenge>, String> {
+    let challenges: Vec<Challenge> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid challenge JSON: {e}"))?;
+    validate_challenges(&challenges)?;
+    Ok(challenges)
+}
+
+/// Assemble `source` and run it against every test case of challenge `id`
+///
+/// Each test case runs on its own machine with `memory_words` of memory, so
+/// no existing CPU is touched.
+pub fn run_challenge(
+    challenges: &[Challenge],
+    id: u32,
+    source: &str,
+    memory_words: usize,
+) -> Result<ValidationResult, String> {
+    let challenge = challenges
+        .iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| format!("Challenge {id} not found"))?;
+    CpuState::with_memory_words(memory_words).map_err(|e| e.to_string())?;
+    let program = crate::assembler::Assembler::new()
+        .assemble(source)
+        .map_err(|e| format!("Assembly error: {e}"))?;
+
+    Ok(challenge.validate_solution_with(&program, || {
+        CpuState::with_memory_words(memory_words).expect("memory size was checked above")
+    }))
+}
+
 /// Challenge 1: Load a Value
 fn challenge_1_load_value() -> Challenge {
     Challenge {
@@ -189,6 +399,11 @@ fn challenge_1_load_value() -> Challenge {
             expected_acc: Some(25),
             expected_memory: vec![],
             expected_xr1: None,
+            expected_xr2: None,
+            expected_xr3: None,
+            expected_ext: None,
+            expected_carry: None,
+            expected_overflow: None,
         }],
         max_cycles: Some(100),
         max_instructions: Some(10),
@@ -202,6 +417,18 @@ fn challenge_1_load_value() -> Challenge {
             "Learn about memory addressing".to_string(),
             "Practice using the accumulator".to_string(),
         ],
+        starter_code: Some(
+            r"; Challenge 1: Load a Value
+; The value 25 is already stored at address 0x0010.
+
+       ORG  0x20      ; Keep the program clear of the data
+START: NOP            ; TODO: load the word at 0x0010 into ACC
+       WAIT
+
+; The test harness stores the value at 0x0010 before the run
+       END  START"
+                .to_string(),
+        ),
     }
 }
 
@@ -223,6 +450,11 @@ fn challenge_2_add_numbers() -> Challenge {
             expected_acc: None, // Don't care about final ACC value
             expected_memory: vec![(0x0012, 42)],
             expected_xr1: None,
+            expected_xr2: None,
+            expected_xr3: None,
+            expected_ext: None,
+            expected_carry: None,
+            expected_overflow: None,
         }],
         max_cycles: Some(200),
         max_instructions: Some(20),
@@ -237,6 +469,18 @@ fn challenge_2_add_numbers() -> Challenge {
             "Use the A instruction for addition".to_string(),
             "Store results with STO".to_string(),
         ],
+        starter_code: Some(
+            r"; Challenge 2: Add Two Numbers
+; Store the sum of the words at 0x0010 and 0x0011 at 0x0012.
+
+       ORG  0x20      ; Keep the program clear of the data
+START: NOP            ; TODO: load 0x0010, add 0x0011, store at 0x0012
+       WAIT
+
+; The test harness stores the two numbers at 0x0010-0x0011 before the run
+       END  START"
+                .to_string(),
+        ),
     }
 }
 
@@ -259,6 +503,11 @@ fn challenge_3_use_index_register() -> Challenge {
             expected_acc: Some(100),
             expected_memory: vec![],
             expected_xr1: Some(5),
+            expected_xr2: None,
+            expected_xr3: None,
+            expected_ext: None,
+            expected_carry: None,
+            expected_overflow: None,
         }],
         max_cycles: Some(200),
         max_instructions: Some(15),
@@ -273,6 +522,229 @@ fn challenge_3_use_index_register() -> Challenge {
             "Learn indexed addressing mode".to_string(),
             "Practice multi-step operations".to_string(),
         ],
+        starter_code: Some(
+            r"; Challenge 3: Use Index Register
+; Put 5 in XR1, then load from 0x0010 + XR1 (= 0x0015).
+
+       ORG  0x20      ; Keep the program clear of the data
+START: NOP            ; TODO: set XR1 to 5, then LD 1 0x10
+       WAIT
+
+; The test harness stores the value at 0x0015 before the run
+       END  START"
+                .to_string(),
+        ),
+    }
+}
+
+/// Challenge 4: Multiply Two Numbers
+fn challenge_4_multiply() -> Challenge {
+    Challenge {
+        id: 4,
+        title: "Challenge 4: Multiply Two Numbers".to_string(),
+        description: "Multiply two numbers whose product doesn't fit in 16 bits.\n\n\
+                     - Address 0x0010 contains 300\n\
+                     - Address 0x0011 contains 250\n\
+                     - The product 75000 (0x000124F8) needs 32 bits\n\n\
+                     M leaves the high word of the product in ACC and the low word in EXT."
+            .to_string(),
+        difficulty: Difficulty::Intermediate,
+        test_cases: vec![TestCase {
+            name: "ACC:EXT should contain 75000 (300 * 250)".to_string(),
+            initial_memory: vec![(0x0010, 300), (0x0011, 250)],
+            expected_acc: Some(0x0001),
+            expected_memory: vec![],
+            expected_xr1: None,
+            expected_xr2: None,
+            expected_xr3: None,
+            expected_ext: Some(0x24F8),
+            expected_carry: None,
+            expected_overflow: None,
+        }],
+        max_cycles: Some(100),
+        max_instructions: Some(10),
+        hints: vec![
+            "Load one factor into ACC with LD".to_string(),
+            "M 0 addr multiplies ACC by the word at addr".to_string(),
+            "The result is split: high word in ACC, low word in EXT".to_string(),
+        ],
+        learning_objectives: vec![
+            "Use the M instruction".to_string(),
+            "Understand 32-bit results in ACC:EXT".to_string(),
+        ],
+        starter_code: Some(
+            r"; Challenge 4: Multiply Two Numbers
+; Multiply the words at 0x0010 and 0x0011 into ACC:EXT.
+
+       ORG  0x20      ; Keep the program clear of the data
+START: NOP            ; TODO: load one factor, then M by the other
+       WAIT
+
+; The test harness stores the factors at 0x0010-0x0011 before the run
+       END  START"
+                .to_string(),
+        ),
+    }
+}
+
+/// Challenge 5: Set Up All Index Registers
+fn challenge_5_all_index_registers() -> Challenge {
+    Challenge {
+        id: 5,
+        title: "Challenge 5: Set Up All Index Registers".to_string(),
+        description: "Load all three index registers from a table in memory.\n\n\
+                     - Address 0x0010 contains 10 (for XR1)\n\
+                     - Address 0x0011 contains 20 (for XR2)\n\
+                     - Address 0x0012 contains 30 (for XR3)\n\n\
+                     XR1, XR2, and XR3 live in memory at word addresses 1, 2, and 3."
+            .to_string(),
+        difficulty: Difficulty::Intermediate,
+        test_cases: vec![TestCase {
+            name: "XR1 = 10, XR2 = 20, XR3 = 30".to_string(),
+            initial_memory: vec![(0x0010, 10), (0x0011, 20), (0x0012, 30)],
+            expected_acc: None,
+            expected_memory: vec![],
+            expected_xr1: Some(10),
+            expected_xr2: Some(20),
+            expected_xr3: Some(30),
+            expected_ext: None,
+            expected_carry: None,
+            expected_overflow: None,
+        }],
+        max_cycles: Some(200),
+        max_instructions: Some(20),
+        hints: vec![
+            "LDX only loads XR1".to_string(),
+            "XR2 and XR3 are memory-mapped: STO 0 2 writes XR2".to_string(),
+            "Load each value into ACC, then store it to its register's address".to_string(),
+        ],
+        learning_objectives: vec![
+            "Understand memory-mapped index registers".to_string(),
+            "Initialize several registers from a data table".to_string(),
+        ],
+        starter_code: Some(
+            r"; Challenge 5: Set Up All Index Registers
+; Copy the words at 0x0010-0x0012 into XR1-XR3 (word addresses 1-3).
+
+       ORG  0x20      ; Keep the program clear of the data
+START: NOP            ; TODO: load each value and store it to its register
+       WAIT
+
+; The test harness stores the table at 0x0010-0x0012 before the run
+       END  START"
+                .to_string(),
+        ),
+    }
+}
+
+/// Challenge 6: Double a Number
+fn challenge_6_double() -> Challenge {
+    let case = |input: u16| TestCase {
+        name: format!("Memory[0x0011] should contain {} (2 * {input})", 2 * input),
+        initial_memory: vec![(0x0010, input)],
+        expected_acc: None,
+        expected_memory: vec![(0x0011, 2 * input)],
+        expected_xr1: None,
+        expected_xr2: None,
+        expected_xr3: None,
+        expected_ext: None,
+        expected_carry: None,
+        expected_overflow: None,
+    };
+
+    Challenge {
+        id: 6,
+        title: "Challenge 6: Double a Number".to_string(),
+        description: "Double the number at 0x0010 and store it at 0x0011.\n\n\
+                     Your program is run once per test case, each time with a\n\
+                     different number at 0x0010, so it must work for any input."
+            .to_string(),
+        difficulty: Difficulty::Beginner,
+        test_cases: vec![case(7), case(100), case(0)],
+        max_cycles: Some(100),
+        max_instructions: Some(10),
+        hints: vec![
+            "Adding a number to itself doubles it".to_string(),
+            "A one-bit SLA also doubles ACC".to_string(),
+        ],
+        learning_objectives: vec![
+            "Write a program that works for any input".to_string(),
+            "See how each test case runs separately".to_string(),
+        ],
+        starter_code: Some(
+            r"; Challenge 6: Double a Number
+; Store twice the word at 0x0010 at 0x0011.
+
+       ORG  0x20      ; Keep the program clear of the data
+START: NOP            ; TODO: load 0x0010, double it, store at 0x0011
+       WAIT
+
+; Each test case stores its own number at 0x0010 before the run
+       END  START"
+                .to_string(),
+        ),
+    }
+}
+
+/// Challenge 7: Find the Larger Number
+fn challenge_7_larger() -> Challenge {
+    let case = |a: i16, b: i16| TestCase {
+        name: format!(
+            "Memory[0x0012] should contain {} (larger of {a} and {b})",
+            a.max(b)
+        ),
+        initial_memory: vec![(0x0010, a as u16), (0x0011, b as u16)],
+        expected_acc: None,
+        expected_memory: vec![(0x0012, a.max(b) as u16)],
+        expected_xr1: None,
+        expected_xr2: None,
+        expected_xr3: None,
+        expected_ext: None,
+        expected_carry: None,
+        expected_overflow: None,
+    };
+
+    Challenge {
+        id: 7,
+        title: "Challenge 7: Find the Larger Number".to_string(),
+        description: "Compare the signed numbers at 0x0010 and 0x0011 and store\n\
+                     the larger one at 0x0012.\n\n\
+                     The numbers are two's complement, so -1 (0xFFFF) is smaller\n\
+                     than 1 even though its bit pattern is bigger."
+            .to_string(),
+        difficulty: Difficulty::Intermediate,
+        test_cases: vec![
+            case(5, 9),
+            case(9, 5),
+            case(4, 4),
+            case(-1, 1),
+            case(-5, -3),
+            case(i16::MIN, i16::MAX),
+        ],
+        max_cycles: Some(100),
+        max_instructions: Some(10),
+        hints: vec![
+            "Subtract one number from the other, then branch on the result".to_string(),
+            "BSC LT branches when the subtraction was signed less-than, even if it overflowed"
+                .to_string(),
+            "The carry flag after S compares the numbers as unsigned".to_string(),
+        ],
+        learning_objectives: vec![
+            "Compare signed numbers with a subtract and a branch".to_string(),
+            "See why overflow matters to a signed comparison".to_string(),
+        ],
+        starter_code: Some(
+            r"; Challenge 7: Find the Larger Number
+; Store the larger of the signed words at 0x0010 and 0x0011 at 0x0012.
+
+       ORG  0x20      ; Keep the program clear of the data
+START: NOP            ; TODO: compare 0x0010 with 0x0011, store the larger
+       WAIT
+
+; Each test case stores its own pair at 0x0010-0x0011 before the run
+       END  START"
+                .to_string(),
+        ),
     }
 }
 
@@ -280,110 +752,387 @@ fn challenge_3_use_index_register() -> Challenge {
 mod tests {
     use super::*;
 
-    fn run_program(program: &str) -> Result<CpuState, String> {
+    /// Assemble `program` at 0x20, clear of the challenge data, and validate it
+    fn run_solution(challenge: &Challenge, program: &str) -> ValidationResult {
         use crate::assembler::Assembler;
 
-        let mut cpu = CpuState::new();
-        let mut assembler = Assembler::new();
+        let program = Assembler::with_origin(0x20).assemble(program).unwrap();
+        challenge.validate_solution(&program)
+    }
 
-        let program = assembler
-            .assemble(program)
-            .map_err(|e| format!("Assembly error: {e}"))?;
+    #[test]
+    fn test_challenge_1_valid_solution() {
+        let result = run_solution(&challenge_1_load_value(), "LD 0 0x10\nWAIT");
+        assert!(result.passed, "{:?}", result.test_results[0].error);
+        assert_eq!(result.test_results[0].instructions, 2);
+    }
 
-        cpu.load_program(4, &program.code)
-            .map_err(|e| format!("Load error: {e}"))?;
+    #[test]
+    fn test_challenge_2_valid_solution() {
+        let result = run_solution(
+            &challenge_2_add_numbers(),
+            "LD 0 0x10\nA 0 0x11\nSTO 0 0x12\nWAIT",
+        );
+        assert!(result.passed, "{:?}", result.test_results[0].error);
+    }
 
-        // Execute until WAIT or max iterations
-        let max_iterations = 10000;
-        for _ in 0..max_iterations {
-            if cpu.is_halted() {
-                break;
-            }
+    #[test]
+    fn test_instruction_budget_exceeded() {
+        let challenge = challenge_2_add_numbers();
+        let wasteful = format!(
+            "{}LD 0 0x10\nA 0 0x11\nSTO 0 0x12\nWAIT",
+            "NOP\n".repeat(20)
+        );
 
-            let iar = cpu.iar();
-            let opcode = cpu.read_word(iar).map_err(|e| format!("Read error: {e}"))?;
+        // The run is cut off one instruction past the budget
+        let result = run_solution(&challenge, &wasteful);
+        assert!(!result.passed);
+        assert_eq!(
+            result.test_results[0].error.as_deref(),
+            Some("Exceeded instruction budget: 21 > 20")
+        );
+    }
 
-            let instr = crate::assembler::decode_instruction(opcode)
-                .map_err(|e| format!("Decode error: {e}"))?;
+    #[test]
+    fn test_cycle_budget_exceeded() {
+        let mut challenge = challenge_2_add_numbers();
+        challenge.max_cycles = Some(10);
+        challenge.max_instructions = None;
 
-            cpu.execute(&instr)
-                .map_err(|e| format!("Execute error: {e}"))?;
+        let result = run_solution(&challenge, "LD 0 0x10\nA 0 0x11\nSTO 0 0x12\nWAIT");
+        assert!(result.passed);
 
-            cpu.increment_iar().map_err(|e| format!("IAR error: {e}"))?;
-        }
+        let result = run_solution(
+            &challenge,
+            "NOP\nNOP\nNOP\nNOP\nLD 0 0x10\nA 0 0x11\nSTO 0 0x12\nWAIT",
+        );
+        assert!(!result.passed);
+        assert_eq!(
+            result.test_results[0].error.as_deref(),
+            Some("Exceeded cycle budget: 11 > 10")
+        );
+    }
 
-        Ok(cpu)
+    #[test]
+    fn test_solution_must_halt_cleanly() {
+        let mut challenge = challenge_1_load_value();
+        challenge.max_cycles = None;
+        challenge.max_instructions = None;
+
+        // Right answer in ACC, but the loop never reaches WAIT
+        let result = run_solution(&challenge, "LD 0 0x10\nLOOP: BSC NZ LOOP");
+        assert_eq!(
+            result.test_results[0].error.as_deref(),
+            Some("Program did not halt within 10000 instructions")
+        );
+
+        // Storing over the trap word faults
+        let result = run_solution(&challenge, "LD 0 0x10\nSTO 0 0\nWAIT");
+        assert!(
+            result.test_results[0]
+                .error
+                .as_deref()
+                .unwrap()
+                .starts_with("Execution error")
+        );
     }
 
     #[test]
-    fn test_challenge_1_valid_solution() {
-        let challenge = challenge_1_load_value();
+    fn test_challenge_4_multiply_solution() {
+        let result = run_solution(&challenge_4_multiply(), "LD 0 0x10\nM 0 0x11\nWAIT");
+        assert!(result.passed, "{:?}", result.test_results[0].error);
+    }
 
-        // Set up initial memory as specified in test case
+    #[test]
+    fn test_ext_mismatch() {
+        let challenge = challenge_4_multiply();
+        // ACC holds the right high word, but nothing was multiplied into EXT
         let mut cpu = CpuState::new();
-        for (addr, value) in &challenge.test_cases[0].initial_memory {
-            cpu.write_word(*addr, *value).unwrap();
-        }
+        cpu.write_acc(1);
+
+        let err = challenge.test_cases[0].validate(&cpu).unwrap_err();
+        assert_eq!(err, "EXT mismatch: expected 0x24F8 (9464), got 0x0000 (0)");
+    }
 
-        // Run the solution
-        let program = "LD 0 0x10\nWAIT";
-        let mut cpu = run_program(program).unwrap();
+    #[test]
+    fn test_flag_expectations() {
+        let mut test_case = challenge_1_load_value().test_cases[0].clone();
+        test_case.expected_acc = None;
+        test_case.expected_carry = Some(true);
+        test_case.expected_overflow = Some(false);
 
-        // Apply initial memory again (since run_program creates new CPU)
-        for (addr, value) in &challenge.test_cases[0].initial_memory {
-            cpu.write_word(*addr, *value).unwrap();
-        }
+        let mut cpu = CpuState::new();
+        assert_eq!(
+            test_case.validate(&cpu).unwrap_err(),
+            "Carry flag mismatch: expected true, got false"
+        );
+
+        cpu.set_carry(true);
+        assert!(test_case.validate(&cpu).is_ok());
+
+        cpu.set_overflow(true);
+        assert_eq!(
+            test_case.validate(&cpu).unwrap_err(),
+            "Overflow flag mismatch: expected false, got true"
+        );
+    }
 
-        // Actually run it properly
+    #[test]
+    fn test_challenge_5_index_registers_solution() {
+        let result = run_solution(
+            &challenge_5_all_index_registers(),
+            "LD 0 0x10\nSTO 0 1\nLD 0 0x11\nSTO 0 2\nLD 0 0x12\nSTO 0 3\nWAIT",
+        );
+        assert!(result.passed, "{:?}", result.test_results[0].error);
+    }
+
+    #[test]
+    fn test_each_test_case_runs_on_its_own_memory() {
+        let challenge = challenge_6_double();
+        assert!(challenge.test_cases.len() > 1);
+
+        let result = run_solution(&challenge, "LD 0 0x10\nA 0 0x10\nSTO 0 0x11\nWAIT");
+        assert!(result.passed, "{:?}", result.test_results);
+
+        // Hard-coding the first answer passes only the first case
+        let result = run_solution(&challenge, "LD 0 0x30\nSTO 0 0x11\nWAIT\nDATA 0x30 14");
+        assert!(!result.passed);
+        assert!(result.test_results[0].passed);
+        assert!(!result.test_results[1].passed);
+        assert_eq!(
+            result.test_results[1].error.as_deref(),
+            Some("Memory[0x0011] mismatch: expected 0x00C8 (200), got 0x000E (14)")
+        );
+    }
+
+    /// Replace the TODO line of a challenge's starter code with `body`
+    fn complete_starter(challenge: &Challenge, body: &str) -> crate::assembler::AssembledProgram {
         use crate::assembler::Assembler;
-        let mut assembler = Assembler::new();
-        let prog = assembler.assemble(program).unwrap();
-        cpu.load_program(4, &prog.code).unwrap();
-
-        // Execute
-        for _ in 0..10 {
-            if cpu.is_halted() {
-                break;
-            }
-            let iar = cpu.iar();
-            let opcode = cpu.read_word(iar).unwrap();
-            let instr = crate::assembler::decode_instruction(opcode).unwrap();
-            cpu.execute(&instr).unwrap();
-            cpu.increment_iar().unwrap();
-        }
 
-        let result = challenge.validate_solution(&cpu).unwrap();
-        assert!(result.passed);
+        let starter = challenge.starter_code.as_deref().unwrap();
+        let todo = starter.lines().find(|line| line.contains("TODO")).unwrap();
+        let source = starter.replace(todo, &format!("START: {body}"));
+        Assembler::new().assemble(&source).unwrap()
     }
 
     #[test]
-    fn test_challenge_2_valid_solution() {
-        let challenge = challenge_2_add_numbers();
+    fn test_challenge_6_starter_code_runs_every_case() {
+        let challenge = challenge_6_double();
+        let program = complete_starter(&challenge, "LD 0 0x10\nA 0 0x10\nSTO 0 0x11");
+        let result = challenge.validate_solution(&program);
+        for test in &result.test_results {
+            assert!(test.passed, "{}: {:?}", test.test_name, test.error);
+        }
+    }
 
+    #[test]
+    fn test_inputs_replace_program_data() {
+        // DATA at an input address is overwritten by each test case's value
+        let result = run_solution(
+            &challenge_6_double(),
+            "LD 0 0x10\nA 0 0x10\nSTO 0 0x11\nWAIT\nDATA 0x10 7",
+        );
+        assert!(result.passed, "{:?}", result.test_results);
+    }
+
+    #[test]
+    fn test_index_register_mismatches() {
+        let test_case = challenge_5_all_index_registers().test_cases[0].clone();
         let mut cpu = CpuState::new();
-        for (addr, value) in &challenge.test_cases[0].initial_memory {
-            cpu.write_word(*addr, *value).unwrap();
-        }
 
+        assert_eq!(
+            test_case.validate(&cpu).unwrap_err(),
+            "XR1 mismatch: expected 0x000A (10), got 0x0000 (0)"
+        );
+
+        cpu.write_xr1(10);
+        assert_eq!(
+            test_case.validate(&cpu).unwrap_err(),
+            "XR2 mismatch: expected 0x0014 (20), got 0x0000 (0)"
+        );
+
+        cpu.write_xr2(20);
+        cpu.write_xr3(31);
+        assert_eq!(
+            test_case.validate(&cpu).unwrap_err(),
+            "XR3 mismatch: expected 0x001E (30), got 0x001F (31)"
+        );
+
+        cpu.write_xr3(30);
+        assert!(test_case.validate(&cpu).is_ok());
+    }
+
+    #[test]
+    fn test_challenges_from_json() {
+        let json = r#"[{
+            "id": 100,
+            "title": "Custom: Load 7",
+            "description": "Load 7 into ACC",
+            "difficulty": "Beginner",
+            "test_cases": [{
+                "name": "ACC = 7",
+                "initial_memory": [[16, 7]],
+                "expected_acc": 7,
+                "expected_xr1": null
+            }],
+            "max_cycles": null,
+            "max_instructions": 5
+        }]"#;
+
+        let challenges = challenges_from_json(json).unwrap();
+        assert_eq!(challenges.len(), 1);
+        assert_eq!(challenges[0].id, 100);
+        assert_eq!(challenges[0].test_cases[0].initial_memory, vec![(16, 7)]);
+        assert_eq!(challenges[0].test_cases[0].expected_ext, None);
+        assert!(challenges[0].hints.is_empty());
+        assert_eq!(challenges[0].starter_code, None);
+    }
+
+    #[test]
+    fn test_challenges_from_json_errors() {
+        assert!(
+            challenges_from_json("[{\"id\": 1")
+                .unwrap_err()
+                .starts_with("Invalid challenge JSON")
+        );
+
+        let mut duplicate = get_all_challenges();
+        duplicate.push(challenge_1_load_value());
+        let json = serde_json::to_string(&duplicate).unwrap();
+        assert_eq!(
+            challenges_from_json(&json).unwrap_err(),
+            "Duplicate challenge ID: 1"
+        );
+
+        let mut empty = challenge_2_add_numbers();
+        empty.test_cases.clear();
+        let json = serde_json::to_string(&vec![empty]).unwrap();
+        assert_eq!(
+            challenges_from_json(&json).unwrap_err(),
+            "Challenge 2 (Challenge 2: Add Two Numbers) has no test cases"
+        );
+    }
+
+    #[test]
+    fn test_builtin_starter_code_assembles() {
         use crate::assembler::Assembler;
-        let mut assembler = Assembler::new();
-        let program = "LD 0 0x10\nA 0 0x11\nSTO 0 0x12\nWAIT";
-        let prog = assembler.assemble(program).unwrap();
-        cpu.load_program(4, &prog.code).unwrap();
-
-        // Execute
-        for _ in 0..20 {
-            if cpu.is_halted() {
-                break;
+
+        for challenge in get_all_challenges() {
+            let starter = challenge
+                .starter_code
+                .as_deref()
+                .unwrap_or_else(|| panic!("Challenge {} has no starter code", challenge.id));
+            assert!(!starter.trim().is_empty());
+            let program = Assembler::new()
+                .assemble(starter)
+                .unwrap_or_else(|e| panic!("Challenge {} starter: {e}", challenge.id));
+            assert!(program.warnings.is_empty(), "Challenge {}", challenge.id);
+
+            // Inputs come from the test cases, not from the starter code
+            for test_case in &challenge.test_cases {
+                for &(addr, _) in &test_case.initial_memory {
+                    assert!(
+                        program.placements.iter().all(|&(placed, _)| placed != addr),
+                        "Challenge {} starter places a word at input 0x{addr:04X}",
+                        challenge.id
+                    );
+                }
             }
-            let iar = cpu.iar();
-            let opcode = cpu.read_word(iar).unwrap();
-            let instr = crate::assembler::decode_instruction(opcode).unwrap();
-            cpu.execute(&instr).unwrap();
-            cpu.increment_iar().unwrap();
         }
+    }
+
+    const LARGER_SOLUTION: &str = "LD 0 0x10\n\
+                                   S 0 0x11\n\
+                                   BSC LT SECOND\n\
+                                   LD 0 0x10\n\
+                                   STO 0 0x12\n\
+                                   WAIT\n\
+                                   SECOND: LD 0 0x11\n\
+                                   STO 0 0x12\n\
+                                   WAIT";
 
-        let result = challenge.validate_solution(&cpu).unwrap();
+    #[test]
+    fn test_challenge_7_larger_solution() {
+        let result = run_solution(&challenge_7_larger(), LARGER_SOLUTION);
+        for test in &result.test_results {
+            assert!(test.passed, "{}: {:?}", test.test_name, test.error);
+        }
+        assert_eq!(result.test_results.len(), 6);
+    }
+
+    #[test]
+    fn test_challenge_7_rejects_unsigned_compare() {
+        // Branching on the borrow compares as unsigned, so 0xFFFF looks larger than 1
+        let naive = LARGER_SOLUTION.replace("BSC LT", "BSC C");
+        let result = run_solution(&challenge_7_larger(), &naive);
+        assert!(!result.passed);
+        let failed: Vec<_> = result
+            .test_results
+            .iter()
+            .filter(|test| !test.passed)
+            .map(|test| test.test_name.as_str())
+            .collect();
+        assert!(
+            failed.iter().any(|name| name.contains("-1 and 1")),
+            "{failed:?}"
+        );
+    }
+
+    #[test]
+    fn test_challenge_7_starter_code_runs_every_case() {
+        let challenge = challenge_7_larger();
+        // Store the second number, then replace it if the first is larger
+        let program = complete_starter(
+            &challenge,
+            "LD 0 0x11\n\
+             STO 0 0x12\n\
+             S 0 0x10\n\
+             BSC GE DONE\n\
+             LD 0 0x10\n\
+             STO 0 0x12\n\
+             DONE: NOP",
+        );
+        let result = challenge.validate_solution(&program);
+        for test in &result.test_results {
+            assert!(test.passed, "{}: {:?}", test.test_name, test.error);
+        }
+        assert_eq!(result.test_results.len(), 6);
+    }
+
+    #[test]
+    fn test_run_challenge() {
+        let challenges = get_all_challenges();
+        let memory_words = crate::cpu::MEMORY_SIZE;
+
+        // Challenge 1: load the 25 stored at 0x10
+        let correct = "ORG 0x20\nSTART: LD 0 0x10\nWAIT\nEND START";
+        let result = run_challenge(&challenges, 1, correct, memory_words).unwrap();
         assert!(result.passed);
+        assert_eq!(result.test_results[0].instructions, 2);
+
+        let wrong = "ORG 0x20\nSTART: LD 0 0x11\nWAIT\nEND START";
+        let result = run_challenge(&challenges, 1, wrong, memory_words).unwrap();
+        assert!(!result.passed);
+        assert!(result.test_results[0].error.is_some());
+    }
+
+    #[test]
+    fn test_run_challenge_failures() {
+        let challenges = get_all_challenges();
+        let memory_words = crate::cpu::MEMORY_SIZE;
+
+        assert!(
+            run_challenge(&challenges, 1, "BOGUS 1 2", memory_words)
+                .unwrap_err()
+                .starts_with("Assembly error")
+        );
+        assert!(run_challenge(&challenges, 999, "WAIT", memory_words).is_err());
+        assert!(run_challenge(&challenges, 1, "WAIT", 1000).is_err());
+
+        // Loading 25 and then spinning never reaches WAIT
+        let spin = "ORG 0x20\nSTART: LD 0 0x10\nLOOP: BSC NZ LOOP\nEND START";
+        let result = run_challenge(&challenges, 1, spin, memory_words).unwrap();
+        assert!(!result.passed);
+        assert!(result.test_results[0].error.is_some());
     }
 }
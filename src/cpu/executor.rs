@@ -3,25 +3,182 @@
 //! This module implements the execution logic for IBM 1130 instructions.
 
 use super::instruction::{AddressingMode, BranchCondition, Instruction};
-use super::state::{CpuError, CpuState};
+use super::state::{CpuError, CpuState, STATUS_CARRY, STATUS_HALTED, STATUS_OVERFLOW};
+use serde::Serialize;
+
+/// Why a run loop stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// A WAIT instruction halted the CPU
+    Halted,
+    /// The IAR reached a breakpoint (the instruction there has not executed)
+    Breakpoint(u16),
+    /// The last instruction wrote a watchpoint address
+    Watchpoint(u16),
+    /// The step budget ran out before the CPU halted (possibly an infinite loop)
+    BudgetExhausted,
+}
+
+/// What a single traced step executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StepRecord {
+    /// Mnemonic of the executed instruction
+    pub mnemonic: &'static str,
+    /// Address the instruction was fetched from
+    pub address: u16,
+    /// True if the instruction transferred control (the IAR was not advanced)
+    pub branched: bool,
+    /// True if the CPU is halted after the step
+    pub halted: bool,
+}
+
+/// Result of running a bounded batch of instructions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RunSummary {
+    /// Number of instructions actually executed
+    pub executed: u64,
+    /// True if the CPU is halted at the end of the batch
+    pub halted: bool,
+    /// Watchpoint address whose write ended the batch early
+    pub watchpoint: Option<u16>,
+}
 
 impl CpuState {
+    /// Fetch, decode, and execute the instruction at the IAR, reporting what ran
+    ///
+    /// The IAR advances past the instruction unless a branch was taken, in
+    /// which case it is left at the branch target.
+    pub fn step_traced(&mut self) -> Result<StepRecord, CpuError> {
+        self.record_step(|cpu| {
+            let address = cpu.iar();
+            let opcode = cpu.read_word(address)?;
+            let instr = crate::assembler::decode_instruction(opcode)
+                .map_err(|_| CpuError::InvalidInstruction(address))?;
+
+            let branched = cpu.execute(&instr)?;
+            if !branched {
+                cpu.increment_iar()?;
+            }
+            cpu.record_trace(address, opcode);
+
+            Ok(StepRecord {
+                mnemonic: instr.mnemonic(),
+                address,
+                branched,
+                halted: cpu.is_halted(),
+            })
+        })
+    }
+
+    /// Fetch, decode, and execute the instruction at the IAR, then advance the
+    /// IAR unless a branch was taken
+    pub fn step(&mut self) -> Result<(), CpuError> {
+        self.step_traced().map(|_| ())
+    }
+
+    /// Run until WAIT, a breakpoint, a watchpoint write, or `max_steps` instructions
+    ///
+    /// The breakpoint check is skipped for the first instruction so that a
+    /// run resumed from a breakpoint makes progress. A watchpoint stops the
+    /// run after the instruction that wrote it, with the IAR at the next one.
+    pub fn run_until_halt_or_break(&mut self, max_steps: u64) -> Result<StopReason, CpuError> {
+        for n in 0..max_steps {
+            if self.is_halted() {
+                return Ok(StopReason::Halted);
+            }
+            let iar = self.iar();
+            if n > 0 && self.has_breakpoint(iar) {
+                return Ok(StopReason::Breakpoint(iar));
+            }
+            self.clear_watchpoint_hit();
+            self.step()?;
+            if let Some(addr) = self.watchpoint_hit() {
+                return Ok(StopReason::Watchpoint(addr));
+            }
+        }
+
+        if self.is_halted() {
+            Ok(StopReason::Halted)
+        } else {
+            Ok(StopReason::BudgetExhausted)
+        }
+    }
+
+    /// Execute up to `max_steps` instructions, stopping early on WAIT, a breakpoint or a watchpoint
+    ///
+    /// A CPU that is already halted executes nothing.
+    pub fn run_steps(&mut self, max_steps: u64) -> Result<RunSummary, CpuError> {
+        let before = self.instruction_count();
+        let reason = self.run_until_halt_or_break(max_steps)?;
+        Ok(RunSummary {
+            executed: self.instruction_count() - before,
+            halted: self.is_halted(),
+            watchpoint: match reason {
+                StopReason::Watchpoint(addr) => Some(addr),
+                _ => None,
+            },
+        })
+    }
+
+    /// Step over the instruction at the IAR, running a called subroutine to completion
+    ///
+    /// A `BSI` is run until control comes back to the word after it, using a
+    /// temporary breakpoint that is removed afterwards; any other instruction
+    /// is a single step (a run with a budget of one). `max_steps` bounds a subroutine that never returns.
+    pub fn step_over(&mut self, max_steps: u64) -> Result<StopReason, CpuError> {
+        let iar = self.iar();
+        let opcode = self.read_word(iar)?;
+        let is_call = matches!(
+            crate::assembler::decode_instruction(opcode),
+            Ok(Instruction::BSI { .. })
+        );
+        if !is_call {
+            return self.run_until_halt_or_break(1);
+        }
+
+        let return_addr = iar.wrapping_add(1);
+        let temporary = !self.has_breakpoint(return_addr);
+        if temporary {
+            self.add_breakpoint(return_addr);
+        }
+        let result = self.run_until_halt_or_break(max_steps);
+        if temporary {
+            self.remove_breakpoint(return_addr);
+        }
+        result
+    }
+
     /// Execute a single instruction
-    pub fn execute(&mut self, instr: &Instruction) -> Result<(), CpuError> {
+    ///
+    /// Returns true if the instruction transferred control by setting the
+    /// IAR, in which case the caller must not advance it.
+    pub fn execute(&mut self, instr: &Instruction) -> Result<bool, CpuError> {
         if self.is_halted() {
             return Err(CpuError::Halted);
         }
+        if self.is_protected(self.iar()) {
+            return Err(CpuError::ProtectionFault(self.wrap_address(self.iar())));
+        }
 
+        let fetched_from = self.iar();
+        let mut branched = false;
         match instr {
             // Load/Store
             Instruction::LD { addr, mode } => self.exec_ld(*addr, *mode)?,
             Instruction::STO { addr, mode } => self.exec_sto(*addr, *mode)?,
+            Instruction::LDS { addr, mode } => self.exec_lds(*addr, *mode)?,
+            Instruction::LDD { addr, mode } => self.exec_ldd(*addr, *mode)?,
+            Instruction::STD { addr, mode } => self.exec_std(*addr, *mode)?,
             Instruction::LDX { addr } => self.exec_ldx(*addr)?,
             Instruction::STX { addr } => self.exec_stx(*addr)?,
 
             // Arithmetic
             Instruction::A { addr, mode } => self.exec_add(*addr, *mode)?,
+            Instruction::AC { addr, mode } => self.exec_addc(*addr, *mode)?,
             Instruction::S { addr, mode } => self.exec_sub(*addr, *mode)?,
+            Instruction::SC { addr, mode } => self.exec_subc(*addr, *mode)?,
+            Instruction::M { addr, mode } => self.exec_mul(*addr, *mode)?,
+            Instruction::D { addr, mode } => self.exec_div(*addr, *mode)?,
 
             // Logical
             Instruction::AND { addr, mode } => self.exec_and(*addr, *mode)?,
@@ -30,61 +187,176 @@ impl CpuState {
             // Shift
             Instruction::SLA { count } => self.exec_sla(*count),
             Instruction::SRA { count } => self.exec_sra(*count),
+            Instruction::SRL { count } => self.exec_srl(*count),
+            Instruction::SLT { count } => self.exec_slt(*count),
+            Instruction::SRT { count } => self.exec_srt(*count),
+            Instruction::XCH => self.exec_xch(),
 
             // Branch
-            Instruction::BSC { addr, condition } => self.exec_bsc(*addr, *condition)?,
-            Instruction::BSI { addr } => self.exec_bsi(*addr)?,
+            Instruction::BSC { addr, condition } => branched = self.exec_bsc(*addr, *condition)?,
+            Instruction::BSI { addr } => branched = self.exec_bsi(*addr)?,
+            Instruction::RET { addr } => branched = self.exec_ret(*addr)?,
+            Instruction::SKP { condition } => self.exec_skp(*condition)?,
+            Instruction::BRL { offset } => branched = self.exec_brl(*offset)?,
 
             // Control
             Instruction::WAIT => self.halt(),
+            Instruction::CLRF => self.exec_clrf(),
+            Instruction::LDSTAT => self.exec_ldstat(),
+            Instruction::STSTAT => self.exec_ststat(),
             Instruction::NOP => {} // Do nothing
+
+            // Raw data is not executable
+            Instruction::Data { .. } => return Err(CpuError::InvalidInstruction(self.iar())),
         }
 
         self.count_instruction();
-        self.tick();
-        Ok(())
+        self.count_execution(fetched_from);
+        self.add_cycles(instr.cycle_cost());
+        Ok(branched)
     }
 
     /// Calculate effective address based on addressing mode
-    fn effective_address(&self, addr: u16, mode: AddressingMode) -> u16 {
-        match mode {
+    ///
+    /// Indirect modes perform one extra memory fetch to obtain the operand
+    /// address, so a pointer outside memory is reported as an error.
+    /// In `AddressWrap::Wrap` mode the result is masked to the memory size.
+    fn effective_address(&self, addr: u16, mode: AddressingMode) -> Result<u16, CpuError> {
+        let ea = match mode {
             AddressingMode::Direct => addr,
             AddressingMode::Indexed => addr.wrapping_add(self.read_xr1()),
-        }
+            AddressingMode::DirectIndirect => self.read_word(addr)?,
+            AddressingMode::IndexedIndirect => {
+                self.read_word(addr.wrapping_add(self.read_xr1()))?
+            }
+        };
+        Ok(self.wrap_address(ea))
     }
 
     // ===== Load/Store Instructions =====
 
     fn exec_ld(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
-        let ea = self.effective_address(addr, mode);
+        let ea = self.effective_address(addr, mode)?;
         let value = self.read_word(ea)?;
         self.write_acc(value);
         Ok(())
     }
 
     fn exec_sto(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
-        let ea = self.effective_address(addr, mode);
+        let ea = self.effective_address(addr, mode)?;
         let value = self.read_acc();
-        self.write_word(ea, value)?;
+        self.check_store(ea)?;
+        self.store_word(ea, value)
+    }
+
+    /// Write a data word for STO/STD/STX, noting stores into the loaded code
+    ///
+    /// BSI's return-address store is not a self-modification: the entry word
+    /// of a subroutine is reserved for it.
+    fn store_word(&mut self, addr: u16, value: u16) -> Result<(), CpuError> {
+        self.write_word(addr, value)?;
+        self.record_store(self.wrap_address(addr), value);
+        Ok(())
+    }
+
+    /// Fail with `ProtectionFault` if a program may not store at `addr`
+    fn check_store(&self, addr: u16) -> Result<(), CpuError> {
+        if self.is_protected(addr) {
+            return Err(CpuError::ProtectionFault(self.wrap_address(addr)));
+        }
         Ok(())
     }
 
+    fn exec_lds(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
+        self.exec_ld(addr, mode)?;
+        // EXT becomes ACC's sign bit copied into all sixteen bits
+        let ext = if self.read_acc() & 0x8000 != 0 {
+            0xFFFF
+        } else {
+            0
+        };
+        self.write_ext(ext);
+        Ok(())
+    }
+
+    fn exec_xch(&mut self) {
+        // A register swap: carry and overflow are left alone
+        let acc = self.read_acc();
+        self.write_acc(self.read_ext());
+        self.write_ext(acc);
+    }
+
+    fn exec_clrf(&mut self) {
+        self.set_carry(false);
+        self.set_overflow(false);
+    }
+
+    fn exec_ldstat(&mut self) {
+        let mut status = 0;
+        if self.carry() {
+            status |= STATUS_CARRY;
+        }
+        if self.overflow() {
+            status |= STATUS_OVERFLOW;
+        }
+        if self.is_halted() {
+            status |= STATUS_HALTED;
+        }
+        self.write_acc(status);
+    }
+
+    fn exec_ststat(&mut self) {
+        let status = self.read_acc();
+        self.set_carry(status & STATUS_CARRY != 0);
+        self.set_overflow(status & STATUS_OVERFLOW != 0);
+        // The halted bit can stop the CPU like WAIT, but never restarts it
+        if status & STATUS_HALTED != 0 {
+            self.halt();
+        }
+    }
+
     fn exec_ldx(&mut self, addr: u16) -> Result<(), CpuError> {
         let value = self.read_word(addr)?;
         self.write_xr1(value);
         Ok(())
     }
 
+    /// Effective addresses of a double-word operand, checking that both words are in memory
+    fn double_word_address(&self, addr: u16, mode: AddressingMode) -> Result<(u16, u16), CpuError> {
+        let ea = self.effective_address(addr, mode)?;
+        match ea.checked_add(1).map(|next| self.wrap_address(next)) {
+            Some(next) if (next as usize) < self.memory_words() => Ok((ea, next)),
+            _ => Err(CpuError::MemoryOutOfBounds(ea.wrapping_add(1))),
+        }
+    }
+
+    fn exec_ldd(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
+        let (high, low) = self.double_word_address(addr, mode)?;
+        let acc = self.read_word(high)?;
+        let ext = self.read_word(low)?;
+        self.write_acc(acc);
+        self.write_ext(ext);
+        Ok(())
+    }
+
+    fn exec_std(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
+        let (high, low) = self.double_word_address(addr, mode)?;
+        self.check_store(high)?;
+        self.check_store(low)?;
+        self.store_word(high, self.read_acc())?;
+        self.store_word(low, self.read_ext())
+    }
+
     fn exec_stx(&mut self, addr: u16) -> Result<(), CpuError> {
         let value = self.read_xr1();
-        self.write_word(addr, value)?;
-        Ok(())
+        self.check_store(addr)?;
+        self.store_word(addr, value)
     }
 
     // ===== Arithmetic Instructions =====
 
     fn exec_add(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
-        let ea = self.effective_address(addr, mode);
+        let ea = self.effective_address(addr, mode)?;
         let operand = self.read_word(ea)?;
         let acc = self.read_acc();
         let result = acc.wrapping_add(operand);
@@ -93,8 +365,24 @@ impl CpuState {
         Ok(())
     }
 
+    fn exec_addc(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
+        let ea = self.effective_address(addr, mode)?;
+        let operand = self.read_word(ea)?;
+        let acc = self.read_acc();
+        let carry_in = self.carry() as u32;
+        // Widen so the carry out and signed overflow cover the whole three-term sum
+        let sum = acc as u32 + operand as u32 + carry_in;
+        let result = sum as u16;
+        self.write_acc(result);
+        self.set_carry(sum > 0xFFFF);
+        let signed = acc as i16 as i32 + operand as i16 as i32 + carry_in as i32;
+        // Overflow is sticky, as for A
+        self.note_arithmetic_overflow(signed != result as i16 as i32);
+        Ok(())
+    }
+
     fn exec_sub(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
-        let ea = self.effective_address(addr, mode);
+        let ea = self.effective_address(addr, mode)?;
         let operand = self.read_word(ea)?;
         let acc = self.read_acc();
         let result = acc.wrapping_sub(operand);
@@ -103,42 +391,133 @@ impl CpuState {
         Ok(())
     }
 
+    fn exec_subc(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
+        let ea = self.effective_address(addr, mode)?;
+        let operand = self.read_word(ea)?;
+        let acc = self.read_acc();
+        let borrow_in = self.carry() as i32;
+        let result = acc.wrapping_sub(operand).wrapping_sub(borrow_in as u16);
+        self.write_acc(result);
+        // Carry is the borrow out, as for S
+        self.set_carry((acc as i32) < operand as i32 + borrow_in);
+        let signed = acc as i16 as i32 - operand as i16 as i32 - borrow_in;
+        self.note_arithmetic_overflow(signed != result as i16 as i32);
+        Ok(())
+    }
+
+    fn exec_mul(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
+        let ea = self.effective_address(addr, mode)?;
+        let operand = self.read_word(ea)? as i16 as i32;
+        let acc = self.read_acc() as i16 as i32;
+        // Signed 16 x 16 product always fits in 32 bits
+        let product = (acc * operand) as u32;
+        self.write_acc((product >> 16) as u16);
+        self.write_ext(product as u16);
+        Ok(())
+    }
+
+    fn exec_div(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
+        let ea = self.effective_address(addr, mode)?;
+        let divisor = self.read_word(ea)? as i16 as i32;
+        let dividend = (((self.read_acc() as u32) << 16) | self.read_ext() as u32) as i32;
+
+        // Division by zero or a quotient that doesn't fit in 16 bits sets
+        // overflow and leaves ACC/EXT unchanged
+        let quotient = dividend.checked_div(divisor);
+        match quotient {
+            Some(q) if i16::try_from(q).is_ok() => {
+                let remainder = dividend % divisor;
+                self.write_acc(q as u16);
+                self.write_ext(remainder as u16);
+            }
+            _ => self.set_overflow(true),
+        }
+        Ok(())
+    }
+
     // ===== Logical Instructions =====
+    //
+    // Logical operations cannot overflow, so they clear the overflow flag;
+    // carry is left as it was.
 
     fn exec_and(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
-        let ea = self.effective_address(addr, mode);
+        let ea = self.effective_address(addr, mode)?;
         let operand = self.read_word(ea)?;
         let result = self.read_acc() & operand;
         self.write_acc(result);
+        self.set_overflow(false);
         Ok(())
     }
 
     fn exec_or(&mut self, addr: u16, mode: AddressingMode) -> Result<(), CpuError> {
-        let ea = self.effective_address(addr, mode);
+        let ea = self.effective_address(addr, mode)?;
         let operand = self.read_word(ea)?;
         let result = self.read_acc() | operand;
         self.write_acc(result);
+        self.set_overflow(false);
         Ok(())
     }
 
     // ===== Shift Instructions =====
 
     fn exec_sla(&mut self, count: u8) {
-        let acc = self.read_acc();
-        let result = acc << count;
+        // A zero-count shift leaves ACC and carry untouched
+        if count == 0 {
+            return;
+        }
+        let acc = self.read_acc() as u32;
+        // Widen so counts of 16 or more shift everything out instead of panicking
+        let result = acc.checked_shl(count as u32).unwrap_or(0) as u16;
         self.write_acc(result);
+        // Carry latches bit 15 as it stands at the end of the final shift iteration
+        self.set_carry((result & 0x8000) != 0);
     }
 
     fn exec_sra(&mut self, count: u8) {
         let acc = self.read_acc() as i16; // Arithmetic shift preserves sign
-        let result = (acc >> count) as u16;
+        // Past 15 only copies of the sign bit remain, so clamp rather than overflow
+        let result = (acc >> count.min(15)) as u16;
+        self.write_acc(result);
+    }
+
+    fn exec_srl(&mut self, count: u8) {
+        let acc = self.read_acc(); // Logical shift treats ACC as unsigned
+        let result = acc.checked_shr(count as u32).unwrap_or(0);
         self.write_acc(result);
     }
 
+    /// Read ACC:EXT as a single 32-bit value (ACC is the high word)
+    fn read_double(&self) -> u32 {
+        ((self.read_acc() as u32) << 16) | self.read_ext() as u32
+    }
+
+    /// Write a 32-bit value back to ACC (high word) and EXT (low word)
+    fn write_double(&mut self, value: u32) {
+        self.write_acc((value >> 16) as u16);
+        self.write_ext(value as u16);
+    }
+
+    fn exec_slt(&mut self, count: u8) {
+        // Counts of 32 or more shift everything out, clearing both registers
+        let result = self.read_double().checked_shl(count as u32).unwrap_or(0);
+        self.write_double(result);
+    }
+
+    fn exec_srt(&mut self, count: u8) {
+        let value = self.read_double() as i32; // Sign comes from bit 15 of ACC
+        let result = if count >= 32 {
+            0
+        } else {
+            (value >> count) as u32
+        };
+        self.write_double(result);
+    }
+
     // ===== Branch Instructions =====
 
-    fn exec_bsc(&mut self, addr: u16, condition: BranchCondition) -> Result<(), CpuError> {
-        let should_branch = match condition {
+    /// Evaluate a branch/skip condition against ACC, EXT and the flags
+    fn condition_met(&self, condition: BranchCondition) -> bool {
+        match condition {
             BranchCondition::Zero => self.read_acc() == 0,
             BranchCondition::NonZero => self.read_acc() != 0,
             BranchCondition::Positive => {
@@ -151,21 +530,68 @@ impl CpuState {
             }
             BranchCondition::Overflow => self.overflow(),
             BranchCondition::Carry => self.carry(),
-        };
+            BranchCondition::EvenAcc => self.read_acc() & 1 == 0,
+            BranchCondition::DoubleZero => self.read_double() == 0,
+            // The sign of a subtract result is wrong exactly when that subtract
+            // overflowed; the sticky latch may be left over from an earlier one
+            BranchCondition::LessSigned => {
+                self.condition_met(BranchCondition::Negative) != self.last_overflow()
+            }
+            BranchCondition::GreaterEqualSigned => {
+                self.condition_met(BranchCondition::Negative) == self.last_overflow()
+            }
+            BranchCondition::ExtNegative => (self.read_ext() as i16) < 0,
+            BranchCondition::ExtZero => self.read_ext() == 0,
+        }
+    }
+
+    /// Evaluate a condition for BSC/SKP; testing overflow turns the latch off
+    fn test_condition(&mut self, condition: BranchCondition) -> bool {
+        let met = self.condition_met(condition);
+        if condition == BranchCondition::Overflow {
+            self.set_overflow(false);
+        }
+        met
+    }
+
+    fn exec_bsc(&mut self, addr: u16, condition: BranchCondition) -> Result<bool, CpuError> {
+        if !self.test_condition(condition) {
+            return Ok(false);
+        }
+        self.set_iar(addr)?;
+        Ok(true)
+    }
 
-        if should_branch {
-            self.set_iar(addr)?;
+    fn exec_skp(&mut self, condition: BranchCondition) -> Result<(), CpuError> {
+        // Step over the next word; the normal IAR increment then moves past it
+        if self.test_condition(condition) {
+            self.increment_iar()?;
         }
         Ok(())
     }
 
-    fn exec_bsi(&mut self, addr: u16) -> Result<(), CpuError> {
-        // Store current IAR at target address (for return)
-        let return_addr = self.iar();
+    fn exec_brl(&mut self, offset: i8) -> Result<bool, CpuError> {
+        // The offset is relative to the BRL itself, so `BRL 0` spins in place
+        let target = self.iar().wrapping_add(offset as i16 as u16);
+        self.set_iar(target)?;
+        Ok(true)
+    }
+
+    fn exec_bsi(&mut self, addr: u16) -> Result<bool, CpuError> {
+        // Store the address of the next instruction at the target (for return)
+        let return_addr = self.iar().wrapping_add(1);
+        self.check_store(addr)?;
         self.write_word(addr, return_addr)?;
         // Branch to addr + 1
         self.set_iar(addr.wrapping_add(1))?;
-        Ok(())
+        Ok(true)
+    }
+
+    fn exec_ret(&mut self, addr: u16) -> Result<bool, CpuError> {
+        // Branch through the return address BSI saved at the routine's entry
+        let return_addr = self.read_word(addr)?;
+        self.set_iar(return_addr)?;
+        Ok(true)
     }
 }
 
@@ -173,6 +599,7 @@ impl CpuState {
 mod tests {
     use super::*;
     use crate::cpu::instruction::AddressingMode;
+    use crate::cpu::state::{AddressWrap, HISTORY_DEPTH, MEMORY_SIZE, MemoryProtection};
 
     #[test]
     fn test_ld_direct() {
@@ -204,6 +631,61 @@ mod tests {
         assert_eq!(cpu.read_acc(), 0xABCD);
     }
 
+    #[test]
+    fn test_ld_indirect() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(50, 60).unwrap(); // Pointer
+        cpu.write_word(60, 0xBEEF).unwrap(); // Operand
+
+        let instr = Instruction::LD {
+            addr: 50,
+            mode: AddressingMode::DirectIndirect,
+        };
+        cpu.execute(&instr).unwrap();
+
+        assert_eq!(cpu.read_acc(), 0xBEEF);
+    }
+
+    #[test]
+    fn test_indexed_indirect_pointer_chain() {
+        let mut cpu = CpuState::new();
+        cpu.write_xr1(2);
+        cpu.write_word(52, 70).unwrap(); // Pointer at 50 + XR1
+        cpu.write_word(70, 0x0123).unwrap();
+        cpu.write_acc(0x0456);
+
+        // Store through the pointer, then read back through it
+        let sto = Instruction::STO {
+            addr: 50,
+            mode: AddressingMode::IndexedIndirect,
+        };
+        cpu.execute(&sto).unwrap();
+        assert_eq!(cpu.read_word(70).unwrap(), 0x0456);
+
+        cpu.write_acc(0);
+        let ld = Instruction::LD {
+            addr: 50,
+            mode: AddressingMode::IndexedIndirect,
+        };
+        cpu.execute(&ld).unwrap();
+        assert_eq!(cpu.read_acc(), 0x0456);
+    }
+
+    #[test]
+    fn test_indirect_pointer_out_of_bounds() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(50, 0x2000).unwrap(); // Points past the end of memory
+
+        let instr = Instruction::LD {
+            addr: 50,
+            mode: AddressingMode::DirectIndirect,
+        };
+        assert_eq!(
+            cpu.execute(&instr),
+            Err(CpuError::MemoryOutOfBounds(0x2000))
+        );
+    }
+
     #[test]
     fn test_sto() {
         let mut cpu = CpuState::new();
@@ -264,6 +746,83 @@ mod tests {
         assert_eq!(cpu.read_acc(), 20);
     }
 
+    #[test]
+    fn test_mul() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(7);
+        cpu.write_word(0x50, 6).unwrap();
+
+        let instr = Instruction::M {
+            addr: 0x50,
+            mode: AddressingMode::Direct,
+        };
+        cpu.execute(&instr).unwrap();
+
+        assert_eq!(cpu.read_acc(), 0); // High word
+        assert_eq!(cpu.read_ext(), 42); // Low word
+    }
+
+    #[test]
+    fn test_mul_large_and_negative() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(1000);
+        cpu.write_word(0x50, 1000).unwrap();
+
+        let instr = Instruction::M {
+            addr: 0x50,
+            mode: AddressingMode::Direct,
+        };
+        cpu.execute(&instr).unwrap();
+
+        // 1,000,000 = 0x000F_4240
+        assert_eq!(cpu.read_acc(), 0x000F);
+        assert_eq!(cpu.read_ext(), 0x4240);
+
+        cpu.write_acc(-3i16 as u16);
+        cpu.write_word(0x50, 5).unwrap();
+        cpu.execute(&instr).unwrap();
+
+        // -15 as a 32-bit value
+        assert_eq!(cpu.read_acc(), 0xFFFF);
+        assert_eq!(cpu.read_ext(), -15i16 as u16);
+    }
+
+    #[test]
+    fn test_div() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0);
+        cpu.write_ext(100);
+        cpu.write_word(0x50, 7).unwrap();
+
+        let instr = Instruction::D {
+            addr: 0x50,
+            mode: AddressingMode::Direct,
+        };
+        cpu.execute(&instr).unwrap();
+
+        assert_eq!(cpu.read_acc(), 14); // Quotient
+        assert_eq!(cpu.read_ext(), 2); // Remainder
+        assert!(!cpu.overflow());
+    }
+
+    #[test]
+    fn test_div_by_zero() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x1234);
+        cpu.write_ext(0x5678);
+        cpu.write_word(0x50, 0).unwrap();
+
+        let instr = Instruction::D {
+            addr: 0x50,
+            mode: AddressingMode::Direct,
+        };
+        cpu.execute(&instr).unwrap();
+
+        assert!(cpu.overflow());
+        assert_eq!(cpu.read_acc(), 0x1234);
+        assert_eq!(cpu.read_ext(), 0x5678);
+    }
+
     #[test]
     fn test_and() {
         let mut cpu = CpuState::new();
@@ -305,6 +864,53 @@ mod tests {
         assert_eq!(cpu.read_acc(), 0b0000_1000);
     }
 
+    #[test]
+    fn test_sla_sets_carry() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x4000);
+
+        cpu.execute(&Instruction::SLA { count: 1 }).unwrap();
+
+        assert_eq!(cpu.read_acc(), 0x8000);
+        assert!(cpu.carry());
+    }
+
+    #[test]
+    fn test_sla_clears_carry() {
+        let mut cpu = CpuState::new();
+        cpu.set_carry(true);
+        cpu.write_acc(0x2000);
+
+        cpu.execute(&Instruction::SLA { count: 1 }).unwrap();
+
+        assert_eq!(cpu.read_acc(), 0x4000);
+        assert!(!cpu.carry());
+    }
+
+    #[test]
+    fn test_sla_zero_count_keeps_carry() {
+        let mut cpu = CpuState::new();
+        cpu.set_carry(true);
+        cpu.write_acc(0x1234);
+
+        cpu.execute(&Instruction::SLA { count: 0 }).unwrap();
+
+        assert_eq!(cpu.read_acc(), 0x1234);
+        assert!(cpu.carry());
+    }
+
+    #[test]
+    fn test_sla_count_over_16() {
+        let mut cpu = CpuState::new();
+        cpu.set_carry(true);
+        cpu.write_acc(0xFFFF);
+
+        cpu.execute(&Instruction::SLA { count: 20 }).unwrap();
+
+        assert_eq!(cpu.read_acc(), 0);
+        assert!(!cpu.carry());
+    }
+
     #[test]
     fn test_sra() {
         let mut cpu = CpuState::new();
@@ -317,6 +923,96 @@ mod tests {
         assert_eq!(cpu.read_acc(), 0b1110_0000_0000_0000);
     }
 
+    #[test]
+    fn test_srl() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x8000);
+
+        let instr = Instruction::SRL { count: 1 };
+        cpu.execute(&instr).unwrap();
+
+        // Logical shift zero-fills (SRA would give 0xC000)
+        assert_eq!(cpu.read_acc(), 0x4000);
+    }
+
+    #[test]
+    fn test_srl_vs_sra() {
+        let mut logical = CpuState::new();
+        logical.write_acc(0xF0F0);
+        logical.execute(&Instruction::SRL { count: 4 }).unwrap();
+
+        let mut arithmetic = CpuState::new();
+        arithmetic.write_acc(0xF0F0);
+        arithmetic.execute(&Instruction::SRA { count: 4 }).unwrap();
+
+        assert_eq!(logical.read_acc(), 0x0F0F);
+        assert_eq!(arithmetic.read_acc(), 0xFF0F);
+    }
+
+    #[test]
+    fn test_slt() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x1234);
+        cpu.write_ext(0x5678);
+
+        cpu.execute(&Instruction::SLT { count: 8 }).unwrap();
+
+        assert_eq!(cpu.read_acc(), 0x3456);
+        assert_eq!(cpu.read_ext(), 0x7800);
+    }
+
+    #[test]
+    fn test_srt_preserves_sign() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x8001);
+        cpu.write_ext(0x0002);
+
+        cpu.execute(&Instruction::SRT { count: 4 }).unwrap();
+
+        // 0x8001_0002 >> 4 (arithmetic) = 0xF800_1000
+        assert_eq!(cpu.read_acc(), 0xF800);
+        assert_eq!(cpu.read_ext(), 0x1000);
+    }
+
+    #[test]
+    fn test_double_shift_crosses_word_boundary() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x0000);
+        cpu.write_ext(0x8000);
+
+        cpu.execute(&Instruction::SLT { count: 1 }).unwrap();
+        assert_eq!(cpu.read_acc(), 0x0001);
+        assert_eq!(cpu.read_ext(), 0x0000);
+
+        cpu.execute(&Instruction::SRT { count: 1 }).unwrap();
+        assert_eq!(cpu.read_acc(), 0x0000);
+        assert_eq!(cpu.read_ext(), 0x8000);
+    }
+
+    #[test]
+    fn test_double_shift_edge_counts() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0xABCD);
+        cpu.write_ext(0x1234);
+
+        // Count 0 leaves both halves unchanged
+        cpu.execute(&Instruction::SLT { count: 0 }).unwrap();
+        cpu.execute(&Instruction::SRT { count: 0 }).unwrap();
+        assert_eq!(cpu.read_acc(), 0xABCD);
+        assert_eq!(cpu.read_ext(), 0x1234);
+
+        // Counts of 32 or more clear both registers
+        cpu.execute(&Instruction::SRT { count: 32 }).unwrap();
+        assert_eq!(cpu.read_acc(), 0);
+        assert_eq!(cpu.read_ext(), 0);
+
+        cpu.write_acc(0xABCD);
+        cpu.write_ext(0x1234);
+        cpu.execute(&Instruction::SLT { count: 40 }).unwrap();
+        assert_eq!(cpu.read_acc(), 0);
+        assert_eq!(cpu.read_ext(), 0);
+    }
+
     #[test]
     fn test_bsc_zero() {
         let mut cpu = CpuState::new();
@@ -368,4 +1064,1212 @@ mod tests {
         assert_eq!(cpu.read_acc(), initial_state);
         assert_eq!(cpu.instruction_count(), 1);
     }
+
+    #[test]
+    fn test_step_fetches_and_advances() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(0x30, 7).unwrap();
+        cpu.load_program(0x10, &[0x1030, 0xF000]).unwrap(); // LD 0 0x30; WAIT
+
+        cpu.step().unwrap();
+        assert_eq!(cpu.read_acc(), 7);
+        assert_eq!(cpu.iar(), 0x11);
+
+        cpu.write_word(0x12, 0xBF00).unwrap(); // BSC with unknown condition
+        cpu.set_iar(0x12).unwrap();
+        assert_eq!(cpu.step(), Err(CpuError::InvalidInstruction(0x12)));
+    }
+
+    #[test]
+    fn test_step_lands_on_branch_target() {
+        let mut cpu = CpuState::new();
+        cpu.load_program(0x10, &[0xB040]).unwrap(); // BSC Z 0x40
+
+        cpu.step().unwrap();
+        assert_eq!(cpu.iar(), 0x40);
+    }
+
+    #[test]
+    fn test_bsi_stores_return_address() {
+        let mut cpu = CpuState::new();
+        cpu.load_program(0x10, &[0xC040]).unwrap(); // BSI 0x40
+
+        cpu.step().unwrap();
+        assert_eq!(cpu.read_word(0x40).unwrap(), 0x11);
+        assert_eq!(cpu.iar(), 0x41);
+    }
+
+    #[test]
+    fn test_run_stops_at_breakpoint_in_loop() {
+        use crate::assembler::Assembler;
+
+        let source = "ORG 0x10\n\
+                      LOOP: A 0 ONE\n\
+                      STO 0 0x40\n\
+                      BSC NZ LOOP\n\
+                      WAIT\n\
+                      ONE EQU 0x41\n\
+                      DATA ONE 1";
+        let program = Assembler::new().assemble(source).unwrap();
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        cpu.add_breakpoint(0x11);
+
+        let reason = cpu.run_until_halt_or_break(100).unwrap();
+        assert_eq!(reason, StopReason::Breakpoint(0x11));
+        assert_eq!(cpu.iar(), 0x11);
+        assert_eq!(cpu.read_acc(), 1);
+        assert_eq!(cpu.read_word(0x40).unwrap(), 0);
+
+        // Resuming executes the instruction at the breakpoint
+        cpu.run_until_halt_or_break(1).unwrap();
+        assert_eq!(cpu.read_word(0x40).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_run_until_halt() {
+        let mut cpu = CpuState::new();
+        cpu.load_program(0x10, &[0x0000, 0xF000]).unwrap(); // NOP; WAIT
+
+        assert_eq!(cpu.run_until_halt_or_break(100), Ok(StopReason::Halted));
+        assert_eq!(cpu.instruction_count(), 2);
+
+        let mut cpu = CpuState::new();
+        cpu.load_program(0x10, &[0x0000, 0x0000, 0xF000]).unwrap();
+        assert_eq!(
+            cpu.run_until_halt_or_break(1),
+            Ok(StopReason::BudgetExhausted)
+        );
+    }
+
+    #[test]
+    fn test_cycle_count_accumulates_cost() {
+        let mut cpu = CpuState::new();
+        cpu.write_xr1(1);
+        // LD 0 0x30; A 1 0x30; M 0 0x30; WAIT
+        cpu.load_program(0x10, &[0x1030, 0x5130, 0xD030, 0xF000])
+            .unwrap();
+
+        cpu.run_until_halt_or_break(10).unwrap();
+
+        assert_eq!(cpu.instruction_count(), 4);
+        assert_eq!(cpu.cycle_count(), 2 + 3 + 8 + 1);
+    }
+
+    #[test]
+    fn test_skp_taken_skips_next_instruction() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0);
+        // SKP Z at 0x10
+        cpu.write_word(0x10, 0xC300).unwrap();
+
+        cpu.step().unwrap();
+        assert_eq!(cpu.iar(), 0x12);
+    }
+
+    #[test]
+    fn test_skp_not_taken_falls_through() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(5);
+        // SKP Z at 0x10
+        cpu.write_word(0x10, 0xC300).unwrap();
+
+        cpu.step().unwrap();
+        assert_eq!(cpu.iar(), 0x11);
+    }
+
+    #[test]
+    fn test_std_then_ldd_roundtrip() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x1234);
+        cpu.write_ext(0xABCD);
+        cpu.execute(&Instruction::STD {
+            addr: 0x40,
+            mode: AddressingMode::Direct,
+        })
+        .unwrap();
+
+        assert_eq!(cpu.read_word(0x40).unwrap(), 0x1234);
+        assert_eq!(cpu.read_word(0x41).unwrap(), 0xABCD);
+
+        cpu.write_acc(0);
+        cpu.write_ext(0);
+        cpu.execute(&Instruction::LDD {
+            addr: 0x40,
+            mode: AddressingMode::Direct,
+        })
+        .unwrap();
+
+        assert_eq!(cpu.read_acc(), 0x1234);
+        assert_eq!(cpu.read_ext(), 0xABCD);
+    }
+
+    #[test]
+    fn test_double_word_at_end_of_memory() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x1111);
+        cpu.write_ext(0x2222);
+        cpu.write_xr1(MEMORY_SIZE as u16 - 0x11);
+
+        // ea = 0xFFF is the last word, so ea+1 is out of bounds
+        let std = Instruction::STD {
+            addr: 0x10,
+            mode: AddressingMode::Indexed,
+        };
+        assert_eq!(
+            cpu.execute(&std),
+            Err(CpuError::MemoryOutOfBounds(MEMORY_SIZE as u16))
+        );
+        assert_eq!(cpu.read_word(MEMORY_SIZE as u16 - 1).unwrap(), 0);
+
+        let ldd = Instruction::LDD {
+            addr: 0x10,
+            mode: AddressingMode::Indexed,
+        };
+        assert_eq!(
+            cpu.execute(&ldd),
+            Err(CpuError::MemoryOutOfBounds(MEMORY_SIZE as u16))
+        );
+    }
+
+    #[test]
+    fn test_step_traced_normal_instruction() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(0x30, 7).unwrap();
+        cpu.load_program(0x10, &[0x1030, 0xF000]).unwrap(); // LD 0 0x30; WAIT
+
+        let record = cpu.step_traced().unwrap();
+        assert_eq!(
+            record,
+            StepRecord {
+                mnemonic: "LD",
+                address: 0x10,
+                branched: false,
+                halted: false,
+            }
+        );
+        assert_eq!(cpu.iar(), 0x11);
+
+        let record = cpu.step_traced().unwrap();
+        assert_eq!(record.mnemonic, "WAIT");
+        assert!(record.halted);
+    }
+
+    #[test]
+    fn test_step_traced_taken_branch() {
+        let mut cpu = CpuState::new();
+        cpu.load_program(0x10, &[0xB040, 0xB040]).unwrap(); // BSC Z 0x40 (twice)
+
+        // ACC is zero, so the branch is taken and the IAR stays at the target
+        let record = cpu.step_traced().unwrap();
+        assert!(record.branched);
+        assert_eq!(record.mnemonic, "BSC");
+        assert_eq!(cpu.iar(), 0x40);
+
+        // Not taken: falls through to the next word
+        cpu.write_acc(1);
+        cpu.set_iar(0x11).unwrap();
+        let record = cpu.step_traced().unwrap();
+        assert!(!record.branched);
+        assert_eq!(cpu.iar(), 0x12);
+    }
+
+    #[test]
+    fn test_run_steps_stops_on_wait() {
+        let mut cpu = CpuState::new();
+        cpu.load_program(0x10, &[0x0000, 0xF000, 0x0000]).unwrap(); // NOP; WAIT; NOP
+
+        let summary = cpu.run_steps(10).unwrap();
+        assert_eq!(
+            summary,
+            RunSummary {
+                executed: 2,
+                halted: true,
+                watchpoint: None,
+            }
+        );
+
+        // Already halted: nothing runs
+        assert_eq!(cpu.run_steps(10).unwrap().executed, 0);
+    }
+
+    #[test]
+    fn test_run_steps_limited_by_count() {
+        let mut cpu = CpuState::new();
+        cpu.load_program(0x10, &[0xB010]).unwrap(); // BSC Z 0x10 (loop forever)
+
+        let summary = cpu.run_steps(5).unwrap();
+        assert_eq!(summary.executed, 5);
+        assert!(!summary.halted);
+        assert_eq!(cpu.iar(), 0x10);
+    }
+
+    #[test]
+    fn test_run_self_branch_exhausts_budget() {
+        let mut cpu = CpuState::new();
+        cpu.load_program(0x10, &[0xB010]).unwrap(); // BSC Z 0x10 (branch to self)
+
+        assert_eq!(
+            cpu.run_until_halt_or_break(10000),
+            Ok(StopReason::BudgetExhausted)
+        );
+        assert_eq!(cpu.instruction_count(), 10000);
+        assert!(!cpu.is_halted());
+    }
+
+    #[test]
+    fn test_logical_ops_clear_overflow_keep_carry() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(0x50, 0x0F0F).unwrap();
+
+        for instr in [
+            Instruction::AND {
+                addr: 0x50,
+                mode: AddressingMode::Direct,
+            },
+            Instruction::OR {
+                addr: 0x50,
+                mode: AddressingMode::Direct,
+            },
+        ] {
+            cpu.write_acc(0x00FF);
+            cpu.set_overflow(true);
+            cpu.set_carry(true);
+            cpu.execute(&instr).unwrap();
+
+            assert!(!cpu.overflow(), "{:?} should clear overflow", instr);
+            assert!(cpu.carry(), "{:?} should leave carry", instr);
+        }
+        assert_eq!(cpu.read_acc(), 0x0FFF);
+    }
+
+    #[test]
+    fn test_bsc_even_acc() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(6);
+        cpu.set_iar(0x10).unwrap();
+
+        let instr = Instruction::BSC {
+            addr: 0x50,
+            condition: BranchCondition::EvenAcc,
+        };
+        assert!(cpu.execute(&instr).unwrap());
+        assert_eq!(cpu.iar(), 0x50);
+
+        cpu.write_acc(7);
+        cpu.set_iar(0x10).unwrap();
+        assert!(!cpu.execute(&instr).unwrap());
+        assert_eq!(cpu.iar(), 0x10);
+    }
+
+    #[test]
+    fn test_bsc_double_zero() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0);
+        cpu.write_ext(0);
+        cpu.set_iar(0x10).unwrap();
+
+        let instr = Instruction::BSC {
+            addr: 0x50,
+            condition: BranchCondition::DoubleZero,
+        };
+        assert!(cpu.execute(&instr).unwrap());
+        assert_eq!(cpu.iar(), 0x50);
+
+        // ACC alone being zero is not enough
+        cpu.write_ext(1);
+        cpu.set_iar(0x10).unwrap();
+        assert!(!cpu.execute(&instr).unwrap());
+        assert_eq!(cpu.iar(), 0x10);
+    }
+
+    #[test]
+    fn test_watch_reports_store_during_run() {
+        let mut cpu = CpuState::new();
+        // LD 0x30; STO 0x31; WAIT
+        cpu.load_program(0x10, &[0x1030, 0x2031, 0xF000]).unwrap();
+        cpu.write_word(0x30, 42).unwrap();
+        cpu.set_watch(0x30);
+        cpu.set_watch(0x31);
+
+        cpu.run_until_halt_or_break(10).unwrap();
+
+        assert_eq!(cpu.take_changed_watches(), vec![0x31]);
+    }
+
+    #[test]
+    fn test_step_back_restores_intermediate_states() {
+        let mut cpu = CpuState::new();
+        cpu.set_history_enabled(true);
+        // LD 0x30; STO 0x31; A 0x30; WAIT
+        cpu.load_program(0x10, &[0x1030, 0x2031, 0x5030, 0xF000])
+            .unwrap();
+        cpu.write_word(0x30, 7).unwrap();
+
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.read_acc(), 14);
+        assert_eq!(cpu.read_word(0x31).unwrap(), 7);
+        assert_eq!(cpu.history_len(), 3);
+
+        // Undo A: back to just after STO
+        assert!(cpu.step_back());
+        assert_eq!(cpu.iar(), 0x12);
+        assert_eq!(cpu.read_acc(), 7);
+        assert_eq!(cpu.read_word(0x31).unwrap(), 7);
+        assert_eq!(cpu.instruction_count(), 2);
+
+        // Undo STO: the stored word reverts too
+        assert!(cpu.step_back());
+        assert_eq!(cpu.iar(), 0x11);
+        assert_eq!(cpu.read_acc(), 7);
+        assert_eq!(cpu.read_word(0x31).unwrap(), 0);
+        assert_eq!(cpu.history_len(), 1);
+    }
+
+    #[test]
+    fn test_step_back_history_is_opt_in_and_bounded() {
+        let mut cpu = CpuState::new();
+        cpu.step().unwrap(); // NOP
+        assert!(!cpu.step_back());
+
+        cpu.set_history_enabled(true);
+        cpu.write_word(0x10, 0xB010).unwrap(); // BSC Z 0x10 (spin in place)
+        cpu.set_iar(0x10).unwrap();
+        cpu.run_until_halt_or_break(HISTORY_DEPTH as u64 + 5)
+            .unwrap();
+        assert_eq!(cpu.history_len(), HISTORY_DEPTH);
+
+        cpu.set_history_enabled(false);
+        assert_eq!(cpu.history_len(), 0);
+        assert!(!cpu.step_back());
+    }
+
+    #[test]
+    fn test_overflow_is_sticky_until_tested() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(0x30, 0x7FFF).unwrap();
+        cpu.write_word(0x31, 1).unwrap();
+
+        // 0x7FFF + 1 overflows
+        cpu.write_acc(1);
+        cpu.execute(&Instruction::A {
+            addr: 0x30,
+            mode: AddressingMode::Direct,
+        })
+        .unwrap();
+        assert!(cpu.overflow());
+
+        // A clean add does not clear the latch
+        cpu.write_acc(1);
+        cpu.execute(&Instruction::A {
+            addr: 0x31,
+            mode: AddressingMode::Direct,
+        })
+        .unwrap();
+        assert_eq!(cpu.read_acc(), 2);
+        assert!(cpu.overflow());
+
+        // BSC V branches on the latch and clears it
+        cpu.set_iar(0x10).unwrap();
+        let bsc_v = Instruction::BSC {
+            addr: 0x50,
+            condition: BranchCondition::Overflow,
+        };
+        assert!(cpu.execute(&bsc_v).unwrap());
+        assert!(!cpu.overflow());
+
+        // Testing again falls through
+        cpu.set_iar(0x10).unwrap();
+        assert!(!cpu.execute(&bsc_v).unwrap());
+        assert_eq!(cpu.iar(), 0x10);
+    }
+
+    #[test]
+    fn test_skp_overflow_clears_latch() {
+        let mut cpu = CpuState::new();
+        cpu.set_overflow(true);
+        cpu.set_iar(0x10).unwrap();
+        cpu.execute(&Instruction::SKP {
+            condition: BranchCondition::Overflow,
+        })
+        .unwrap();
+        assert_eq!(cpu.iar(), 0x11);
+        assert!(!cpu.overflow());
+    }
+
+    #[test]
+    fn test_brl_forward_and_backward() {
+        let mut cpu = CpuState::new();
+        cpu.set_iar(0x100).unwrap();
+        assert!(cpu.execute(&Instruction::BRL { offset: 5 }).unwrap());
+        assert_eq!(cpu.iar(), 0x105);
+
+        assert!(cpu.execute(&Instruction::BRL { offset: -3 }).unwrap());
+        assert_eq!(cpu.iar(), 0x102);
+    }
+
+    #[test]
+    fn test_brl_boundary_offsets() {
+        let mut cpu = CpuState::new();
+        cpu.set_iar(0x200).unwrap();
+        cpu.execute(&Instruction::BRL { offset: i8::MAX }).unwrap();
+        assert_eq!(cpu.iar(), 0x27F);
+
+        cpu.set_iar(0x200).unwrap();
+        cpu.execute(&Instruction::BRL { offset: i8::MIN }).unwrap();
+        assert_eq!(cpu.iar(), 0x180);
+
+        // A target below address 0 wraps and falls outside memory
+        cpu.set_iar(0x10).unwrap();
+        assert_eq!(
+            cpu.execute(&Instruction::BRL { offset: -0x11 }),
+            Err(CpuError::IarOutOfBounds(0xFFFF))
+        );
+    }
+
+    #[test]
+    fn test_brl_loop_runs() {
+        let mut cpu = CpuState::new();
+        // 0x10: S 0x30; 0x11: BSC Z 0x13; 0x12: BRL -2; 0x13: WAIT
+        cpu.load_program(0x10, &[0x6030, 0xB013, 0xC1FE, 0xF000])
+            .unwrap();
+        cpu.write_word(0x30, 1).unwrap();
+        cpu.write_acc(3);
+        assert_eq!(
+            cpu.run_until_halt_or_break(100).unwrap(),
+            StopReason::Halted
+        );
+        assert_eq!(cpu.read_acc(), 0);
+    }
+
+    #[test]
+    fn test_step_rejects_data_word() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(0x10, 0x00FF).unwrap(); // not a canonical NOP
+        assert_eq!(cpu.step(), Err(CpuError::InvalidInstruction(0x10)));
+        assert_eq!(cpu.iar(), 0x10);
+        assert_eq!(cpu.instruction_count(), 0);
+    }
+
+    #[test]
+    fn test_indexed_overflow_traps_by_default() {
+        let mut cpu = CpuState::new();
+        cpu.write_xr1(0x20);
+        let ld = Instruction::LD {
+            addr: MEMORY_SIZE as u16 - 0x10,
+            mode: AddressingMode::Indexed,
+        };
+        assert_eq!(
+            cpu.execute(&ld),
+            Err(CpuError::MemoryOutOfBounds(MEMORY_SIZE as u16 + 0x10))
+        );
+    }
+
+    #[test]
+    fn test_indexed_overflow_wraps_in_wrap_mode() {
+        let mut cpu = CpuState::new();
+        cpu.set_address_wrap(AddressWrap::Wrap);
+        cpu.write_xr1(0x20);
+        cpu.write_word(0x10, 0xCAFE).unwrap();
+        let ld = Instruction::LD {
+            addr: MEMORY_SIZE as u16 - 0x10,
+            mode: AddressingMode::Indexed,
+        };
+        cpu.execute(&ld).unwrap();
+        assert_eq!(cpu.read_acc(), 0xCAFE);
+
+        // A double word at the last address continues at address 0, which
+        // only a program without trap-word protection may store into
+        cpu.set_memory_protection(MemoryProtection::Disabled);
+        cpu.write_acc(0x1111);
+        cpu.write_ext(0x2222);
+        cpu.write_xr1(MEMORY_SIZE as u16 - 0x11);
+        let std = Instruction::STD {
+            addr: 0x10,
+            mode: AddressingMode::Indexed,
+        };
+        cpu.execute(&std).unwrap();
+        assert_eq!(cpu.read_word(MEMORY_SIZE as u16 - 1).unwrap(), 0x1111);
+        assert_eq!(cpu.read_word(0).unwrap(), 0x2222);
+    }
+
+    #[test]
+    fn test_bsi_ret_subroutine_call() {
+        use crate::assembler::Assembler;
+
+        let source = "ORG 0x10\n\
+                      BSI DOUBLE\n\
+                      WAIT\n\
+                      ORG 0x40\n\
+                      DOUBLE: BSS 1\n\
+                      A 0 0x50\n\
+                      RET DOUBLE\n\
+                      DATA 0x50 21";
+        let program = Assembler::new().assemble(source).unwrap();
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        cpu.write_acc(21);
+
+        cpu.step().unwrap(); // BSI
+        assert_eq!(cpu.iar(), 0x41);
+        cpu.step().unwrap(); // A
+        cpu.step().unwrap(); // RET
+        assert_eq!(cpu.iar(), 0x11);
+        assert_eq!(cpu.read_acc(), 42);
+
+        cpu.step().unwrap();
+        assert!(cpu.is_halted());
+    }
+
+    #[test]
+    fn test_reset_counters_keeps_registers() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(0x30, 5).unwrap();
+        cpu.write_xr1(3);
+        // LD 0 0x30; A 0 0x30; SLA 1; WAIT
+        cpu.load_program(0x10, &[0x1030, 0x5030, 0x9001, 0xF000])
+            .unwrap();
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.instruction_count(), 2);
+
+        cpu.reset_counters();
+        assert_eq!(cpu.instruction_count(), 0);
+        assert_eq!(cpu.cycle_count(), 0);
+        assert_eq!(cpu.read_acc(), 10);
+        assert_eq!(cpu.iar(), 0x12);
+
+        cpu.step().unwrap();
+        assert_eq!(cpu.instruction_count(), 1);
+        assert_eq!(
+            cpu.cycle_count(),
+            Instruction::SLA { count: 1 }.cycle_cost()
+        );
+        assert_eq!(cpu.read_acc(), 20);
+        assert_eq!(cpu.read_xr1(), 3);
+        assert_eq!(cpu.read_word(0x30).unwrap(), 5);
+    }
+
+    /// Run `S` of `b` from `a`, then report whether `BSC LT` and `BSC GE` branch
+    fn signed_compare(a: i16, b: i16) -> (bool, bool) {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(a as u16);
+        cpu.write_word(0x30, b as u16).unwrap();
+        cpu.execute(&Instruction::S {
+            addr: 0x30,
+            mode: AddressingMode::Direct,
+        })
+        .unwrap();
+
+        let mut branches = |condition| {
+            cpu.set_iar(0x10).unwrap();
+            cpu.execute(&Instruction::BSC {
+                addr: 0x50,
+                condition,
+            })
+            .unwrap()
+        };
+        (
+            branches(BranchCondition::LessSigned),
+            branches(BranchCondition::GreaterEqualSigned),
+        )
+    }
+
+    #[test]
+    fn test_signed_compare_without_overflow() {
+        assert_eq!(signed_compare(5, 7), (true, false));
+        assert_eq!(signed_compare(7, 5), (false, true));
+        assert_eq!(signed_compare(-3, -3), (false, true));
+    }
+
+    #[test]
+    fn test_signed_compare_with_overflow() {
+        // -30000 - 10000 wraps to a positive result but ACC was smaller
+        assert_eq!(signed_compare(-30000, 10000), (true, false));
+        // 30000 - (-10000) wraps negative but ACC was larger
+        assert_eq!(signed_compare(30000, -10000), (false, true));
+        // The plain N condition gets the overflowed case wrong
+        let mut cpu = CpuState::new();
+        cpu.write_acc(-30000i16 as u16);
+        cpu.write_word(0x30, 10000).unwrap();
+        cpu.execute(&Instruction::S {
+            addr: 0x30,
+            mode: AddressingMode::Direct,
+        })
+        .unwrap();
+        assert!(cpu.overflow());
+        assert!(!cpu.condition_met(BranchCondition::Negative));
+    }
+
+    #[test]
+    fn test_signed_compare_ignores_earlier_overflow() {
+        use crate::assembler::Assembler;
+
+        // 0x7FFF + 1 leaves the overflow latch on; 5 - 3 does not overflow
+        let source = "ORG 0x10\n\
+                      LD 0 BIG\n\
+                      A 0 ONE\n\
+                      LD 0 FIVE\n\
+                      S 0 THREE\n\
+                      BSC LT LESS\n\
+                      WAIT\n\
+                      LESS: WAIT\n\
+                      BIG EQU 0x40\n\
+                      ONE EQU 0x41\n\
+                      FIVE EQU 0x42\n\
+                      THREE EQU 0x43\n\
+                      DATA BIG 0x7FFF 1 5 3";
+        let program = Assembler::new().assemble(source).unwrap();
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        cpu.run_until_halt_or_break(20).unwrap();
+
+        assert!(cpu.overflow());
+        assert!(!cpu.last_overflow());
+        // Halted on the fall-through WAIT (0x15), not at LESS (0x16)
+        assert_eq!(cpu.iar(), 0x16, "LT must not branch for 5 >= 3");
+        assert!(cpu.condition_met(BranchCondition::GreaterEqualSigned));
+    }
+
+    #[test]
+    fn test_lds_sign_extends_into_ext() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(0x30, 0x1234).unwrap();
+        cpu.write_word(0x31, 0xFFFE).unwrap();
+        cpu.write_ext(0xAAAA);
+
+        let lds = |addr| Instruction::LDS {
+            addr,
+            mode: AddressingMode::Direct,
+        };
+        cpu.execute(&lds(0x30)).unwrap();
+        assert_eq!(cpu.read_acc(), 0x1234);
+        assert_eq!(cpu.read_ext(), 0x0000);
+
+        cpu.execute(&lds(0x31)).unwrap();
+        assert_eq!(cpu.read_acc(), 0xFFFE);
+        assert_eq!(cpu.read_ext(), 0xFFFF);
+    }
+
+    #[test]
+    fn test_step_over_bsi_runs_subroutine() {
+        use crate::assembler::Assembler;
+
+        let source = "ORG 0x10\n\
+                      BSI DOUBLE\n\
+                      WAIT\n\
+                      ORG 0x40\n\
+                      DOUBLE: BSS 1\n\
+                      A 0 0x50\n\
+                      RET DOUBLE\n\
+                      DATA 0x50 21";
+        let program = Assembler::new().assemble(source).unwrap();
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        cpu.write_acc(21);
+
+        let reason = cpu.step_over(100).unwrap();
+        assert_eq!(reason, StopReason::Breakpoint(0x11));
+        assert_eq!(cpu.iar(), 0x11);
+        assert_eq!(cpu.read_acc(), 42);
+        assert!(!cpu.has_breakpoint(0x11));
+    }
+
+    #[test]
+    fn test_step_over_plain_instruction_is_single_step() {
+        let mut cpu = CpuState::new();
+        cpu.set_iar(0x10).unwrap();
+        cpu.write_word(0x10, 0x0000).unwrap(); // NOP
+        cpu.write_word(0x11, 0xF000).unwrap(); // WAIT
+
+        cpu.step_over(100).unwrap();
+        assert_eq!(cpu.iar(), 0x11);
+        assert_eq!(cpu.step_over(100).unwrap(), StopReason::Halted);
+    }
+
+    #[test]
+    fn test_step_over_keeps_user_breakpoint_and_respects_budget() {
+        let mut cpu = CpuState::new();
+        cpu.set_iar(0x10).unwrap();
+        cpu.write_word(0x10, 0xC040).unwrap(); // BSI 0x40
+        cpu.write_word(0x41, 0xB041).unwrap(); // BSC Z 0x41: spins forever (ACC = 0)
+        cpu.add_breakpoint(0x11);
+
+        let reason = cpu.step_over(50).unwrap();
+        assert_eq!(reason, StopReason::BudgetExhausted);
+        assert!(cpu.has_breakpoint(0x11));
+    }
+
+    #[test]
+    fn test_xch_swaps_acc_and_ext() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x1234);
+        cpu.write_ext(0xABCD);
+        cpu.set_carry(true);
+
+        cpu.execute(&Instruction::XCH).unwrap();
+        assert_eq!(cpu.read_acc(), 0xABCD);
+        assert_eq!(cpu.read_ext(), 0x1234);
+        assert_eq!(cpu.instruction_count(), 1);
+        assert!(cpu.carry());
+        assert!(!cpu.overflow());
+    }
+
+    #[test]
+    fn test_branch_to_zero_faults_when_protected() {
+        let mut cpu = CpuState::new();
+        cpu.hard_reset();
+        cpu.set_iar(0x10).unwrap();
+        cpu.write_word(0x10, 0xB200).unwrap(); // BSC P 0 (ACC is zero, so not taken)
+        cpu.write_word(0x11, 0xB700).unwrap(); // BSC DZ 0 (taken)
+
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.iar(), 0);
+        assert_eq!(cpu.step(), Err(CpuError::ProtectionFault(0)));
+        assert_eq!(cpu.instruction_count(), 2);
+    }
+
+    #[test]
+    fn test_store_to_zero_faults_when_protected() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x1234);
+        let sto = Instruction::STO {
+            addr: 0,
+            mode: AddressingMode::Direct,
+        };
+        assert_eq!(cpu.execute(&sto), Err(CpuError::ProtectionFault(0)));
+        assert_eq!(cpu.read_word(0).unwrap(), 0);
+
+        // Index registers are only protected on request
+        cpu.execute(&Instruction::STX { addr: 1 }).unwrap();
+        cpu.set_memory_protection(MemoryProtection::ReservedWords);
+        assert_eq!(
+            cpu.execute(&Instruction::STX { addr: 1 }),
+            Err(CpuError::ProtectionFault(1))
+        );
+    }
+
+    #[test]
+    fn test_branch_to_zero_spins_when_protection_disabled() {
+        let mut cpu = CpuState::new();
+        cpu.hard_reset();
+        cpu.set_memory_protection(MemoryProtection::Disabled);
+        cpu.write_word(0x10, 0xB700).unwrap(); // BSC DZ 0
+
+        let reason = cpu.run_until_halt_or_break(20).unwrap();
+        assert_eq!(reason, StopReason::BudgetExhausted);
+        assert_eq!(cpu.iar(), 0);
+
+        cpu.write_acc(0x5555);
+        let sto = Instruction::STO {
+            addr: 0,
+            mode: AddressingMode::Direct,
+        };
+        cpu.execute(&sto).unwrap();
+        assert_eq!(cpu.read_word(0).unwrap(), 0x5555);
+    }
+
+    #[test]
+    fn test_single_shift_edge_counts() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x1234);
+        cpu.execute(&Instruction::SLA { count: 16 }).unwrap();
+        assert_eq!(cpu.read_acc(), 0);
+
+        cpu.write_acc(0x8421);
+        cpu.execute(&Instruction::SRA { count: 0 }).unwrap();
+        cpu.execute(&Instruction::SRL { count: 0 }).unwrap();
+        assert_eq!(cpu.read_acc(), 0x8421);
+
+        // Over-range counts from hand-built instructions must not panic
+        cpu.execute(&Instruction::SRA { count: 200 }).unwrap();
+        assert_eq!(cpu.read_acc(), 0xFFFF);
+        cpu.execute(&Instruction::SRL { count: 16 }).unwrap();
+        assert_eq!(cpu.read_acc(), 0);
+    }
+
+    #[test]
+    fn test_trace_records_executed_instructions() {
+        let mut cpu = CpuState::new();
+        cpu.load_program(0x10, &[0x1030, 0x5031, 0xF000]).unwrap(); // LD; A; WAIT
+        cpu.write_word(0x30, 5).unwrap();
+        cpu.write_word(0x31, 7).unwrap();
+        cpu.set_iar(0x10).unwrap();
+        cpu.set_tracing(true);
+
+        cpu.run_until_halt_or_break(10).unwrap();
+        let trace = cpu.take_trace();
+        let entries: Vec<(u16, u16, u16)> = trace
+            .iter()
+            .map(|entry| (entry.iar, entry.opcode, entry.acc))
+            .collect();
+        assert_eq!(
+            entries,
+            [(0x10, 0x1030, 5), (0x11, 0x5031, 12), (0x12, 0xF000, 12)]
+        );
+    }
+
+    #[test]
+    fn test_clrf_clears_flags_only() {
+        let mut cpu = CpuState::new();
+        assert!(!cpu.carry() && !cpu.overflow());
+        cpu.write_acc(0x1234);
+        cpu.write_ext(0x5678);
+        cpu.set_carry(true);
+        cpu.set_overflow(true);
+
+        cpu.execute(&Instruction::CLRF).unwrap();
+        assert!(!cpu.carry());
+        assert!(!cpu.overflow());
+        assert_eq!(cpu.read_acc(), 0x1234);
+        assert_eq!(cpu.read_ext(), 0x5678);
+
+        cpu.set_carry(true);
+        cpu.set_overflow(true);
+        cpu.reset();
+        assert!(!cpu.carry() && !cpu.overflow());
+    }
+
+    #[test]
+    fn test_ac_chains_a_32_bit_sum() {
+        use crate::assembler::Assembler;
+
+        // 0x0001FFFF + 0x00020001 = 0x00040000: the low words carry into the high words
+        let source = "ORG 0x10\n\
+                      CLRF\n\
+                      LD 0 0x31\n\
+                      AC 0 0x33\n\
+                      STO 0 0x35\n\
+                      LD 0 0x30\n\
+                      AC 0 0x32\n\
+                      STO 0 0x34\n\
+                      WAIT\n\
+                      DATA 0x30 0x0001 0xFFFF 0x0002 0x0001";
+        let program = Assembler::new().assemble(source).unwrap();
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        cpu.set_carry(true); // CLRF must clear a stale carry
+        cpu.run_until_halt_or_break(20).unwrap();
+
+        assert_eq!(cpu.read_word(0x34).unwrap(), 0x0004);
+        assert_eq!(cpu.read_word(0x35).unwrap(), 0x0000);
+        assert!(!cpu.carry());
+        assert!(!cpu.overflow());
+    }
+
+    #[test]
+    fn test_ac_flags() {
+        let mut cpu = CpuState::new();
+        let ac = Instruction::AC {
+            addr: 0x30,
+            mode: AddressingMode::Direct,
+        };
+
+        // Without a carry in, AC is an ordinary add
+        cpu.write_acc(0x1000);
+        cpu.write_word(0x30, 0x0234).unwrap();
+        cpu.execute(&ac).unwrap();
+        assert_eq!(cpu.read_acc(), 0x1234);
+        assert!(!cpu.carry());
+
+        // The carry in alone can carry out: 0xFFFF + 0 + 1
+        cpu.write_acc(0xFFFF);
+        cpu.write_word(0x30, 0).unwrap();
+        cpu.set_carry(true);
+        cpu.execute(&ac).unwrap();
+        assert_eq!(cpu.read_acc(), 0);
+        assert!(cpu.carry());
+        assert!(!cpu.overflow());
+
+        // ...or overflow: 0x7FFF + 0 + 1
+        cpu.write_acc(0x7FFF);
+        cpu.execute(&ac).unwrap();
+        assert_eq!(cpu.read_acc(), 0x8000);
+        assert!(!cpu.carry());
+        assert!(cpu.overflow());
+    }
+
+    #[test]
+    fn test_sc_chains_a_32_bit_difference() {
+        use crate::assembler::Assembler;
+
+        // High word at 0x30/0x32, low word at 0x31/0x33, difference to 0x34/0x35
+        let program_for = |minuend: u32, subtrahend: u32| {
+            let source = format!(
+                "ORG 0x10\n\
+                 CLRF\n\
+                 LD 0 0x31\n\
+                 SC 0 0x33\n\
+                 STO 0 0x35\n\
+                 LD 0 0x30\n\
+                 SC 0 0x32\n\
+                 STO 0 0x34\n\
+                 WAIT\n\
+                 DATA 0x30 {} {} {} {}",
+                minuend >> 16,
+                minuend & 0xFFFF,
+                subtrahend >> 16,
+                subtrahend & 0xFFFF
+            );
+            Assembler::new().assemble(&source).unwrap()
+        };
+
+        // 0x00040000 - 0x00000001: the low word borrows from the high word
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program_for(0x0004_0000, 0x0000_0001))
+            .unwrap();
+        cpu.run_until_halt_or_break(20).unwrap();
+        assert_eq!(cpu.read_word(0x34).unwrap(), 0x0003);
+        assert_eq!(cpu.read_word(0x35).unwrap(), 0xFFFF);
+        assert!(!cpu.carry());
+
+        // 0x00051234 - 0x00021000: no borrow between the words
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program_for(0x0005_1234, 0x0002_1000))
+            .unwrap();
+        cpu.run_until_halt_or_break(20).unwrap();
+        assert_eq!(cpu.read_word(0x34).unwrap(), 0x0003);
+        assert_eq!(cpu.read_word(0x35).unwrap(), 0x0234);
+        assert!(!cpu.carry());
+        assert!(!cpu.overflow());
+    }
+
+    #[test]
+    fn test_sc_flags() {
+        let mut cpu = CpuState::new();
+        let sc = Instruction::SC {
+            addr: 0x30,
+            mode: AddressingMode::Direct,
+        };
+
+        // The borrow in alone can borrow out: 0 - 0 - 1
+        cpu.write_acc(0);
+        cpu.write_word(0x30, 0).unwrap();
+        cpu.set_carry(true);
+        cpu.execute(&sc).unwrap();
+        assert_eq!(cpu.read_acc(), 0xFFFF);
+        assert!(cpu.carry());
+        assert!(!cpu.overflow());
+
+        // ...or overflow: 0x8000 - 0 - 1
+        cpu.write_acc(0x8000);
+        cpu.set_carry(true);
+        cpu.execute(&sc).unwrap();
+        assert_eq!(cpu.read_acc(), 0x7FFF);
+        assert!(!cpu.carry());
+        assert!(cpu.overflow());
+    }
+
+    #[test]
+    fn test_ldstat_ststat_save_and_restore_flags() {
+        let mut cpu = CpuState::new();
+        cpu.set_carry(true);
+        cpu.set_overflow(true);
+
+        cpu.execute(&Instruction::LDSTAT).unwrap();
+        assert_eq!(cpu.read_acc(), STATUS_CARRY | STATUS_OVERFLOW);
+
+        cpu.execute(&Instruction::CLRF).unwrap();
+        assert!(!cpu.carry() && !cpu.overflow());
+
+        cpu.execute(&Instruction::STSTAT).unwrap();
+        assert!(cpu.carry());
+        assert!(cpu.overflow());
+        assert!(!cpu.is_halted());
+
+        // Bits are independent: carry alone
+        cpu.write_acc(STATUS_CARRY);
+        cpu.execute(&Instruction::STSTAT).unwrap();
+        assert!(cpu.carry());
+        assert!(!cpu.overflow());
+        cpu.execute(&Instruction::LDSTAT).unwrap();
+        assert_eq!(cpu.read_acc(), STATUS_CARRY);
+    }
+
+    #[test]
+    fn test_ststat_halted_bit_halts() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(STATUS_HALTED | 0xFFF8);
+        cpu.execute(&Instruction::STSTAT).unwrap();
+        assert!(cpu.is_halted());
+        assert!(!cpu.carry() && !cpu.overflow());
+    }
+
+    #[test]
+    fn test_execution_counts_show_loop_body() {
+        use crate::assembler::Assembler;
+
+        // Count 3 down to 0: two setup words, a four-word body run three times, WAIT
+        let source = "ORG 0x10\n\
+                      LD 0 0x30\n\
+                      STO 0 0x32\n\
+                      LOOP: LD 0 0x32\n\
+                      S 0 0x31\n\
+                      STO 0 0x32\n\
+                      BSC NZ LOOP\n\
+                      WAIT\n\
+                      DATA 0x30 3 1";
+        let program = Assembler::new().assemble(source).unwrap();
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        cpu.run_until_halt_or_break(100).unwrap();
+        assert!(cpu.is_halted());
+
+        let counts: Vec<(u16, u32)> = cpu
+            .execution_counts()
+            .iter()
+            .map(|c| (c.address, c.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                (0x10, 1),
+                (0x11, 1),
+                (0x12, 3),
+                (0x13, 3),
+                (0x14, 3),
+                (0x15, 3),
+                (0x16, 1),
+            ]
+        );
+
+        cpu.reset_counters();
+        assert!(cpu.execution_counts().is_empty());
+    }
+
+    #[test]
+    fn test_ext_conditions_ignore_acc() {
+        let branches = |acc: u16, ext: u16, condition| {
+            let mut cpu = CpuState::new();
+            cpu.write_acc(acc);
+            cpu.write_ext(ext);
+            cpu.execute(&Instruction::BSC {
+                addr: 0x50,
+                condition,
+            })
+            .unwrap()
+        };
+
+        for acc in [0x0000, 0x0001, 0x8000, 0xFFFF] {
+            assert!(branches(acc, 0x8000, BranchCondition::ExtNegative));
+            assert!(branches(acc, 0xFFFF, BranchCondition::ExtNegative));
+            assert!(!branches(acc, 0x0000, BranchCondition::ExtNegative));
+            assert!(!branches(acc, 0x7FFF, BranchCondition::ExtNegative));
+
+            assert!(branches(acc, 0x0000, BranchCondition::ExtZero));
+            assert!(!branches(acc, 0x0001, BranchCondition::ExtZero));
+            assert!(!branches(acc, 0x8000, BranchCondition::ExtZero));
+        }
+    }
+
+    #[test]
+    fn test_ext_negative_after_double_shift() {
+        // SRT moves ACC's low bit into EXT's sign bit
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x0001);
+        cpu.write_ext(0x4000);
+        cpu.execute(&Instruction::SRT { count: 1 }).unwrap();
+        assert_eq!(cpu.read_acc(), 0);
+        assert_eq!(cpu.read_ext(), 0xA000);
+
+        let iar = cpu.iar();
+        cpu.execute(&Instruction::SKP {
+            condition: BranchCondition::ExtNegative,
+        })
+        .unwrap();
+        assert_eq!(cpu.iar(), iar + 1);
+    }
+
+    #[test]
+    fn test_self_modifying_store_is_recorded() {
+        use crate::assembler::Assembler;
+
+        // Patch the NOP at PATCH into `A 0 0x31` before reaching it
+        let source = "ORG 0x10\n\
+                      LD 0 0x30\n\
+                      STO 0 PATCH\n\
+                      LD 0 0x31\n\
+                      STO 0 0x32\n\
+                      PATCH: NOP\n\
+                      WAIT\n\
+                      DATA 0x30 0x5031 4";
+        let program = Assembler::new().assemble(source).unwrap();
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        assert_eq!(cpu.code_region(), Some((0x10, 0x15)));
+
+        cpu.run_until_halt_or_break(20).unwrap();
+        assert_eq!(cpu.read_acc(), 8);
+        // The data store to 0x32 is outside the code and not reported
+        assert_eq!(
+            cpu.self_modifications(),
+            &[crate::cpu::SelfModification {
+                address: 0x14,
+                iar: 0x11,
+                value: 0x5031,
+            }]
+        );
+
+        cpu.hard_reset();
+        assert!(cpu.self_modifications().is_empty());
+        assert_eq!(cpu.code_region(), None);
+    }
+
+    #[test]
+    fn test_watchpoint_stops_run_after_store() {
+        use crate::assembler::Assembler;
+
+        let source = "ORG 0x10\n\
+                      LD 0 SEVEN\n\
+                      A 0 SEVEN\n\
+                      STO 0 RESULT\n\
+                      NOP\n\
+                      WAIT\n\
+                      SEVEN EQU 0x40\n\
+                      RESULT EQU 0x41\n\
+                      DATA SEVEN 7";
+        let program = Assembler::new().assemble(source).unwrap();
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        cpu.add_watchpoint(0x41);
+
+        // Reads of other cells and the loader's writes do not trigger it
+        let reason = cpu.run_until_halt_or_break(100).unwrap();
+        assert_eq!(reason, StopReason::Watchpoint(0x41));
+        assert_eq!(cpu.instruction_count(), 3);
+        assert_eq!(cpu.iar(), 0x13);
+        assert_eq!(cpu.read_word(0x41).unwrap(), 14);
+
+        // Resuming carries on to the WAIT
+        assert_eq!(cpu.run_until_halt_or_break(100), Ok(StopReason::Halted));
+        assert_eq!(cpu.watchpoint_hit(), None);
+
+        assert!(cpu.remove_watchpoint(0x41));
+        assert!(!cpu.has_watchpoint(0x41));
+    }
+
+    #[test]
+    fn test_run_steps_reports_watchpoint() {
+        let mut cpu = CpuState::new();
+        // NOP; STO 0 0x40; NOP; WAIT
+        cpu.load_program(0x10, &[0x0000, 0x2040, 0x0000, 0xF000])
+            .unwrap();
+        cpu.add_watchpoint(0x40);
+
+        let summary = cpu.run_steps(10).unwrap();
+        assert_eq!(
+            summary,
+            RunSummary {
+                executed: 2,
+                halted: false,
+                watchpoint: Some(0x40),
+            }
+        );
+
+        cpu.clear_watchpoints();
+        assert_eq!(cpu.run_steps(10).unwrap().watchpoint, None);
+    }
 }
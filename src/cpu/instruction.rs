@@ -2,6 +2,12 @@
 //!
 //! This module defines the instruction types and addressing modes for the simplified IBM 1130 ISA.
 
+/// Largest meaningful count for a single-word shift (SLA, SRA, SRL)
+pub const MAX_SHIFT: u8 = 16;
+
+/// Largest meaningful count for a double-word shift (SLT, SRT)
+pub const MAX_DOUBLE_SHIFT: u8 = 31;
+
 /// Addressing modes supported by the IBM 1130
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AddressingMode {
@@ -9,6 +15,10 @@ pub enum AddressingMode {
     Direct,
     /// Indexed addressing - add XR1 to address
     Indexed,
+    /// Indirect addressing - address holds a pointer to the operand
+    DirectIndirect,
+    /// Indexed indirect addressing - address + XR1 holds a pointer to the operand
+    IndexedIndirect,
 }
 
 /// Branch conditions for BSC instruction
@@ -26,6 +36,18 @@ pub enum BranchCondition {
     Overflow,
     /// Branch if carry flag set
     Carry,
+    /// Branch if ACC is even (low bit clear)
+    EvenAcc,
+    /// Branch if the full ACC:EXT double word is zero
+    DoubleZero,
+    /// Branch if signed less than after a subtract (negative != that subtract overflowed)
+    LessSigned,
+    /// Branch if signed greater or equal after a subtract (negative == that subtract overflowed)
+    GreaterEqualSigned,
+    /// Branch if EXT < 0 (sign bit set), whatever ACC holds
+    ExtNegative,
+    /// Branch if EXT == 0, whatever ACC holds
+    ExtZero,
 }
 
 /// IBM 1130 instructions (simplified subset)
@@ -38,6 +60,15 @@ pub enum Instruction {
     /// Store ACC to memory
     STO { addr: u16, mode: AddressingMode },
 
+    /// Load ACC from memory and sign-extend it into EXT
+    LDS { addr: u16, mode: AddressingMode },
+
+    /// Load double word: ACC from memory[ea], EXT from memory[ea+1]
+    LDD { addr: u16, mode: AddressingMode },
+
+    /// Store double word: ACC to memory[ea], EXT to memory[ea+1]
+    STD { addr: u16, mode: AddressingMode },
+
     /// Load index register XR1 from memory
     LDX { addr: u16 },
 
@@ -48,9 +79,21 @@ pub enum Instruction {
     /// Add memory to ACC
     A { addr: u16, mode: AddressingMode },
 
+    /// Add memory and the carry flag to ACC (for multi-word sums)
+    AC { addr: u16, mode: AddressingMode },
+
     /// Subtract memory from ACC
     S { addr: u16, mode: AddressingMode },
 
+    /// Subtract memory and the borrow (carry flag) from ACC (for multi-word differences)
+    SC { addr: u16, mode: AddressingMode },
+
+    /// Multiply ACC by memory, 32-bit product in ACC (high) : EXT (low)
+    M { addr: u16, mode: AddressingMode },
+
+    /// Divide ACC:EXT by memory, quotient in ACC and remainder in EXT
+    D { addr: u16, mode: AddressingMode },
+
     // ===== Logical Instructions =====
     /// Boolean AND with ACC
     AND { addr: u16, mode: AddressingMode },
@@ -65,6 +108,18 @@ pub enum Instruction {
     /// Shift Right ACC (arithmetic)
     SRA { count: u8 },
 
+    /// Shift Right ACC (logical, zero-fill)
+    SRL { count: u8 },
+
+    /// Shift Left ACC:EXT as one 32-bit value
+    SLT { count: u8 },
+
+    /// Shift Right ACC:EXT as one 32-bit value (arithmetic)
+    SRT { count: u8 },
+
+    /// Exchange ACC and EXT
+    XCH,
+
     // ===== Branch Instructions =====
     /// Branch or Skip on Condition
     BSC {
@@ -75,12 +130,35 @@ pub enum Instruction {
     /// Branch and Store IAR (subroutine call)
     BSI { addr: u16 },
 
+    /// Return from subroutine: IAR ← memory[addr] (the word BSI stored)
+    RET { addr: u16 },
+
+    /// Skip the next instruction if the condition holds (short-form BSC)
+    SKP { condition: BranchCondition },
+
+    /// Branch relative: IAR ← address of this instruction + offset
+    BRL { offset: i8 },
+
     // ===== Control Instructions =====
     /// Wait/Halt
     WAIT,
 
+    /// Clear the carry and overflow flags
+    CLRF,
+
+    /// Load the status word (carry, overflow, halted) into ACC
+    LDSTAT,
+
+    /// Set carry and overflow from ACC's status bits; the halted bit halts
+    STSTAT,
+
     /// No Operation
     NOP,
+
+    // ===== Raw Data =====
+    /// A word that is not an instruction encoding, kept as-is; executing it
+    /// is an error
+    Data { word: u16 },
 }
 
 impl Instruction {
@@ -89,18 +167,86 @@ impl Instruction {
         match self {
             Instruction::LD { .. } => "LD",
             Instruction::STO { .. } => "STO",
+            Instruction::LDS { .. } => "LDS",
+            Instruction::LDD { .. } => "LDD",
+            Instruction::STD { .. } => "STD",
             Instruction::LDX { .. } => "LDX",
             Instruction::STX { .. } => "STX",
             Instruction::A { .. } => "A",
+            Instruction::AC { .. } => "AC",
             Instruction::S { .. } => "S",
+            Instruction::SC { .. } => "SC",
+            Instruction::M { .. } => "M",
+            Instruction::D { .. } => "D",
             Instruction::AND { .. } => "AND",
             Instruction::OR { .. } => "OR",
             Instruction::SLA { .. } => "SLA",
             Instruction::SRA { .. } => "SRA",
+            Instruction::SRL { .. } => "SRL",
+            Instruction::SLT { .. } => "SLT",
+            Instruction::SRT { .. } => "SRT",
+            Instruction::XCH => "XCH",
             Instruction::BSC { .. } => "BSC",
             Instruction::BSI { .. } => "BSI",
+            Instruction::RET { .. } => "RET",
+            Instruction::SKP { .. } => "SKP",
+            Instruction::BRL { .. } => "BRL",
             Instruction::WAIT => "WAIT",
+            Instruction::CLRF => "CLRF",
+            Instruction::LDSTAT => "LDSTAT",
+            Instruction::STSTAT => "STSTAT",
             Instruction::NOP => "NOP",
+            Instruction::Data { .. } => "DATA",
+        }
+    }
+
+    /// Representative execution time in machine cycles
+    ///
+    /// Memory-reference instructions take two cycles, plus one for indexing
+    /// and one for each indirect fetch; multiply and divide take much longer.
+    pub fn cycle_cost(&self) -> u64 {
+        match self {
+            Instruction::LD { mode, .. }
+            | Instruction::STO { mode, .. }
+            | Instruction::LDS { mode, .. }
+            | Instruction::A { mode, .. }
+            | Instruction::AC { mode, .. }
+            | Instruction::S { mode, .. }
+            | Instruction::SC { mode, .. }
+            | Instruction::AND { mode, .. }
+            | Instruction::OR { mode, .. } => 2 + mode.extra_cycles(),
+            Instruction::LDD { mode, .. } | Instruction::STD { mode, .. } => {
+                3 + mode.extra_cycles()
+            }
+            Instruction::M { mode, .. } => 8 + mode.extra_cycles(),
+            Instruction::D { mode, .. } => 12 + mode.extra_cycles(),
+            Instruction::LDX { .. } | Instruction::STX { .. } => 2,
+            Instruction::SLA { .. }
+            | Instruction::SRA { .. }
+            | Instruction::SRL { .. }
+            | Instruction::SLT { .. }
+            | Instruction::SRT { .. }
+            | Instruction::XCH => 2,
+            Instruction::BSC { .. } | Instruction::BRL { .. } => 2,
+            Instruction::BSI { .. } | Instruction::RET { .. } => 3,
+            Instruction::SKP { .. }
+            | Instruction::WAIT
+            | Instruction::CLRF
+            | Instruction::LDSTAT
+            | Instruction::STSTAT
+            | Instruction::NOP
+            | Instruction::Data { .. } => 1,
+        }
+    }
+}
+
+impl AddressingMode {
+    /// Cycles added to a memory-reference instruction by this mode
+    pub fn extra_cycles(&self) -> u64 {
+        match self {
+            AddressingMode::Direct => 0,
+            AddressingMode::Indexed | AddressingMode::DirectIndirect => 1,
+            AddressingMode::IndexedIndirect => 2,
         }
     }
 }
@@ -115,6 +261,12 @@ impl BranchCondition {
             BranchCondition::Negative => "N",
             BranchCondition::Overflow => "V",
             BranchCondition::Carry => "C",
+            BranchCondition::EvenAcc => "E",
+            BranchCondition::DoubleZero => "DZ",
+            BranchCondition::LessSigned => "LT",
+            BranchCondition::GreaterEqualSigned => "GE",
+            BranchCondition::ExtNegative => "XN",
+            BranchCondition::ExtZero => "XZ",
         }
     }
 
@@ -127,6 +279,12 @@ impl BranchCondition {
             "N" => Some(BranchCondition::Negative),
             "V" => Some(BranchCondition::Overflow),
             "C" => Some(BranchCondition::Carry),
+            "E" => Some(BranchCondition::EvenAcc),
+            "DZ" => Some(BranchCondition::DoubleZero),
+            "LT" => Some(BranchCondition::LessSigned),
+            "GE" => Some(BranchCondition::GreaterEqualSigned),
+            "XN" => Some(BranchCondition::ExtNegative),
+            "XZ" => Some(BranchCondition::ExtZero),
             _ => None,
         }
     }
@@ -147,6 +305,21 @@ mod tests {
     fn test_branch_condition_parse() {
         assert_eq!(BranchCondition::parse("Z"), Some(BranchCondition::Zero));
         assert_eq!(BranchCondition::parse("nz"), Some(BranchCondition::NonZero));
+        assert_eq!(BranchCondition::parse("e"), Some(BranchCondition::EvenAcc));
+        assert_eq!(
+            BranchCondition::parse("DZ"),
+            Some(BranchCondition::DoubleZero)
+        );
+        assert_eq!(
+            BranchCondition::parse("lt"),
+            Some(BranchCondition::LessSigned)
+        );
+        assert_eq!(BranchCondition::GreaterEqualSigned.to_str(), "GE");
+        assert_eq!(
+            BranchCondition::parse("xn"),
+            Some(BranchCondition::ExtNegative)
+        );
+        assert_eq!(BranchCondition::ExtZero.to_str(), "XZ");
         assert_eq!(BranchCondition::parse("invalid"), None);
     }
 
@@ -161,4 +334,46 @@ mod tests {
         let wait = Instruction::WAIT;
         assert_eq!(wait.mnemonic(), "WAIT");
     }
+
+    #[test]
+    fn test_cycle_cost() {
+        assert_eq!(Instruction::NOP.cycle_cost(), 1);
+        assert_eq!(Instruction::WAIT.cycle_cost(), 1);
+        assert_eq!(
+            Instruction::LD {
+                addr: 0,
+                mode: AddressingMode::Direct
+            }
+            .cycle_cost(),
+            2
+        );
+        assert_eq!(
+            Instruction::A {
+                addr: 0,
+                mode: AddressingMode::Indexed
+            }
+            .cycle_cost(),
+            3
+        );
+        assert_eq!(
+            Instruction::STO {
+                addr: 0,
+                mode: AddressingMode::IndexedIndirect
+            }
+            .cycle_cost(),
+            4
+        );
+        assert!(
+            Instruction::M {
+                addr: 0,
+                mode: AddressingMode::Direct
+            }
+            .cycle_cost()
+                > Instruction::S {
+                    addr: 0,
+                    mode: AddressingMode::Direct
+                }
+                .cycle_cost()
+        );
+    }
 }
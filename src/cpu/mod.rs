@@ -6,5 +6,11 @@ pub mod executor;
 pub mod instruction;
 pub mod state;
 
-pub use instruction::{AddressingMode, BranchCondition, Instruction};
-pub use state::{CpuError, CpuState, MEMORY_SIZE, PROGRAM_START, XR1_ADDR};
+pub use executor::{RunSummary, StepRecord, StopReason};
+pub use instruction::{AddressingMode, BranchCondition, Instruction, MAX_DOUBLE_SHIFT, MAX_SHIFT};
+pub use state::{
+    AddressWrap, CpuError, CpuSnapshot, CpuState, ExecutionCount, HISTORY_DEPTH, MEMORY_SIZE,
+    MemoryProtection, PROGRAM_START, SELF_MODIFICATION_LIMIT, STATUS_CARRY, STATUS_HALTED,
+    STATUS_OVERFLOW, SavedState, SelfModification, TRACE_DEPTH, TRAP_ADDR, TRAP_INSTRUCTION,
+    TraceEntry, XR1_ADDR,
+};
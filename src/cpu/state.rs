@@ -2,13 +2,20 @@
 //!
 //! This module implements the CPU state including registers, memory, and flags.
 
+use crate::assembler::AssembledProgram;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet, VecDeque};
 use thiserror::Error;
 
-/// Number of 16-bit words in memory (4K words = 4096)
-/// IBM 1130 could have up to 32K words, but we use 4K for this emulator
+/// Default number of 16-bit words in memory (4K words = 4096)
+/// IBM 1130 could have up to 32K words; see `CpuState::with_memory_words`
 pub const MEMORY_SIZE: usize = 4096;
 
+/// Largest memory configuration (32K words)
+pub const MAX_MEMORY_SIZE: usize = 32768;
+
 /// Reserved memory locations
+pub const TRAP_ADDR: u16 = 0x0000; // Safety trap for wild branches
 pub const XR1_ADDR: u16 = 0x0001; // Index Register 1
 pub const XR2_ADDR: u16 = 0x0002; // Index Register 2
 pub const XR3_ADDR: u16 = 0x0003; // Index Register 3
@@ -17,6 +24,23 @@ pub const XR3_ADDR: u16 = 0x0003; // Index Register 3
 /// Address 0x0000-0x0003 are reserved for system use (infinite loop trap and index registers)
 pub const PROGRAM_START: u16 = 0x0010;
 
+/// Word seeded at `TRAP_ADDR` on hard reset: `BRL 0`, a branch to itself
+pub const TRAP_INSTRUCTION: u16 = 0xC100;
+
+/// Maximum number of steps kept for `step_back`
+pub const HISTORY_DEPTH: usize = 1000;
+
+/// Maximum number of self-modifications recorded; later ones are dropped
+pub const SELF_MODIFICATION_LIMIT: usize = 1000;
+
+/// Maximum number of entries kept in the trace buffer; the oldest are dropped
+pub const TRACE_DEPTH: usize = 10_000;
+
+/// Status word bits used by LDSTAT and STSTAT
+pub const STATUS_CARRY: u16 = 0b001;
+pub const STATUS_OVERFLOW: u16 = 0b010;
+pub const STATUS_HALTED: u16 = 0b100;
+
 /// CPU execution errors
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
 pub enum CpuError {
@@ -31,6 +55,163 @@ pub enum CpuError {
 
     #[error("Program counter out of bounds: 0x{0:04X}")]
     IarOutOfBounds(u16),
+
+    #[error("Invalid saved state: {0}")]
+    InvalidSavedState(String),
+
+    #[error("Unsupported memory size: {0} words (expected 4K, 8K, 16K or 32K)")]
+    InvalidMemorySize(usize),
+
+    #[error("Protection fault: program accessed protected address 0x{0:04X}")]
+    ProtectionFault(u16),
+}
+
+/// What happens when an address falls outside installed memory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AddressWrap {
+    /// Out-of-range accesses fail with `MemoryOutOfBounds`
+    #[default]
+    Trap,
+    /// Addresses are masked to the memory size, like a core machine with fewer address bits
+    Wrap,
+}
+
+/// Which low-memory words a running program may not execute or store into
+///
+/// Loading, depositing, and the debugger's memory writes are not restricted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MemoryProtection {
+    /// Programs may execute and store anywhere
+    Disabled,
+    /// Location 0, the safety trap, is protected
+    #[default]
+    TrapWord,
+    /// Locations 0-3, the safety trap and the index registers, are protected
+    ReservedWords,
+}
+
+impl CpuError {
+    /// True for `Halted`, the expected end of a program; every other
+    /// variant is a genuine fault
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, CpuError::Halted)
+    }
+}
+
+/// Serializable copy of the machine state, used to save and resume sessions
+///
+/// Breakpoints are debugger settings rather than machine state and are not saved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedState {
+    pub acc: u16,
+    pub ext: u16,
+    pub iar: u16,
+    pub carry: bool,
+    pub overflow: bool,
+    /// Signed overflow of the last add or subtract (absent in older saves)
+    #[serde(default)]
+    pub last_overflow: bool,
+    pub halted: bool,
+    pub cycle_count: u64,
+    pub instruction_count: u64,
+    /// Full memory image (must match the machine's memory size)
+    pub memory: Vec<u16>,
+}
+
+/// Registers, flags, counters, and memory as the front end displays them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuSnapshot {
+    pub acc: u16,
+    pub ext: u16,
+    pub iar: u16,
+    pub xr1: u16,
+    pub xr2: u16,
+    pub xr3: u16,
+    pub carry: bool,
+    pub overflow: bool,
+    /// ACC is non-zero with the sign bit clear
+    pub positive: bool,
+    /// ACC is zero
+    pub zero: bool,
+    /// ACC has the sign bit set
+    pub negative: bool,
+    pub halted: bool,
+    pub cycle_count: u64,
+    pub instruction_count: u64,
+    pub memory: Vec<u16>,
+}
+
+impl CpuSnapshot {
+    /// Capture registers, flags, counters, and memory from a CPU
+    pub fn capture(cpu: &CpuState) -> Self {
+        let acc = cpu.read_acc();
+        let flags = components::acc_flags(acc);
+        Self {
+            acc,
+            ext: cpu.read_ext(),
+            iar: cpu.iar(),
+            xr1: cpu.read_xr1(),
+            xr2: cpu.read_xr2(),
+            xr3: cpu.read_xr3(),
+            carry: cpu.carry(),
+            overflow: cpu.overflow(),
+            positive: flags.positive,
+            zero: flags.zero,
+            negative: flags.negative,
+            halted: cpu.is_halted(),
+            cycle_count: cpu.cycle_count(),
+            instruction_count: cpu.instruction_count(),
+            memory: cpu.memory().to_vec(),
+        }
+    }
+}
+
+/// Everything needed to undo one step: the registers before it ran and the
+/// previous value of each memory word it wrote
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UndoRecord {
+    acc: u16,
+    ext: u16,
+    iar: u16,
+    carry: bool,
+    overflow: bool,
+    last_overflow: bool,
+    halted: bool,
+    cycle_count: u64,
+    instruction_count: u64,
+    /// (address, previous value) in write order
+    writes: Vec<(u16, u16)>,
+}
+
+/// One executed instruction in the trace buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TraceEntry {
+    /// Address the instruction was fetched from
+    pub iar: u16,
+    /// The instruction word
+    pub opcode: u16,
+    /// ACC after the instruction executed
+    pub acc: u16,
+}
+
+/// How many times the instruction at one address has executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ExecutionCount {
+    /// Address the instruction was fetched from
+    pub address: u16,
+    /// Executions since the counters were last reset
+    pub count: u32,
+}
+
+/// A store by the running program into its own loaded code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SelfModification {
+    /// Address of the overwritten code word
+    pub address: u16,
+    /// Address of the storing instruction
+    pub iar: u16,
+    /// The word stored
+    pub value: u16,
 }
 
 /// IBM 1130 CPU state
@@ -51,9 +232,13 @@ pub struct CpuState {
     /// Overflow flag
     overflow: bool,
 
-    /// Memory (4096 16-bit words = 4K)
+    /// Signed overflow of the most recent add or subtract; unlike `overflow`
+    /// it is not sticky, so the signed compare conditions can rely on it
+    last_overflow: bool,
+
+    /// Memory (4K to 32K 16-bit words, fixed at construction)
     /// Note: Index registers XR1-XR3 are stored at memory[1], memory[2], memory[3]
-    memory: [u16; MEMORY_SIZE],
+    memory: Vec<u16>,
 
     /// Execution state
     halted: bool,
@@ -63,6 +248,51 @@ pub struct CpuState {
 
     /// Instruction counter
     instruction_count: u64,
+
+    /// Executions per address, keyed by the IAR at fetch (the heat map)
+    executions: Vec<u32>,
+
+    /// Addresses where the run loop stops before executing
+    breakpoints: HashSet<u16>,
+
+    /// Addresses whose writes are reported by `take_changed_watches`
+    watches: HashSet<u16>,
+
+    /// Watched addresses whose value changed since the last take
+    changed_watches: BTreeSet<u16>,
+
+    /// Addresses whose writes stop the run loop
+    watchpoints: HashSet<u16>,
+
+    /// Watchpoint written by the most recent step, if any
+    watchpoint_hit: Option<u16>,
+
+    /// Cells written since the last `clear_dirty`
+    dirty: Vec<bool>,
+
+    /// Undo records for `step_back`, oldest first (`None` when history is off)
+    history: Option<VecDeque<UndoRecord>>,
+
+    /// Memory writes made by the step currently being recorded
+    journal: Option<Vec<(u16, u16)>>,
+
+    /// Executed instructions, oldest first (`None` when tracing is off)
+    trace: Option<VecDeque<TraceEntry>>,
+
+    /// How out-of-range memory addresses are handled
+    address_wrap: AddressWrap,
+
+    /// Which low-memory words programs may not execute or store into
+    memory_protection: MemoryProtection,
+
+    /// First and last address of the loaded code (entry point through first WAIT)
+    code_region: Option<(u16, u16)>,
+
+    /// Entry point of the program last loaded by `load_assembled`, kept for `reset_reload`
+    entry_point: Option<u16>,
+
+    /// Stores into `code_region` made by executing instructions, oldest first
+    self_modifications: Vec<SelfModification>,
 }
 
 impl Default for CpuState {
@@ -72,18 +302,47 @@ impl Default for CpuState {
 }
 
 impl CpuState {
-    /// Create a new CPU with default state
+    /// Create a new CPU with default state and 4K words of memory
     pub fn new() -> Self {
+        Self::with_words(MEMORY_SIZE)
+    }
+
+    /// Create a new CPU with `words` of memory: 4K, 8K, 16K or 32K
+    pub fn with_memory_words(words: usize) -> Result<Self, CpuError> {
+        let valid = words.is_power_of_two() && (MEMORY_SIZE..=MAX_MEMORY_SIZE).contains(&words);
+        if !valid {
+            return Err(CpuError::InvalidMemorySize(words));
+        }
+        Ok(Self::with_words(words))
+    }
+
+    fn with_words(words: usize) -> Self {
         Self {
             acc: 0,
             ext: 0,
             iar: PROGRAM_START,
             carry: false,
             overflow: false,
-            memory: [0; MEMORY_SIZE],
+            last_overflow: false,
+            memory: vec![0; words],
             halted: false,
             cycle_count: 0,
             instruction_count: 0,
+            executions: vec![0; words],
+            breakpoints: HashSet::new(),
+            watches: HashSet::new(),
+            changed_watches: BTreeSet::new(),
+            watchpoints: HashSet::new(),
+            watchpoint_hit: None,
+            dirty: vec![false; words],
+            history: None,
+            journal: None,
+            trace: None,
+            address_wrap: AddressWrap::Trap,
+            memory_protection: MemoryProtection::TrapWord,
+            code_region: None,
+            entry_point: None,
+            self_modifications: Vec::new(),
         }
     }
 
@@ -94,16 +353,44 @@ impl CpuState {
         self.iar = PROGRAM_START;
         self.carry = false;
         self.overflow = false;
+        self.last_overflow = false;
         self.halted = false;
+        self.reset_counters();
+        self.clear_history();
+        // Note: Memory, breakpoints and watches are NOT cleared on reset (program stays loaded)
+    }
+
+    /// Zero the cycle, instruction and per-address execution counters,
+    /// leaving registers and memory alone
+    pub fn reset_counters(&mut self) {
         self.cycle_count = 0;
         self.instruction_count = 0;
-        // Note: Memory is NOT cleared on reset (program stays loaded)
+        self.executions.fill(0);
     }
 
-    /// Reset and clear all memory
+    /// Reset registers to run the last assembled program again from its entry point
+    ///
+    /// Memory is kept, including any words the program changed. Without an
+    /// assembled program this is a plain `reset`.
+    pub fn reset_reload(&mut self) {
+        self.reset();
+        if let Some(entry) = self.entry_point {
+            // The entry point was a valid IAR when the program was loaded
+            let _ = self.set_iar(entry);
+        }
+    }
+
+    /// Reset and clear all memory, leaving the self-branch `TRAP_INSTRUCTION`
+    /// at location 0 so a wild jump there is easy to spot
     pub fn hard_reset(&mut self) {
         self.reset();
-        self.memory = [0; MEMORY_SIZE];
+        self.memory.fill(0);
+        self.memory[TRAP_ADDR as usize] = TRAP_INSTRUCTION;
+        self.changed_watches.clear();
+        self.clear_dirty();
+        self.code_region = None;
+        self.entry_point = None;
+        self.self_modifications.clear();
     }
 
     // ===== Register Access =====
@@ -135,7 +422,7 @@ impl CpuState {
 
     /// Set instruction address register
     pub fn set_iar(&mut self, addr: u16) -> Result<(), CpuError> {
-        if addr as usize >= MEMORY_SIZE {
+        if addr as usize >= self.memory.len() {
             return Err(CpuError::IarOutOfBounds(addr));
         }
         self.iar = addr;
@@ -154,7 +441,7 @@ impl CpuState {
 
     /// Write index register 1 (to memory location 1)
     pub fn write_xr1(&mut self, value: u16) {
-        self.memory[XR1_ADDR as usize] = value;
+        self.store(XR1_ADDR, value);
     }
 
     /// Read index register 2 (from memory location 2)
@@ -164,7 +451,7 @@ impl CpuState {
 
     /// Write index register 2 (to memory location 2)
     pub fn write_xr2(&mut self, value: u16) {
-        self.memory[XR2_ADDR as usize] = value;
+        self.store(XR2_ADDR, value);
     }
 
     /// Read index register 3 (from memory location 3)
@@ -174,14 +461,53 @@ impl CpuState {
 
     /// Write index register 3 (to memory location 3)
     pub fn write_xr3(&mut self, value: u16) {
-        self.memory[XR3_ADDR as usize] = value;
+        self.store(XR3_ADDR, value);
     }
 
     // ===== Memory Access =====
 
+    /// Set how out-of-range memory addresses are handled
+    pub fn set_address_wrap(&mut self, mode: AddressWrap) {
+        self.address_wrap = mode;
+    }
+
+    /// How out-of-range memory addresses are handled
+    pub fn address_wrap(&self) -> AddressWrap {
+        self.address_wrap
+    }
+
+    /// Map an address onto memory: masked in `Wrap` mode, unchanged in `Trap` mode
+    pub fn wrap_address(&self, addr: u16) -> u16 {
+        match self.address_wrap {
+            AddressWrap::Trap => addr,
+            AddressWrap::Wrap => addr & (self.memory.len() - 1) as u16,
+        }
+    }
+
+    /// Set which low-memory words programs may not execute or store into
+    pub fn set_memory_protection(&mut self, protection: MemoryProtection) {
+        self.memory_protection = protection;
+    }
+
+    /// Which low-memory words programs may not execute or store into
+    pub fn memory_protection(&self) -> MemoryProtection {
+        self.memory_protection
+    }
+
+    /// Whether a running program may not execute or store at `addr`
+    pub fn is_protected(&self, addr: u16) -> bool {
+        let addr = self.wrap_address(addr);
+        match self.memory_protection {
+            MemoryProtection::Disabled => false,
+            MemoryProtection::TrapWord => addr == TRAP_ADDR,
+            MemoryProtection::ReservedWords => addr <= XR3_ADDR,
+        }
+    }
+
     /// Read a word from memory
     pub fn read_word(&self, addr: u16) -> Result<u16, CpuError> {
-        if addr as usize >= MEMORY_SIZE {
+        let addr = self.wrap_address(addr);
+        if addr as usize >= self.memory.len() {
             return Err(CpuError::MemoryOutOfBounds(addr));
         }
         Ok(self.memory[addr as usize])
@@ -189,16 +515,48 @@ impl CpuState {
 
     /// Write a word to memory
     pub fn write_word(&mut self, addr: u16, value: u16) -> Result<(), CpuError> {
-        if addr as usize >= MEMORY_SIZE {
+        let addr = self.wrap_address(addr);
+        if addr as usize >= self.memory.len() {
             return Err(CpuError::MemoryOutOfBounds(addr));
         }
-        self.memory[addr as usize] = value;
+        self.store(addr, value);
         Ok(())
     }
 
+    /// Write consecutive words starting at `start`
+    ///
+    /// The whole range is checked before anything is written. Unlike
+    /// `load_program`, each word is an ordinary memory write, so watches and
+    /// dirty tracking see it.
+    pub fn write_words(&mut self, start: u16, values: &[u16]) -> Result<(), CpuError> {
+        if start as usize + values.len() > self.memory.len() {
+            return Err(CpuError::MemoryOutOfBounds(start));
+        }
+        for (addr, &value) in (start..).zip(values) {
+            self.store(addr, value);
+        }
+        Ok(())
+    }
+
+    /// Store a word at an in-bounds address, noting changes to watched cells
+    fn store(&mut self, addr: u16, value: u16) {
+        let cell = &mut self.memory[addr as usize];
+        if let Some(journal) = &mut self.journal {
+            journal.push((addr, *cell));
+        }
+        if *cell != value && self.watches.contains(&addr) {
+            self.changed_watches.insert(addr);
+        }
+        if self.watchpoints.contains(&addr) {
+            self.watchpoint_hit = Some(addr);
+        }
+        *cell = value;
+        self.dirty[addr as usize] = true;
+    }
+
     /// Load program into memory starting at address
     pub fn load_program(&mut self, start_addr: u16, data: &[u16]) -> Result<(), CpuError> {
-        if start_addr as usize + data.len() > MEMORY_SIZE {
+        if start_addr as usize + data.len() > self.memory.len() {
             return Err(CpuError::MemoryOutOfBounds(start_addr));
         }
 
@@ -207,6 +565,98 @@ impl CpuState {
         Ok(())
     }
 
+    /// Load an assembled program, writing every instruction and DATA word at its
+    /// address, and point the IAR at its entry point
+    ///
+    /// Like `load_program`, the words are copied in directly: loading does not
+    /// mark memory dirty or trip watches and watchpoints. Nothing is written
+    /// unless every word fits in memory.
+    ///
+    /// The words from the entry point through the first WAIT (or the last
+    /// placed word if there is none) become the code region watched for
+    /// self-modification.
+    pub fn load_assembled(&mut self, program: &AssembledProgram) -> Result<(), CpuError> {
+        self.check_fits(program)?;
+        for &(addr, word) in &program.placements {
+            let addr = self.wrap_address(addr);
+            self.memory[addr as usize] = word;
+        }
+        let start = program.start_addr;
+        let last = program.placements.iter().map(|&(addr, _)| addr).max();
+        self.code_region = last.filter(|&last| last >= start).map(|last| {
+            let is_wait = |addr: u16| {
+                crate::assembler::decode_instruction(self.memory[addr as usize])
+                    .is_ok_and(|instr| instr == crate::cpu::Instruction::WAIT)
+            };
+            (
+                start,
+                (start..=last).find(|&addr| is_wait(addr)).unwrap_or(last),
+            )
+        });
+        self.self_modifications.clear();
+        self.entry_point = Some(program.start_addr);
+        self.set_iar(program.start_addr)
+    }
+
+    /// Hard-reset, then assemble `source` at `origin` and load it into the
+    /// cleared machine
+    ///
+    /// Memory holds only the new image and the IAR is at the entry point. A
+    /// failed assembly, or a program that does not fit in memory, leaves the
+    /// machine as it was.
+    pub fn assemble_and_load(
+        &mut self,
+        source: &str,
+        origin: u16,
+    ) -> Result<AssembledProgram, String> {
+        let program = crate::assembler::Assembler::with_origin(origin)
+            .assemble(source)
+            .map_err(|e| e.to_string())?;
+        self.check_fits(&program).map_err(|e| e.to_string())?;
+        self.hard_reset();
+        self.load_assembled(&program).map_err(|e| e.to_string())?;
+        Ok(program)
+    }
+
+    /// Check that every word of an assembled program fits in memory, as
+    /// `load_assembled` requires
+    pub fn check_fits(&self, program: &AssembledProgram) -> Result<(), CpuError> {
+        for &(addr, _) in &program.placements {
+            let addr = self.wrap_address(addr);
+            if addr as usize >= self.memory.len() {
+                return Err(CpuError::MemoryOutOfBounds(addr));
+            }
+        }
+        Ok(())
+    }
+
+    /// First and last address of the code loaded by `load_assembled`
+    pub fn code_region(&self) -> Option<(u16, u16)> {
+        self.code_region
+    }
+
+    /// Stores the running program made into its own code, oldest first
+    ///
+    /// At most `SELF_MODIFICATION_LIMIT` are kept; a hard reset or a new
+    /// `load_assembled` clears them.
+    pub fn self_modifications(&self) -> &[SelfModification] {
+        &self.self_modifications
+    }
+
+    /// Note a program store of `value` at `addr` if it lands in the code region
+    pub(crate) fn record_store(&mut self, addr: u16, value: u16) {
+        let in_code = self
+            .code_region
+            .is_some_and(|(first, last)| (first..=last).contains(&addr));
+        if in_code && self.self_modifications.len() < SELF_MODIFICATION_LIMIT {
+            self.self_modifications.push(SelfModification {
+                address: addr,
+                iar: self.iar,
+                value,
+            });
+        }
+    }
+
     // ===== Flags =====
 
     /// Get carry flag
@@ -229,7 +679,23 @@ impl CpuState {
         self.overflow = value;
     }
 
+    /// Whether the most recent add or subtract overflowed, whatever the
+    /// sticky overflow latch holds
+    pub fn last_overflow(&self) -> bool {
+        self.last_overflow
+    }
+
+    /// Record the signed overflow of an add or subtract, latching it into
+    /// the sticky flag as well
+    pub fn note_arithmetic_overflow(&mut self, overflowed: bool) {
+        self.last_overflow = overflowed;
+        self.overflow |= overflowed;
+    }
+
     /// Update flags for addition
+    ///
+    /// Overflow is sticky: a signed overflow sets it, but a clean add leaves
+    /// an earlier overflow latched until a `V` condition test clears it.
     pub fn update_flags_add(&mut self, a: u16, b: u16, result: u16) {
         // Carry: unsigned overflow
         self.carry = result < a;
@@ -239,10 +705,10 @@ impl CpuState {
         let a_sign = (a & 0x8000) != 0;
         let b_sign = (b & 0x8000) != 0;
         let r_sign = (result & 0x8000) != 0;
-        self.overflow = (a_sign == b_sign) && (a_sign != r_sign);
+        self.note_arithmetic_overflow((a_sign == b_sign) && (a_sign != r_sign));
     }
 
-    /// Update flags for subtraction
+    /// Update flags for subtraction (overflow is sticky, as for addition)
     pub fn update_flags_sub(&mut self, a: u16, b: u16, result: u16) {
         // Carry: unsigned underflow (borrow)
         self.carry = a < b;
@@ -251,7 +717,7 @@ impl CpuState {
         let a_sign = (a & 0x8000) != 0;
         let b_sign = (b & 0x8000) != 0;
         let r_sign = (result & 0x8000) != 0;
-        self.overflow = (a_sign != b_sign) && (a_sign != r_sign);
+        self.note_arithmetic_overflow((a_sign != b_sign) && (a_sign != r_sign));
     }
 
     // ===== Execution State =====
@@ -286,22 +752,312 @@ impl CpuState {
         self.cycle_count += 1;
     }
 
+    /// Advance cycle counter by `n` cycles
+    pub fn add_cycles(&mut self, n: u64) {
+        self.cycle_count += n;
+    }
+
+    // ===== Breakpoints =====
+
+    /// Set a breakpoint at an address
+    pub fn add_breakpoint(&mut self, addr: u16) {
+        self.breakpoints.insert(addr);
+    }
+
+    /// Remove a breakpoint; returns true if one was set
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.remove(&addr)
+    }
+
+    /// Remove all breakpoints
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Check if a breakpoint is set at an address
+    pub fn has_breakpoint(&self, addr: u16) -> bool {
+        self.breakpoints.contains(&addr)
+    }
+
+    // ===== Watches =====
+
+    /// Watch an address for changes
+    pub fn set_watch(&mut self, addr: u16) {
+        self.watches.insert(addr);
+    }
+
+    /// Stop watching an address; returns true if it was watched
+    pub fn clear_watch(&mut self, addr: u16) -> bool {
+        self.changed_watches.remove(&addr);
+        self.watches.remove(&addr)
+    }
+
+    /// Return the watched addresses written with a new value since the last call, in order
+    pub fn take_changed_watches(&mut self) -> Vec<u16> {
+        std::mem::take(&mut self.changed_watches)
+            .into_iter()
+            .collect()
+    }
+
+    /// Set a watchpoint: a run stops after any instruction that writes `addr`
+    pub fn add_watchpoint(&mut self, addr: u16) {
+        self.watchpoints.insert(addr);
+    }
+
+    /// Remove a watchpoint; returns true if one was set
+    pub fn remove_watchpoint(&mut self, addr: u16) -> bool {
+        self.watchpoints.remove(&addr)
+    }
+
+    /// Remove all watchpoints
+    pub fn clear_watchpoints(&mut self) {
+        self.watchpoints.clear();
+    }
+
+    /// Check if a watchpoint is set at an address
+    pub fn has_watchpoint(&self, addr: u16) -> bool {
+        self.watchpoints.contains(&addr)
+    }
+
+    /// The watchpoint written by the most recent step, if any
+    pub fn watchpoint_hit(&self) -> Option<u16> {
+        self.watchpoint_hit
+    }
+
+    /// Forget the watchpoint hit before executing the next step
+    pub(crate) fn clear_watchpoint_hit(&mut self) {
+        self.watchpoint_hit = None;
+    }
+
+    // ===== Dirty Tracking =====
+
+    /// Addresses written since the last `clear_dirty`, in ascending order
+    pub fn dirty_addresses(&self) -> Vec<u16> {
+        self.dirty
+            .iter()
+            .enumerate()
+            .filter(|&(_, &written)| written)
+            .map(|(addr, _)| addr as u16)
+            .collect()
+    }
+
+    /// Forget which addresses have been written
+    pub fn clear_dirty(&mut self) {
+        self.dirty.fill(false);
+    }
+
+    // ===== Step History =====
+
+    /// Turn step history on or off; turning it off discards recorded steps
+    ///
+    /// History costs a snapshot per step, so it is off by default.
+    pub fn set_history_enabled(&mut self, enabled: bool) {
+        match (enabled, &self.history) {
+            (true, None) => self.history = Some(VecDeque::new()),
+            (false, _) => self.history = None,
+            _ => {}
+        }
+    }
+
+    /// Check whether steps are being recorded for `step_back`
+    pub fn history_enabled(&self) -> bool {
+        self.history.is_some()
+    }
+
+    /// Number of steps that can currently be undone
+    pub fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, VecDeque::len)
+    }
+
+    /// Discard recorded steps without turning history off
+    fn clear_history(&mut self) {
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+    }
+
+    /// Run one step, recording how to undo it when history is enabled
+    ///
+    /// A step that fails is not recorded.
+    pub(crate) fn record_step<T>(
+        &mut self,
+        step: impl FnOnce(&mut Self) -> Result<T, CpuError>,
+    ) -> Result<T, CpuError> {
+        if self.history.is_none() {
+            return step(self);
+        }
+
+        let mut record = UndoRecord {
+            acc: self.acc,
+            ext: self.ext,
+            iar: self.iar,
+            carry: self.carry,
+            overflow: self.overflow,
+            last_overflow: self.last_overflow,
+            halted: self.halted,
+            cycle_count: self.cycle_count,
+            instruction_count: self.instruction_count,
+            writes: Vec::new(),
+        };
+        self.journal = Some(Vec::new());
+        let result = step(self);
+        record.writes = self.journal.take().unwrap_or_default();
+
+        if result.is_ok()
+            && let Some(history) = &mut self.history
+        {
+            if history.len() == HISTORY_DEPTH {
+                history.pop_front();
+            }
+            history.push_back(record);
+        }
+        result
+    }
+
+    /// Undo the most recently recorded step; returns false if there is none
+    pub fn step_back(&mut self) -> bool {
+        let Some(record) = self.history.as_mut().and_then(VecDeque::pop_back) else {
+            return false;
+        };
+
+        for &(addr, value) in record.writes.iter().rev() {
+            self.memory[addr as usize] = value;
+        }
+        self.acc = record.acc;
+        self.ext = record.ext;
+        self.iar = record.iar;
+        self.carry = record.carry;
+        self.overflow = record.overflow;
+        self.last_overflow = record.last_overflow;
+        self.halted = record.halted;
+        self.cycle_count = record.cycle_count;
+        self.instruction_count = record.instruction_count;
+        true
+    }
+
+    // ===== Tracing =====
+
+    /// Turn the instruction trace on or off; turning it off discards the trace
+    pub fn set_tracing(&mut self, enabled: bool) {
+        match (enabled, &self.trace) {
+            (true, None) => self.trace = Some(VecDeque::new()),
+            (false, _) => self.trace = None,
+            _ => {}
+        }
+    }
+
+    /// Check whether executed instructions are being traced
+    pub fn is_tracing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    /// Return the traced instructions, oldest first, and clear the buffer
+    ///
+    /// At most `TRACE_DEPTH` of the most recent instructions are kept.
+    pub fn take_trace(&mut self) -> Vec<TraceEntry> {
+        self.trace
+            .as_mut()
+            .map(|trace| trace.drain(..).collect())
+            .unwrap_or_default()
+    }
+
+    /// Add an executed instruction to the trace when tracing is on
+    pub(crate) fn record_trace(&mut self, iar: u16, opcode: u16) {
+        let acc = self.acc;
+        if let Some(trace) = &mut self.trace {
+            if trace.len() == TRACE_DEPTH {
+                trace.pop_front();
+            }
+            trace.push_back(TraceEntry { iar, opcode, acc });
+        }
+    }
+
     /// Increment instruction counter
     pub fn count_instruction(&mut self) {
         self.instruction_count += 1;
     }
 
+    /// Count one execution of the instruction fetched from `addr`
+    pub(crate) fn count_execution(&mut self, addr: u16) {
+        if let Some(count) = self.executions.get_mut(addr as usize) {
+            *count = count.saturating_add(1);
+        }
+    }
+
+    /// Addresses that have executed since the counters were reset, ascending
+    pub fn execution_counts(&self) -> Vec<ExecutionCount> {
+        self.executions
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .map(|(addr, &count)| ExecutionCount {
+                address: addr as u16,
+                count,
+            })
+            .collect()
+    }
+
     // ===== Debugging =====
 
     /// Get a reference to memory (for debugging/display)
-    pub fn memory(&self) -> &[u16; MEMORY_SIZE] {
+    pub fn memory(&self) -> &[u16] {
         &self.memory
     }
 
+    /// Number of words of memory this machine has
+    pub fn memory_words(&self) -> usize {
+        self.memory.len()
+    }
+
+    // ===== Save/Restore =====
+
+    /// Capture the full machine state
+    pub fn save_state(&self) -> SavedState {
+        SavedState {
+            acc: self.acc,
+            ext: self.ext,
+            iar: self.iar,
+            carry: self.carry,
+            overflow: self.overflow,
+            last_overflow: self.last_overflow,
+            halted: self.halted,
+            cycle_count: self.cycle_count,
+            instruction_count: self.instruction_count,
+            memory: self.memory.to_vec(),
+        }
+    }
+
+    /// Replace the machine state with a saved one (breakpoints are kept)
+    pub fn restore_state(&mut self, saved: &SavedState) -> Result<(), CpuError> {
+        if saved.memory.len() != self.memory.len() {
+            return Err(CpuError::InvalidSavedState(format!(
+                "expected {} memory words, got {}",
+                self.memory.len(),
+                saved.memory.len()
+            )));
+        }
+        if saved.iar as usize >= self.memory.len() {
+            return Err(CpuError::IarOutOfBounds(saved.iar));
+        }
+
+        self.acc = saved.acc;
+        self.ext = saved.ext;
+        self.iar = saved.iar;
+        self.carry = saved.carry;
+        self.overflow = saved.overflow;
+        self.last_overflow = saved.last_overflow;
+        self.halted = saved.halted;
+        self.cycle_count = saved.cycle_count;
+        self.instruction_count = saved.instruction_count;
+        self.memory.copy_from_slice(&saved.memory);
+        self.clear_history();
+        Ok(())
+    }
+
     /// Get a slice of memory for a specific range
     pub fn memory_slice(&self, start: u16, len: usize) -> Result<&[u16], CpuError> {
         let start = start as usize;
-        if start + len > MEMORY_SIZE {
+        if start + len > self.memory.len() {
             return Err(CpuError::MemoryOutOfBounds(start as u16));
         }
         Ok(&self.memory[start..start + len])
@@ -377,8 +1133,12 @@ mod tests {
         cpu.increment_iar().unwrap();
         assert_eq!(cpu.iar(), 0x51);
 
-        // Out of bounds
-        assert!(cpu.set_iar(MEMORY_SIZE as u16).is_err());
+        // Out of bounds is rejected and leaves the IAR unchanged
+        assert_eq!(
+            cpu.set_iar(MEMORY_SIZE as u16),
+            Err(CpuError::IarOutOfBounds(MEMORY_SIZE as u16))
+        );
+        assert_eq!(cpu.iar(), 0x51);
     }
 
     #[test]
@@ -426,4 +1186,396 @@ mod tests {
         let too_large = vec![0; MEMORY_SIZE + 1];
         assert!(cpu.load_program(0, &too_large).is_err());
     }
+
+    #[test]
+    fn test_load_assembled() {
+        use crate::assembler::Assembler;
+
+        let source = "ORG 0x10\n\
+                      LD 0 0x30\n\
+                      DATA 0x30 -2\n\
+                      WAIT\n\
+                      ORG 0x40\n\
+                      A 0 0x31\n\
+                      DATA 0x31 7";
+        let program = Assembler::new().assemble(source).unwrap();
+
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+
+        assert_eq!(cpu.read_word(0x10).unwrap(), 0x1030);
+        assert_eq!(cpu.read_word(0x11).unwrap(), 0xF000);
+        assert_eq!(cpu.read_word(0x12).unwrap(), 0);
+        assert_eq!(cpu.read_word(0x30).unwrap(), 0xFFFE);
+        assert_eq!(cpu.read_word(0x31).unwrap(), 7);
+        assert_eq!(cpu.read_word(0x40).unwrap(), 0x5031);
+    }
+
+    #[test]
+    fn test_load_assembled_is_not_a_store() {
+        use crate::assembler::Assembler;
+
+        let program = Assembler::new()
+            .assemble("LD 0 0x30\nWAIT\nDATA 0x30 5")
+            .unwrap();
+        let mut cpu = CpuState::new();
+        cpu.set_watch(0x30);
+        cpu.add_watchpoint(0x30);
+        cpu.load_assembled(&program).unwrap();
+
+        assert_eq!(cpu.read_word(0x30).unwrap(), 5);
+        assert!(cpu.dirty_addresses().is_empty());
+        assert!(cpu.take_changed_watches().is_empty());
+        assert_eq!(cpu.watchpoint_hit(), None);
+
+        // A program that does not fit leaves memory untouched
+        let program = Assembler::new()
+            .assemble("DATA 0x20 7\nORG 0x1000\nWAIT")
+            .unwrap();
+        assert!(cpu.load_assembled(&program).is_err());
+        assert_eq!(cpu.read_word(0x20).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_reset_reload_restarts_assembled_program() {
+        let program = crate::assembler::Assembler::new()
+            .assemble(
+                "ORG 0x20\nMAIN: LD 0 0x30\nA 0 0x30\nSTO 0 0x31\nWAIT\nDATA 0x30 4\nEND MAIN",
+            )
+            .unwrap();
+        let mut cpu = CpuState::new();
+        cpu.load_assembled(&program).unwrap();
+        assert_eq!(
+            cpu.run_until_halt_or_break(100),
+            Ok(crate::cpu::StopReason::Halted)
+        );
+        assert_eq!(cpu.read_word(0x31).unwrap(), 8);
+
+        cpu.reset_reload();
+        assert_eq!(cpu.iar(), 0x20);
+        assert!(!cpu.is_halted());
+        assert_eq!(cpu.read_word(0x20).unwrap(), 0x1030);
+        assert_eq!(
+            cpu.run_until_halt_or_break(100),
+            Ok(crate::cpu::StopReason::Halted)
+        );
+
+        cpu.hard_reset();
+        cpu.reset_reload();
+        assert_eq!(cpu.iar(), PROGRAM_START);
+    }
+
+    #[test]
+    fn test_assemble_and_load_replaces_memory() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(0x100, 0xDEAD).unwrap();
+        cpu.write_acc(7);
+
+        let program = cpu
+            .assemble_and_load(
+                "ORG 0x20\nDATA 0x30 5 6\nMAIN: LD 0 =9\nA 0 0x30\nWAIT\nEND MAIN",
+                0x10,
+            )
+            .unwrap();
+
+        // Memory holds exactly the program image on top of a hard reset
+        for &(addr, word) in &program.to_image() {
+            assert_eq!(cpu.read_word(addr).unwrap(), word);
+        }
+        assert_eq!(cpu.read_word(0x30).unwrap(), 5);
+        assert_eq!(cpu.read_word(0x31).unwrap(), 6);
+        assert_eq!(cpu.read_word(0x100).unwrap(), 0);
+        assert_eq!(cpu.read_word(TRAP_ADDR).unwrap(), TRAP_INSTRUCTION);
+        assert_eq!(cpu.read_acc(), 0);
+        assert_eq!(cpu.iar(), 0x20);
+
+        cpu.run_until_halt_or_break(10).unwrap();
+        assert_eq!(cpu.read_acc(), 14);
+    }
+
+    #[test]
+    fn test_assemble_and_load_error_keeps_machine() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(0x100, 0xBEEF).unwrap();
+        assert!(cpu.assemble_and_load("BOGUS 1", 0x10).is_err());
+        assert_eq!(cpu.read_word(0x100).unwrap(), 0xBEEF);
+
+        // Assembles, but lands past the end of 4K memory
+        assert!(cpu.assemble_and_load("ORG 0x1000\nWAIT", 0x10).is_err());
+        assert_eq!(cpu.read_word(0x100).unwrap(), 0xBEEF);
+    }
+
+    #[test]
+    fn test_snapshot_flags() {
+        let mut cpu = CpuState::new();
+        let flags = |cpu: &CpuState| {
+            let snapshot = CpuSnapshot::capture(cpu);
+            [
+                snapshot.carry,
+                snapshot.overflow,
+                snapshot.positive,
+                snapshot.zero,
+                snapshot.negative,
+            ]
+        };
+
+        assert_eq!(flags(&cpu), [false, false, false, true, false]);
+
+        cpu.write_acc(5);
+        cpu.set_carry(true);
+        assert_eq!(flags(&cpu), [true, false, true, false, false]);
+
+        cpu.write_acc(0x8000);
+        cpu.set_carry(false);
+        cpu.set_overflow(true);
+        assert_eq!(flags(&cpu), [false, true, false, false, true]);
+    }
+
+    #[test]
+    fn test_breakpoint_management() {
+        let mut cpu = CpuState::new();
+
+        cpu.add_breakpoint(0x20);
+        cpu.add_breakpoint(0x30);
+        assert!(cpu.has_breakpoint(0x20));
+
+        assert!(cpu.remove_breakpoint(0x20));
+        assert!(!cpu.remove_breakpoint(0x20));
+        assert!(!cpu.has_breakpoint(0x20));
+
+        cpu.reset();
+        assert!(cpu.has_breakpoint(0x30));
+
+        cpu.clear_breakpoints();
+        assert!(!cpu.has_breakpoint(0x30));
+    }
+
+    #[test]
+    fn test_save_restore_roundtrip() {
+        let mut cpu = CpuState::new();
+        cpu.write_acc(0x1234);
+        cpu.write_ext(0xABCD);
+        cpu.write_xr1(7);
+        cpu.write_word(0x0FFF, 0xBEEF).unwrap();
+        cpu.set_iar(0x42).unwrap();
+        cpu.set_carry(true);
+        cpu.tick();
+        cpu.count_instruction();
+        cpu.halt();
+
+        let json = serde_json::to_string(&cpu.save_state()).unwrap();
+
+        let mut restored = CpuState::new();
+        let saved: SavedState = serde_json::from_str(&json).unwrap();
+        restored.restore_state(&saved).unwrap();
+
+        assert_eq!(restored.save_state(), cpu.save_state());
+        assert_eq!(restored.read_xr1(), 7);
+        assert_eq!(restored.read_word(0x0FFF).unwrap(), 0xBEEF);
+        assert!(restored.is_halted());
+    }
+
+    #[test]
+    fn test_restore_rejects_wrong_memory_size() {
+        let mut cpu = CpuState::new();
+        let mut saved = cpu.save_state();
+        saved.memory.truncate(100);
+        saved.acc = 99;
+
+        assert!(matches!(
+            cpu.restore_state(&saved),
+            Err(CpuError::InvalidSavedState(_))
+        ));
+        assert_eq!(cpu.read_acc(), 0);
+    }
+
+    #[test]
+    fn test_watch_reports_changed_values() {
+        let mut cpu = CpuState::new();
+        cpu.set_watch(0x40);
+        cpu.set_watch(XR2_ADDR);
+
+        cpu.write_word(0x41, 7).unwrap(); // not watched
+        cpu.write_word(0x40, 0).unwrap(); // same value, not a change
+        assert!(cpu.take_changed_watches().is_empty());
+
+        cpu.write_xr2(5);
+        cpu.write_word(0x40, 9).unwrap();
+        assert_eq!(cpu.take_changed_watches(), vec![XR2_ADDR, 0x40]);
+        assert!(cpu.take_changed_watches().is_empty());
+
+        assert!(cpu.clear_watch(0x40));
+        assert!(!cpu.clear_watch(0x40));
+        cpu.write_word(0x40, 1).unwrap();
+        assert!(cpu.take_changed_watches().is_empty());
+    }
+
+    #[test]
+    fn test_dirty_tracking() {
+        let mut cpu = CpuState::new();
+        assert!(cpu.dirty_addresses().is_empty());
+
+        cpu.write_word(0x40, 0).unwrap(); // writes count even if the value is unchanged
+        cpu.write_word(0x20, 5).unwrap();
+        cpu.write_xr1(3);
+        let _ = cpu.read_word(0x30).unwrap();
+        assert_eq!(cpu.dirty_addresses(), vec![XR1_ADDR, 0x20, 0x40]);
+
+        cpu.clear_dirty();
+        assert!(cpu.dirty_addresses().is_empty());
+
+        cpu.write_word(0x50, 1).unwrap();
+        cpu.hard_reset();
+        assert!(cpu.dirty_addresses().is_empty());
+    }
+
+    #[test]
+    fn test_write_words() {
+        let mut cpu = CpuState::new();
+        cpu.write_words(0x20, &[1, 2, 3]).unwrap();
+        assert_eq!(cpu.memory_slice(0x20, 3).unwrap(), &[1, 2, 3]);
+        assert_eq!(cpu.dirty_addresses(), vec![0x20, 0x21, 0x22]);
+
+        let end = (MEMORY_SIZE - 2) as u16;
+        assert_eq!(
+            cpu.write_words(end, &[7, 8, 9]),
+            Err(CpuError::MemoryOutOfBounds(end))
+        );
+        assert_eq!(cpu.memory_slice(end, 2).unwrap(), &[0, 0]);
+        cpu.write_words(end, &[7, 8]).unwrap();
+    }
+
+    #[test]
+    fn test_memory_size_configuration() {
+        let mut big = CpuState::with_memory_words(8192).unwrap();
+        assert_eq!(big.memory_words(), 8192);
+        big.write_word(0x1FFF, 42).unwrap();
+        assert_eq!(big.read_word(0x1FFF).unwrap(), 42);
+        big.set_iar(0x1FFF).unwrap();
+
+        let mut small = CpuState::new();
+        assert_eq!(small.memory_words(), MEMORY_SIZE);
+        assert_eq!(
+            small.write_word(0x1FFF, 42),
+            Err(CpuError::MemoryOutOfBounds(0x1FFF))
+        );
+        assert!(small.read_word(0x1FFF).is_err());
+
+        assert!(CpuState::with_memory_words(MAX_MEMORY_SIZE).is_ok());
+        for words in [0, 2048, 6144, 65536] {
+            assert_eq!(
+                CpuState::with_memory_words(words).unwrap_err(),
+                CpuError::InvalidMemorySize(words)
+            );
+        }
+    }
+
+    #[test]
+    fn test_restore_requires_matching_memory_size() {
+        let big = CpuState::with_memory_words(16384).unwrap();
+        let mut cpu = CpuState::new();
+        assert!(matches!(
+            cpu.restore_state(&big.save_state()),
+            Err(CpuError::InvalidSavedState(_))
+        ));
+    }
+
+    #[test]
+    fn test_address_wrap_masks_to_memory_size() {
+        let mut cpu = CpuState::new();
+        assert_eq!(cpu.address_wrap(), AddressWrap::Trap);
+        assert_eq!(
+            cpu.write_word(MEMORY_SIZE as u16 + 0x20, 0x1234),
+            Err(CpuError::MemoryOutOfBounds(MEMORY_SIZE as u16 + 0x20))
+        );
+
+        cpu.set_address_wrap(AddressWrap::Wrap);
+        cpu.write_word(MEMORY_SIZE as u16 + 0x20, 0x1234).unwrap();
+        assert_eq!(cpu.read_word(0x20).unwrap(), 0x1234);
+        assert_eq!(
+            cpu.read_word(0xFFFF).unwrap(),
+            cpu.read_word(0x0FFF).unwrap()
+        );
+
+        // The mode is configuration and survives a reset
+        cpu.reset();
+        assert_eq!(cpu.address_wrap(), AddressWrap::Wrap);
+    }
+
+    #[test]
+    fn test_cpu_error_display_and_recoverable() {
+        let cases = [
+            (
+                CpuError::MemoryOutOfBounds(0x1000),
+                "Memory access out of bounds: 0x1000",
+                false,
+            ),
+            (CpuError::Halted, "CPU is halted", true),
+            (
+                CpuError::InvalidInstruction(0x0012),
+                "Invalid instruction at 0x0012",
+                false,
+            ),
+            (
+                CpuError::IarOutOfBounds(0xFFFF),
+                "Program counter out of bounds: 0xFFFF",
+                false,
+            ),
+            (
+                CpuError::InvalidSavedState("bad".to_string()),
+                "Invalid saved state: bad",
+                false,
+            ),
+            (
+                CpuError::InvalidMemorySize(100),
+                "Unsupported memory size: 100 words (expected 4K, 8K, 16K or 32K)",
+                false,
+            ),
+        ];
+        for (error, text, recoverable) in cases {
+            assert_eq!(error.to_string(), text);
+            assert_eq!(error.is_recoverable(), recoverable, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn test_hard_reset_seeds_trap_word() {
+        let mut cpu = CpuState::new();
+        cpu.write_word(TRAP_ADDR, 0x1234).unwrap();
+        cpu.hard_reset();
+        assert_eq!(cpu.read_word(TRAP_ADDR).unwrap(), TRAP_INSTRUCTION);
+        assert_eq!(cpu.read_word(1).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_memory_protection_regions() {
+        let mut cpu = CpuState::new();
+        assert_eq!(cpu.memory_protection(), MemoryProtection::TrapWord);
+        assert!(cpu.is_protected(0));
+        assert!(!cpu.is_protected(1));
+
+        cpu.set_memory_protection(MemoryProtection::ReservedWords);
+        assert!(cpu.is_protected(3));
+        assert!(!cpu.is_protected(4));
+
+        cpu.set_memory_protection(MemoryProtection::Disabled);
+        assert!(!cpu.is_protected(0));
+    }
+
+    #[test]
+    fn test_trace_is_bounded() {
+        let mut cpu = CpuState::new();
+        cpu.record_trace(0x10, 0x0000);
+        assert!(cpu.take_trace().is_empty());
+
+        cpu.set_tracing(true);
+        for n in 0..TRACE_DEPTH + 5 {
+            cpu.record_trace(n as u16, 0x0000);
+        }
+        let trace = cpu.take_trace();
+        assert_eq!(trace.len(), TRACE_DEPTH);
+        assert_eq!(trace[0].iar, 5);
+        assert!(cpu.take_trace().is_empty());
+        assert!(cpu.is_tracing());
+    }
 }
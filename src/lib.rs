@@ -11,17 +11,24 @@
 //! - Extension register (EXT) - used for double-precision operations
 //! - Instruction Address Register (IAR) - program counter
 //! - Index registers (XR1-XR3) - memory-mapped at addresses 1, 2, 3
-//! - 256 words of memory (16-bit words)
+//! - 4K words of memory by default, configurable up to 32K (16-bit words)
 //!
 //! ## Instruction Set
 //!
 //! The simplified instruction set includes:
-//! - **Load/Store**: LD, STO, LDX, STX
-//! - **Arithmetic**: A (add), S (subtract)
+//! - **Load/Store**: LD, STO, LDS (load sign-extended), LDD/STD (double word), LDX, STX,
+//!   and the LDI pseudo-instruction (load a literal)
+//! - **Arithmetic**: A (add), S (subtract), AC/SC (with carry), M (multiply), D (divide)
 //! - **Logical**: AND, OR
-//! - **Shift**: SLA (shift left), SRA (shift right arithmetic)
-//! - **Branch**: BSC (branch on condition), BSI (branch and store IAR)
-//! - **Control**: WAIT (halt), NOP
+//! - **Shift**: SLA (shift left), SRA (shift right arithmetic), SRL (shift right logical),
+//!   SLT/SRT (double-word ACC:EXT shifts), XCH (exchange ACC and EXT)
+//! - **Branch**: BSC (branch on condition), SKP (skip on condition), BSI (branch and store IAR),
+//!   RET (return from subroutine), BRL (branch relative)
+//! - **Control**: WAIT (halt), CLRF (clear carry and overflow), LDSTAT/STSTAT (load and store
+//!   the status word), NOP
+//!
+//! Branch conditions are Z, NZ, P, N, V (overflow), C (carry), E (even), DZ (ACC:EXT zero),
+//! LT/GE (signed compare after a subtract) and XN/XZ (EXT negative or zero).
 //!
 //! ## Usage
 //!
@@ -41,7 +48,7 @@ pub mod app;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
 
-pub use assembler::{Assembler, AssemblerError, decode_instruction, encode_instruction};
+pub use assembler::{Assembler, AssemblerError, LineError, decode_instruction, encode_instruction};
 pub use challenge::{
     Challenge, Difficulty, TestCase, TestResult, ValidationResult, get_all_challenges,
 };
@@ -2,8 +2,9 @@
 //!
 //! This module provides JavaScript-accessible functions for the IBM 1130 CPU emulator.
 
-use crate::cpu::{CpuState, Instruction};
-use serde::{Deserialize, Serialize};
+use crate::assembler::AssembledProgram;
+use crate::challenge::{Challenge, ValidationResult, get_all_challenges, run_challenge};
+use crate::cpu::{CpuSnapshot, CpuState, Instruction, MemoryProtection, SavedState, StopReason};
 use wasm_bindgen::prelude::*;
 
 /// WASM-accessible CPU wrapper
@@ -11,23 +12,33 @@ use wasm_bindgen::prelude::*;
 #[derive(Clone)]
 pub struct WasmCpu {
     cpu: CpuState,
+    /// Built-in challenges plus any loaded from JSON
+    challenges: Vec<Challenge>,
 }
 
-/// CPU state snapshot for JavaScript
-#[derive(Serialize, Deserialize)]
-pub struct CpuSnapshot {
-    pub acc: u16,
-    pub ext: u16,
-    pub iar: u16,
-    pub xr1: u16,
-    pub xr2: u16,
-    pub xr3: u16,
-    pub carry: bool,
-    pub overflow: bool,
-    pub halted: bool,
-    pub cycle_count: u64,
-    pub instruction_count: u64,
-    pub memory: Vec<u16>,
+/// Why `WasmCpu::run` stopped
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    /// A WAIT instruction halted the CPU
+    Halted,
+    /// The step budget ran out before the program halted
+    BudgetExhausted,
+    /// Execution reached a breakpoint
+    Breakpoint,
+    /// An instruction wrote a watchpoint address (see `watchpoint_hit`)
+    Watchpoint,
+}
+
+impl From<StopReason> for RunStatus {
+    fn from(reason: StopReason) -> Self {
+        match reason {
+            StopReason::Halted => RunStatus::Halted,
+            StopReason::BudgetExhausted => RunStatus::BudgetExhausted,
+            StopReason::Breakpoint(_) => RunStatus::Breakpoint,
+            StopReason::Watchpoint(_) => RunStatus::Watchpoint,
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -37,15 +48,44 @@ impl WasmCpu {
     pub fn new() -> Self {
         Self {
             cpu: CpuState::new(),
+            challenges: get_all_challenges(),
         }
     }
 
+    /// Create a CPU with 4K, 8K, 16K or 32K words of memory
+    pub fn with_memory_words(words: usize) -> Result<WasmCpu, JsValue> {
+        let cpu =
+            CpuState::with_memory_words(words).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(Self {
+            cpu,
+            challenges: get_all_challenges(),
+        })
+    }
+
+    /// Number of words of memory this machine has
+    pub fn memory_words(&self) -> usize {
+        self.cpu.memory_words()
+    }
+
     /// Reset CPU to initial state (keeps program in memory)
     pub fn reset(&mut self) {
         self.cpu.reset();
     }
 
-    /// Hard reset - clears all memory
+    /// Reset registers to run the last assembled program again from its entry point
+    ///
+    /// Memory is kept, including any words the program changed. Without an
+    /// assembled program this is a plain `reset`.
+    pub fn reset_reload(&mut self) {
+        self.cpu.reset_reload();
+    }
+
+    /// Zero the cycle and instruction counters only
+    pub fn reset_counters(&mut self) {
+        self.cpu.reset_counters();
+    }
+
+    /// Hard reset - clears all memory, including the assembled program
     pub fn hard_reset(&mut self) {
         self.cpu.hard_reset();
     }
@@ -58,54 +98,203 @@ impl WasmCpu {
     }
 
     /// Execute a single instruction at current IAR
+    ///
+    /// The IAR advances past the instruction unless it was a taken branch.
     pub fn step(&mut self, opcode: u16) -> Result<(), JsValue> {
         let instr = self.decode(opcode)?;
         self.cpu
-            .execute(&instr)
+            .record_step(|cpu| {
+                let iar = cpu.iar();
+                let branched = cpu.execute(&instr)?;
+                cpu.record_trace(iar, opcode);
+                if branched {
+                    return Ok(());
+                }
+                cpu.increment_iar()
+            })
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Execute the instruction at the current IAR
+    ///
+    /// Returns `{mnemonic, address, branched, halted}` describing the step.
+    /// The IAR is left at the target of a taken branch.
+    pub fn step_auto(&mut self) -> Result<JsValue, JsValue> {
+        let record = self
+            .cpu
+            .step_traced()
             .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_wasm_bindgen::to_value(&record).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Run until WAIT instruction, breakpoint, watchpoint write, or error
+    ///
+    /// Returns why execution stopped; `BudgetExhausted` means the program
+    /// did not halt within `max_cycles` instructions.
+    pub fn run(&mut self, max_cycles: u64) -> Result<RunStatus, JsValue> {
+        self.cpu
+            .run_until_halt_or_break(max_cycles)
+            .map(RunStatus::from)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Step over the instruction at the IAR, running a `BSI` subroutine until it returns
+    ///
+    /// Other instructions execute as a single step. `max_cycles` guards against
+    /// a subroutine that never returns.
+    pub fn step_over(&mut self, max_cycles: u64) -> Result<RunStatus, JsValue> {
         self.cpu
-            .increment_iar()
+            .step_over(max_cycles)
+            .map(RunStatus::from)
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
-    /// Run until WAIT instruction or error
-    pub fn run(&mut self, max_cycles: u64) -> Result<(), JsValue> {
-        for _ in 0..max_cycles {
-            if self.cpu.is_halted() {
-                break;
-            }
+    /// Run up to `n` instructions, stopping early on WAIT, a breakpoint or a watchpoint
+    ///
+    /// Returns `{executed, halted, watchpoint}` so the caller can re-render between batches.
+    pub fn run_steps(&mut self, n: u64) -> Result<JsValue, JsValue> {
+        let summary = self
+            .cpu
+            .run_steps(n)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_wasm_bindgen::to_value(&summary).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-            let iar = self.cpu.iar();
-            let opcode = self
-                .cpu
-                .read_word(iar)
-                .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    /// Turn the instruction trace on or off
+    pub fn set_tracing(&mut self, enabled: bool) {
+        self.cpu.set_tracing(enabled);
+    }
 
-            self.step(opcode)?;
-        }
-        Ok(())
+    /// Return and clear the instruction trace
+    ///
+    /// Returns an array of `{iar, opcode, acc}` entries, oldest first, with
+    /// `acc` as it stood after the instruction ran.
+    pub fn get_trace(&mut self) -> Result<JsValue, JsValue> {
+        let trace = self.cpu.take_trace();
+        serde_wasm_bindgen::to_value(&trace).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// How often each address has executed since the counters were reset
+    ///
+    /// Returns an array of `{address, count}` entries for the addresses that
+    /// ran at least once, in ascending address order.
+    pub fn execution_counts(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.cpu.execution_counts())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Stores the program made into its own code since it was loaded
+    ///
+    /// Returns an array of `{address, iar, value}` entries, oldest first:
+    /// the overwritten address, the storing instruction, and the word stored.
+    pub fn self_modifications(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(self.cpu.self_modifications())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Turn step history on or off so `step_back` can undo recent steps
+    pub fn set_history_enabled(&mut self, enabled: bool) {
+        self.cpu.set_history_enabled(enabled);
+    }
+
+    /// Undo the most recent step; returns false if there is nothing to undo
+    pub fn step_back(&mut self) -> bool {
+        self.cpu.step_back()
+    }
+
+    /// Number of steps that can currently be undone
+    pub fn history_len(&self) -> usize {
+        self.cpu.history_len()
+    }
+
+    /// Turn protection of location 0 on or off
+    ///
+    /// While on (the default), a program that executes or stores at location 0
+    /// stops with a protection fault; while off, a wild branch there spins on
+    /// the self-branch `hard_reset` leaves in it.
+    pub fn set_protection_enabled(&mut self, enabled: bool) {
+        self.cpu.set_memory_protection(if enabled {
+            MemoryProtection::TrapWord
+        } else {
+            MemoryProtection::Disabled
+        });
+    }
+
+    /// Set a breakpoint at an address
+    pub fn add_breakpoint(&mut self, addr: u16) {
+        self.cpu.add_breakpoint(addr);
+    }
+
+    /// Remove a breakpoint; returns true if one was set
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        self.cpu.remove_breakpoint(addr)
+    }
+
+    /// Remove all breakpoints
+    pub fn clear_breakpoints(&mut self) {
+        self.cpu.clear_breakpoints();
+    }
+
+    /// Watch an address; writes that change its value are reported by `take_changed_watches`
+    pub fn set_watch(&mut self, addr: u16) {
+        self.cpu.set_watch(addr);
+    }
+
+    /// Stop watching an address; returns true if it was watched
+    pub fn clear_watch(&mut self, addr: u16) -> bool {
+        self.cpu.clear_watch(addr)
+    }
+
+    /// Return and clear the watched addresses that changed since the last call
+    pub fn take_changed_watches(&mut self) -> Vec<u16> {
+        self.cpu.take_changed_watches()
+    }
+
+    /// Set a watchpoint; `run` and `run_steps` stop after an instruction writes `addr`
+    pub fn set_watchpoint(&mut self, addr: u16) {
+        self.cpu.add_watchpoint(addr);
+    }
+
+    /// Remove a watchpoint; returns true if one was set
+    pub fn clear_watchpoint(&mut self, addr: u16) -> bool {
+        self.cpu.remove_watchpoint(addr)
+    }
+
+    /// Address of the watchpoint that stopped the last run, if any
+    pub fn watchpoint_hit(&self) -> Option<u16> {
+        self.cpu.watchpoint_hit()
+    }
+
+    /// Addresses written since the last `clear_dirty`, in ascending order
+    pub fn dirty_addresses(&self) -> Vec<u16> {
+        self.cpu.dirty_addresses()
+    }
+
+    /// Forget which addresses have been written
+    pub fn clear_dirty(&mut self) {
+        self.cpu.clear_dirty();
     }
 
     /// Get CPU state as JSON
     pub fn get_state(&self) -> Result<JsValue, JsValue> {
-        let snapshot = CpuSnapshot {
-            acc: self.cpu.read_acc(),
-            ext: self.cpu.read_ext(),
-            iar: self.cpu.iar(),
-            xr1: self.cpu.read_xr1(),
-            xr2: self.cpu.read_xr2(),
-            xr3: self.cpu.read_xr3(),
-            carry: self.cpu.carry(),
-            overflow: self.cpu.overflow(),
-            halted: self.cpu.is_halted(),
-            cycle_count: self.cpu.cycle_count(),
-            instruction_count: self.cpu.instruction_count(),
-            memory: self.cpu.memory().to_vec(),
-        };
-
+        let snapshot = CpuSnapshot::capture(&self.cpu);
         serde_wasm_bindgen::to_value(&snapshot).map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
+    /// Export the full machine state (registers, flags, counters, memory) as JSON
+    pub fn export_state(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.cpu.save_state()).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Replace the machine state with one produced by `export_state`
+    pub fn import_state(&mut self, json: String) -> Result<(), JsValue> {
+        let saved: SavedState =
+            serde_json::from_str(&json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.cpu
+            .restore_state(&saved)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Read a word from memory
     pub fn read_memory(&self, addr: u16) -> Result<u16, JsValue> {
         self.cpu
@@ -120,11 +309,33 @@ impl WasmCpu {
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
+    /// Write consecutive words starting at `start` in one call
+    ///
+    /// Nothing is written if the block would run past the end of memory.
+    pub fn write_memory_block(&mut self, start: u16, values: Vec<u16>) -> Result<(), JsValue> {
+        self.cpu
+            .write_words(start, &values)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Read `len` consecutive words starting at `start`
+    pub fn read_memory_block(&self, start: u16, len: usize) -> Result<Vec<u16>, JsValue> {
+        self.cpu
+            .memory_slice(start, len)
+            .map(<[u16]>::to_vec)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Get accumulator value
     pub fn get_acc(&self) -> u16 {
         self.cpu.read_acc()
     }
 
+    /// Set accumulator value (the console's LOAD ACC)
+    pub fn set_acc(&mut self, value: u16) {
+        self.cpu.write_acc(value);
+    }
+
     /// Get extension register value
     pub fn get_ext(&self) -> u16 {
         self.cpu.read_ext()
@@ -135,6 +346,13 @@ impl WasmCpu {
         self.cpu.iar()
     }
 
+    /// Set the instruction address register (console LOAD IAR)
+    pub fn set_iar(&mut self, addr: u16) -> Result<(), JsValue> {
+        self.cpu
+            .set_iar(addr)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Get index register 1
     pub fn get_xr1(&self) -> u16 {
         self.cpu.read_xr1()
@@ -155,6 +373,16 @@ impl WasmCpu {
         self.cpu.is_halted()
     }
 
+    /// Halt the CPU (console PROG STOP)
+    pub fn halt(&mut self) {
+        self.cpu.halt();
+    }
+
+    /// Clear the halted state so execution can continue (console PROG START)
+    pub fn resume(&mut self) {
+        self.cpu.resume();
+    }
+
     /// Get cycle count
     pub fn get_cycle_count(&self) -> u64 {
         self.cpu.cycle_count()
@@ -165,66 +393,102 @@ impl WasmCpu {
         self.cpu.instruction_count()
     }
 
-    /// Assemble source code and load into memory
-    pub fn assemble(&mut self, source: String, start_addr: u16) -> Result<JsValue, JsValue> {
+    /// Assemble source code and load instructions and data into memory
+    ///
+    /// Assembly starts at `start_addr` (default `PROGRAM_START`) unless the
+    /// source sets an `ORG`; execution starts at the program's `END` entry
+    /// point, or at `start_addr` if it has none. Returns `{listing, symbols,
+    /// warnings}`; warnings are `{line, message}` and do not stop loading.
+    pub fn assemble(
+        &mut self,
+        source: String,
+        start_addr: Option<u16>,
+    ) -> Result<JsValue, JsValue> {
         use crate::assembler::Assembler;
 
-        let mut assembler = Assembler::new();
+        let start_addr = start_addr.unwrap_or(crate::cpu::PROGRAM_START);
+        let mut assembler = Assembler::with_origin(start_addr);
         let program = assembler
             .assemble(&source)
             .map_err(|e| JsValue::from_str(&e.to_string()))?;
 
-        // Load program into memory
         self.cpu
-            .load_program(start_addr, &program.code)
+            .load_assembled(&program)
             .map_err(|e| JsValue::from_str(&e.to_string()))?;
 
-        // Convert listing to JSON for JavaScript
-        #[derive(serde::Serialize)]
-        struct ListingLine {
-            address: u16,
-            opcode: String,
-            source: String,
-        }
+        assemble_result(program)
+    }
 
-        // Adjust listing addresses to match where code was actually loaded
-        let addr_offset = start_addr.wrapping_sub(program.start_addr);
+    /// Hard-reset, then assemble `source` and load it into the cleared machine
+    ///
+    /// Like `assemble`, but nothing from an earlier program survives: memory
+    /// holds only the new image (instructions, DATA, TEXT and literals) and the
+    /// IAR is at the entry point. A failed assembly, or a program that does not
+    /// fit in memory, leaves the machine as it was. Returns `{listing, symbols, warnings}`.
+    pub fn assemble_and_load(
+        &mut self,
+        source: String,
+        start_addr: u16,
+    ) -> Result<JsValue, JsValue> {
+        let program = self
+            .cpu
+            .assemble_and_load(&source, start_addr)
+            .map_err(|e| JsValue::from_str(&e))?;
+        assemble_result(program)
+    }
 
-        let listing: Vec<ListingLine> = program
-            .listing
-            .iter()
-            .map(|line| ListingLine {
-                address: line.address.wrapping_add(addr_offset), // Adjust address
-                opcode: format!("0x{:04X}", line.opcode),
-                source: line.source.clone(),
-            })
-            .collect();
+    /// Disassemble `count` words of memory starting at `start`
+    ///
+    /// Returns an array of `{address, word, text}` entries; the range is
+    /// clamped to the end of memory.
+    pub fn disassemble_range(&self, start: u16, count: u16) -> Result<JsValue, JsValue> {
+        let words = crate::assembler::disassemble_range(self.cpu.memory(), start, count);
+        serde_wasm_bindgen::to_value(&words).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-        serde_wasm_bindgen::to_value(&listing).map_err(|e| JsValue::from_str(&e.to_string()))
+    /// Plain-text listing of the loaded program, from `PROGRAM_START` to the first WAIT
+    pub fn listing_text(&self) -> String {
+        crate::assembler::listing_text(self.cpu.memory(), crate::cpu::PROGRAM_START)
     }
 
     /// Get all available challenges
     pub fn get_challenges(&self) -> Result<JsValue, JsValue> {
-        use crate::challenge::get_all_challenges;
-
-        let challenges = get_all_challenges();
-        serde_wasm_bindgen::to_value(&challenges).map_err(|e| JsValue::from_str(&e.to_string()))
+        serde_wasm_bindgen::to_value(&self.challenges)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
-    /// Check current CPU state against a challenge
-    pub fn check_challenge(&self, challenge_id: u32) -> Result<JsValue, JsValue> {
-        use crate::challenge::get_all_challenges;
-
-        let challenges = get_all_challenges();
-        let challenge = challenges
-            .iter()
-            .find(|c| c.id == challenge_id)
-            .ok_or_else(|| JsValue::from_str(&format!("Challenge {} not found", challenge_id)))?;
+    /// Load challenges from a JSON array
+    ///
+    /// With `replace` the built-in challenges are discarded; otherwise the new
+    /// challenges are added to the current set. IDs must stay unique.
+    pub fn load_challenges_from_json(
+        &mut self,
+        json: String,
+        replace: bool,
+    ) -> Result<(), JsValue> {
+        use crate::challenge::{challenges_from_json, validate_challenges};
+
+        let loaded = challenges_from_json(&json).map_err(|e| JsValue::from_str(&e))?;
+        let mut combined = if replace {
+            Vec::new()
+        } else {
+            self.challenges.clone()
+        };
+        combined.extend(loaded);
+        validate_challenges(&combined).map_err(|e| JsValue::from_str(&e))?;
 
-        let result = challenge
-            .validate_solution(&self.cpu)
-            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.challenges = combined;
+        Ok(())
+    }
 
+    /// Assemble `source` and run it against every test case of a challenge
+    ///
+    /// Each test case runs on a freshly reset machine holding its initial
+    /// memory and the program, so the CPU on display is left untouched.
+    pub fn run_challenge(&self, challenge_id: u32, source: String) -> Result<JsValue, JsValue> {
+        let result = self
+            .run_challenge_solution(challenge_id, &source)
+            .map_err(|e| JsValue::from_str(&e))?;
         serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
@@ -246,6 +510,81 @@ impl WasmCpu {
     pub fn cpu_state(&self) -> &CpuState {
         &self.cpu
     }
+
+    /// Native form of `run_challenge`
+    pub fn run_challenge_solution(
+        &self,
+        challenge_id: u32,
+        source: &str,
+    ) -> Result<ValidationResult, String> {
+        run_challenge(
+            &self.challenges,
+            challenge_id,
+            source,
+            self.cpu.memory_words(),
+        )
+    }
+}
+
+/// Convert an assembled program to the `{listing, symbols, warnings}` JSON of `assemble`
+fn assemble_result(program: AssembledProgram) -> Result<JsValue, JsValue> {
+    #[derive(serde::Serialize)]
+    struct ListingLine {
+        address: u16,
+        opcode: String,
+        source: String,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Symbol {
+        name: String,
+        value: u16,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Warning {
+        line: usize,
+        message: String,
+    }
+
+    #[derive(serde::Serialize)]
+    struct AssembleResult {
+        listing: Vec<ListingLine>,
+        symbols: Vec<Symbol>,
+        warnings: Vec<Warning>,
+    }
+
+    let listing: Vec<ListingLine> = program
+        .listing
+        .iter()
+        .map(|line| ListingLine {
+            address: line.address,
+            opcode: format!("0x{:04X}", line.opcode),
+            source: line.source.clone(),
+        })
+        .collect();
+
+    let symbols = program
+        .symbols
+        .into_iter()
+        .map(|(name, value)| Symbol { name, value })
+        .collect();
+
+    let warnings = program
+        .warnings
+        .iter()
+        .map(|warning| Warning {
+            line: warning.line(),
+            message: warning.to_string(),
+        })
+        .collect();
+
+    serde_wasm_bindgen::to_value(&AssembleResult {
+        listing,
+        symbols,
+        warnings,
+    })
+    .map_err(|e| JsValue::from_str(&e.to_string()))
 }
 
 /// Initialize WASM module and mount Yew app